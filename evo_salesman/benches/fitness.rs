@@ -0,0 +1,37 @@
+// Benchmark of the fitness evaluation of the salesman on fixed-seed cities
+// Run by `cargo bench --bench fitness`, throughput is reported in evaluations per second
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use evo_salesman::salesman::SalesmanIndividual;
+use evo_salesman::salesman_data::{SalesmanIndividualData, SalesmanInitType};
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use revo::evo_individual::EvoIndividual;
+
+const N_CITIES: [u32; 3] = [100, 300, 1000];
+
+fn bench_count_fitness(c: &mut Criterion) {
+    let mut group = c.benchmark_group("salesman_count_fitness");
+    group.throughput(Throughput::Elements(1));
+    for n_cities in N_CITIES {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let ind_data = SalesmanIndividualData::new_with_rng(
+            &mut rng,
+            n_cities,
+            1000,
+            1000,
+            0.3,
+            0.3,
+            SalesmanInitType::Noise,
+        );
+        let mut ind = SalesmanIndividual::new_randomised(&ind_data, &mut rng);
+
+        group.bench_function(BenchmarkId::from_parameter(n_cities), |b| {
+            b.iter(|| ind.count_fitness(&ind_data))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_count_fitness);
+criterion_main!(benches);
@@ -3,53 +3,129 @@ extern crate revo;
 
 use evo_salesman::salesman::SalesmanIndividual;
 use evo_salesman::salesman_data::SalesmanIndividualData;
-use revo::config::{Config, DEFAULT_CONFIG_FILENAME};
-use revo::evo_individual::{EvoIndividual, Visualise};
-use revo::population::Population;
-use std::fs;
+use revo::animation::AnimationExporter;
+use revo::cli::{or_exit, CliArgs, HALL_OF_FAME_FILENAME};
+use revo::evo_individual::Visualise;
+use revo::genealogy::Operator;
+#[cfg(feature = "monitor")]
+use revo::monitor::Monitor;
+use revo::population::{Population, VisualisationMode};
 
 fn main() {
-    // Prepare output directory and remove old files if they exist
-    let output_dir = "./out";
-    let _ = fs::remove_dir_all(output_dir);
-    fs::create_dir(output_dir).unwrap();
+    // Parse the command line and prepare the output directory, existing output is kept unless overwriting is allowed
+    let args = CliArgs::from_env();
+    or_exit(args.prepare_output_dir());
 
     // Load the population config and create the individual data
-    let config = Config::new(DEFAULT_CONFIG_FILENAME);
-    let visualise = config.may_get_bool("visualise").unwrap().unwrap_or(false);
+    let config = or_exit(args.load_config());
+    let visualise = VisualisationMode::from_config(&config).unwrap().is_some();
 
-    // Create the population
+    // Create the population, resumed run is replayed up to its last logged generation
     let mut pop: Population<SalesmanIndividual, SalesmanIndividualData> = Population::new(&config);
+    let mut logger = or_exit(args.start_run(&config, &mut pop));
+    // Resumed run keeps the hall of fame exported by the interrupted run
+    or_exit(args.resume_hall_of_fame(&mut pop));
 
-    // Get the best individual
-    let mut all_best_ind = pop.get_best().clone();
+    // Population maps are streamed into a single animation, resumed run writes a new segment of it
+    let mut pop_exporter = if visualise {
+        Some(or_exit(AnimationExporter::from_config(
+            &config,
+            &args.animation_stem("population", pop.get_generation()),
+        )))
+    } else {
+        None
+    };
 
-    // Run the evolution
-    loop {
-        let best_ind = pop.get_best();
+    // Renders of the best individual are streamed into another animation
+    let mut best_exporter = if config.may_get_bool("export_best").unwrap().unwrap_or(false) {
+        Some(or_exit(AnimationExporter::from_config(
+            &config,
+            &args.animation_stem("best", pop.get_generation()),
+        )))
+    } else {
+        None
+    };
 
-        if best_ind.get_fitness() > all_best_ind.get_fitness() {
-            all_best_ind = best_ind.clone();
+    // Run can be observed and controlled over HTTP when built with the monitor feature
+    #[cfg(feature = "monitor")]
+    let monitor = if config.may_get_bool("monitor").unwrap().unwrap_or(false) {
+        let monitor = or_exit(Monitor::from_config(&config));
+        args.info(&format!(
+            "Monitor running at http://{}",
+            monitor.get_address()
+        ));
+        Some(monitor)
+    } else {
+        None
+    };
 
-            println!(
+    // Run the evolution
+    while args.should_continue(pop.get_generation()) {
+        // Best individual ever seen is new if it entered the hall of fame in this generation
+        let hall_of_fame = pop.get_hall_of_fame();
+        if let Some(best) = hall_of_fame
+            .get_best()
+            .filter(|best| best.generation == pop.get_generation())
+        {
+            args.info(&format!(
                 "Round {}, best fitness: {}",
                 pop.get_generation(),
-                all_best_ind.get_fitness()
-            );
-            let image = all_best_ind.visualise(pop.get_individual_data());
+                best.fitness
+            ));
+            let image = best.individual.visualise(pop.get_individual_data());
             image
-                .save(format!("{}/best_{}.png", output_dir, pop.get_generation()).as_str())
+                .save(args.output_path(&format!("best_{}.png", pop.get_generation())))
                 .unwrap();
         }
+        args.debug(&format!("{:?}", pop.get_stats()));
 
-        if visualise {
-            let image = pop.visualise();
-            image
-                .save(format!("{}/pop_{}.png", output_dir, pop.get_generation()))
-                .unwrap();
+        or_exit(logger.log(&pop));
+        if let Some(exporter) = &mut pop_exporter {
+            or_exit(exporter.add_population(&pop));
+        }
+        if let Some(exporter) = &mut best_exporter {
+            or_exit(exporter.add_best(&pop));
+        }
+
+        // Log is flushed every generation, so checkpoint only writes the hall of fame that is otherwise written at the end
+        #[cfg(feature = "monitor")]
+        if let Some(monitor) = &monitor {
+            monitor.update_with_best(&pop);
+            if monitor.take_checkpoint_request() {
+                or_exit(pop.get_hall_of_fame().write_json(
+                    &args.output_path(HALL_OF_FAME_FILENAME),
+                    pop.get_individual_data(),
+                ));
+                args.info(&format!("Checkpoint at round {}", pop.get_generation()));
+            }
         }
 
         // Advance to the next generation
         pop.next_gen();
     }
+
+    // Hall of fame is written first, so it is kept even if an animation fails
+    or_exit(pop.get_hall_of_fame().write_json(
+        &args.output_path(HALL_OF_FAME_FILENAME),
+        pop.get_individual_data(),
+    ));
+    for exporter in [pop_exporter, best_exporter].into_iter().flatten() {
+        or_exit(exporter.finish());
+    }
+
+    // Export the ancestry of the best individual and report whether the operators help
+    if let (Some(genealogy), Some(best_id)) = (pop.get_genealogy(), pop.get_best_id()) {
+        or_exit(genealogy.write_ancestry(&args.output_path("genealogy.dot"), best_id));
+        or_exit(genealogy.write_ancestry(&args.output_path("genealogy.json"), best_id));
+
+        for operator in [Operator::Mutation, Operator::Crossover] {
+            let stats = genealogy.get_operator_stats(operator);
+            args.info(&format!(
+                "{}: {} offspring, success rate: {:.4}",
+                operator,
+                stats.n_offspring,
+                stats.success_rate()
+            ));
+        }
+    }
 }
@@ -5,139 +5,26 @@ use imageproc::rect::Rect;
 use rand::prelude::SmallRng;
 use rand::seq::SliceRandom;
 use rand::Rng;
-use revo::evo_individual::{EvoIndividual, Visualise};
+use revo::evo_individual::{EvoIndividual, RemoteIndividual, Visualise};
+use revo::permutation::{self, PermutationIndividual};
 use revo::utils::Coord;
-use std::collections::HashSet;
+use serde_json::Value;
 
+// Tour through the cities, the genome and the crossover are the ones of the permutation individual
 #[derive(Clone)]
 pub struct SalesmanIndividual {
-    pub fitness: f64,
-    genom: Vec<u16>,
+    pub tour: PermutationIndividual<u16>,
 }
 
 impl SalesmanIndividual {
-    fn reverse_part(&mut self, from: usize, to: usize) {
-        let len = self.genom.len();
-
-        let to = match from < to {
-            true => to,
-            false => to + len,
-        };
-
-        let mut frmi = from;
-        let mut toi = to;
-
-        while frmi <= toi {
-            let abs_toi = toi % len;
-            let abs_frmi = frmi % len;
-
-            self.genom.swap(abs_frmi, abs_toi);
-
-            frmi += 1;
-            toi -= 1;
-        }
-    }
-
-    fn shift_multiple(&mut self, from: usize, to: usize, shift: usize) {
-        let len = self.genom.len();
-        let mut i_from = from;
-        let mut i_to = to + 1;
-
-        let slice_len = if to < from {
-            i_to + len - i_from
-        } else {
-            i_to - i_from
-        };
-
-        let mut tmp: Vec<u16> = Vec::with_capacity(slice_len);
-
-        let mut source_i = i_from;
-        for _ in 0..slice_len {
-            tmp.push(self.genom[source_i]);
-
-            source_i += 1;
-            if source_i >= len {
-                source_i = 0
-            };
-        }
-
-        // Do shifting
-        for _ in 0..shift {
-            self.genom.swap(i_from, i_to);
-
-            i_from += 1;
-            if i_from >= len {
-                i_from = 0;
-            }
-
-            i_to += 1;
-            if i_to >= len {
-                i_to = 0;
-            }
-        }
-
-        // Put stuff back
-        let mut source_i = i_from;
-        for item in tmp.iter().take(slice_len) {
-            self.genom[source_i] = *item;
-
-            source_i += 1;
-            if source_i >= len {
-                source_i = 0
-            };
+    fn _from_genes(genes: Vec<u16>) -> Self {
+        SalesmanIndividual {
+            tour: PermutationIndividual::new(genes),
         }
     }
 
-    fn _impl_crossover_to(
-        &self,
-        another_ind: &SalesmanIndividual,
-        dest_ind: &mut SalesmanIndividual,
-        start_cross_point: usize,
-        end_cross_point: usize,
-        other_ind_start_cross_point: usize,
-    ) {
-        let mut used: Vec<bool> = vec![false; self.genom.len()];
-
-        let mut i = start_cross_point;
-
-        // Copy points from first parent
-        loop {
-            dest_ind.genom[i] = self.genom[i];
-            used[self.genom[i] as usize] = true;
-
-            if i == end_cross_point {
-                break;
-            }
-
-            i += 1;
-            if i >= self.genom.len() {
-                i = 0;
-            }
-        }
-
-        // Copy points from second parent
-        let mut other_i = other_ind_start_cross_point;
-        let mut i = end_cross_point + 1;
-        loop {
-            if !used[another_ind.genom[other_i] as usize] {
-                dest_ind.genom[i] = another_ind.genom[other_i];
-                used[another_ind.genom[other_i] as usize] = true;
-
-                i += 1;
-                if i >= self.genom.len() {
-                    i = 0;
-                }
-            }
-
-            if i == start_cross_point {
-                break;
-            }
-
-            other_i += 1;
-            if other_i >= self.genom.len() {
-                other_i = 0;
-            }
-        }
+    pub fn get_genes(&self) -> &[u16] {
+        self.tour.get_genes()
     }
 
     fn new_random_naive(ind_data: &SalesmanIndividualData, rng: &mut SmallRng) -> Self {
@@ -166,35 +53,29 @@ impl SalesmanIndividual {
             genom[i] = closest_j as u16;
         }
 
-        SalesmanIndividual {
-            genom,
-            fitness: 0.0,
-        }
+        Self::_from_genes(genom)
     }
 
     fn new_random_noise(ind_data: &SalesmanIndividualData, rng: &mut SmallRng) -> Self {
         let mut genom: Vec<u16> = (0_u16..ind_data.coords.len() as u16).collect();
         genom.shuffle(rng);
 
-        SalesmanIndividual {
-            genom,
-            fitness: 0.0,
-        }
+        Self::_from_genes(genom)
     }
 
     fn new_random_insertion(ind_data: &SalesmanIndividualData, rng: &mut SmallRng) -> Self {
-        let mut cities: HashSet<u16> = (0_u16..ind_data.coords.len() as u16).collect();
+        // Cities are kept in a vector, so the same random numbers select the same cities
+        let mut cities: Vec<u16> = (0_u16..ind_data.coords.len() as u16).collect();
         let mut genom: Vec<u16> = Vec::new();
 
         for _ in 0..3 {
             // Select random city
-            let available_cities: Vec<u16> = cities.clone().into_iter().collect();
-            let selected_city = if available_cities.len() > 1 {
-                available_cities[rng.gen_range(0..available_cities.len() - 1)]
+            let selected_i = if cities.len() > 1 {
+                rng.gen_range(0..cities.len() - 1)
             } else {
-                available_cities[0]
+                0
             };
-            cities.remove(&selected_city);
+            let selected_city = cities.swap_remove(selected_i);
 
             // Insert random city to genom
             genom.push(selected_city);
@@ -202,13 +83,12 @@ impl SalesmanIndividual {
 
         for _ in 3..ind_data.coords.len() {
             // Select random city
-            let available_cities: Vec<u16> = cities.clone().into_iter().collect();
-            let selected_city = if available_cities.len() > 1 {
-                available_cities[rng.gen_range(0..available_cities.len() - 1)]
+            let selected_i = if cities.len() > 1 {
+                rng.gen_range(0..cities.len() - 1)
             } else {
-                available_cities[0]
+                0
             };
-            cities.remove(&selected_city);
+            let selected_city = cities.swap_remove(selected_i);
 
             let city_1 = &ind_data.coords[genom[0] as usize];
             let city_2 = &ind_data.coords[selected_city as usize];
@@ -234,10 +114,7 @@ impl SalesmanIndividual {
             genom.insert(shortest_j, selected_city);
         }
 
-        SalesmanIndividual {
-            genom,
-            fitness: 0.0,
-        }
+        Self::_from_genes(genom)
     }
 
     fn new_random_greedy_joining(ind_data: &SalesmanIndividualData, rng: &mut SmallRng) -> Self {
@@ -304,10 +181,7 @@ impl SalesmanIndividual {
             }
         }
 
-        SalesmanIndividual {
-            genom: paths[0].clone(),
-            fitness: 0.0,
-        }
+        Self::_from_genes(paths[0].clone())
     }
 }
 
@@ -321,6 +195,7 @@ impl EvoIndividual<SalesmanIndividualData> for SalesmanIndividual {
         }
     }
 
+    // Shifting, reversing and swapping are applied independently, swapping with the mutation probability
     fn mutate(
         &mut self,
         ind_data: &SalesmanIndividualData,
@@ -328,30 +203,33 @@ impl EvoIndividual<SalesmanIndividualData> for SalesmanIndividual {
         mut_prob: f32,
         _mut_amount: f32,
     ) {
+        let genes = &mut self.tour.genes;
+        if genes.len() < 3 {
+            return;
+        }
+
         // Shifting
         if rng.gen_range(0.0..1.0) < ind_data.shift_prob {
-            self.shift_multiple(
-                rng.gen_range(0..self.genom.len() - 1),
-                rng.gen_range(0..self.genom.len() - 1),
-                rng.gen_range(1..self.genom.len() - 1),
-            );
+            let from = rng.gen_range(0..genes.len() - 1);
+            let to = rng.gen_range(0..genes.len() - 1);
+            let shift = rng.gen_range(1..genes.len() - 1);
+            permutation::shift_multiple(genes, from, to, shift);
         }
 
         // Reversing
         if rng.gen_range(0.0..1.0) < ind_data.rev_prob {
-            self.reverse_part(
-                rng.gen_range(0..self.genom.len() - 1),
-                rng.gen_range(0..self.genom.len() - 1),
-            );
+            let from = rng.gen_range(0..genes.len() - 1);
+            let to = rng.gen_range(0..genes.len() - 1);
+            permutation::reverse_part(genes, from, to);
         }
 
         // Swapping
         if rng.gen_range(0.0..1.0) < mut_prob {
-            let i = rng.gen_range(0..self.genom.len() - 1);
-            let j = rng.gen_range(0..self.genom.len() - 1);
+            let i = rng.gen_range(0..genes.len() - 1);
+            let j = rng.gen_range(0..genes.len() - 1);
 
             if i != j {
-                self.genom.swap(i, j);
+                genes.swap(i, j);
             }
         }
     }
@@ -359,63 +237,58 @@ impl EvoIndividual<SalesmanIndividualData> for SalesmanIndividual {
     fn crossover(
         &self,
         another_ind: &SalesmanIndividual,
-        _ind_data: &SalesmanIndividualData,
+        ind_data: &SalesmanIndividualData,
         rng: &mut SmallRng,
     ) -> SalesmanIndividual {
-        let start_cross_point = rng.gen_range(0..self.genom.len() - 1);
-        let end_cross_point = rng.gen_range(0..self.genom.len() - 1);
-        let other_i = rng.gen_range(0..self.genom.len() - 1);
-
-        let mut dest_ind = self.clone();
-
-        self._impl_crossover_to(
-            another_ind,
-            &mut dest_ind,
-            start_cross_point,
-            end_cross_point,
-            other_i,
-        );
+        SalesmanIndividual {
+            tour: self
+                .tour
+                .crossover(&another_ind.tour, &ind_data.perm_data, rng),
+        }
+    }
 
-        dest_ind
+    fn crossover_into(
+        &self,
+        another_ind: &SalesmanIndividual,
+        ind_data: &SalesmanIndividualData,
+        rng: &mut SmallRng,
+        dest: &mut SalesmanIndividual,
+    ) {
+        self.tour
+            .crossover_into(&another_ind.tour, &ind_data.perm_data, rng, &mut dest.tour);
     }
 
+    // Fitness is the negative length of the tour
     fn count_fitness(&mut self, ind_data: &SalesmanIndividualData) {
-        self.fitness = 0.0;
-
-        for i in 0..ind_data.coords.len() - 1 {
-            self.fitness -= Coord::distance_euclid(
-                &ind_data.coords[self.genom[i] as usize],
-                &ind_data.coords[self.genom[i + 1] as usize],
-            ) as f64;
-        }
-
-        self.fitness -= Coord::distance_euclid(
-            &ind_data.coords[self.genom[0] as usize],
-            &ind_data.coords[self.genom[self.genom.len() - 1] as usize],
-        ) as f64;
+        self.tour.count_fitness(&ind_data.perm_data);
     }
 
     fn get_fitness(&self) -> f64 {
-        self.fitness
+        self.tour.get_fitness()
+    }
+
+    fn copy_from(&mut self, other: &Self) {
+        self.tour.copy_from(&other.tour);
     }
 
     fn get_visuals(&self, ind_data: &SalesmanIndividualData) -> (f64, f64) {
         let mut a: f64 = 0.0;
         let mut b: f64 = 0.0;
 
-        let len = self.genom.len();
+        let genes = self.get_genes();
+        let len = genes.len();
 
         for i in 0..len - 1 {
-            let city_1 = ind_data.coords[self.genom[i] as usize];
-            let city_2 = ind_data.coords[self.genom[i + 1] as usize];
+            let city_1 = ind_data.coords[genes[i] as usize];
+            let city_2 = ind_data.coords[genes[i + 1] as usize];
 
             let (dx, dy) = Coord::normalized_distance_between_points(&city_1, &city_2);
             a += dx.abs();
             b += dy.abs();
         }
 
-        let city_1 = ind_data.coords[self.genom[0] as usize];
-        let city_2 = ind_data.coords[self.genom[len - 1] as usize];
+        let city_1 = ind_data.coords[genes[0] as usize];
+        let city_2 = ind_data.coords[genes[len - 1] as usize];
 
         let (dx, dy) = Coord::normalized_distance_between_points(&city_1, &city_2);
 
@@ -424,6 +297,32 @@ impl EvoIndividual<SalesmanIndividualData> for SalesmanIndividual {
 
         (a, b)
     }
+
+    // Distance is the ratio of the edges of the tour that are not in the other tour
+    fn distance(&self, other: &Self, _ind_data: &SalesmanIndividualData) -> f64 {
+        permutation::edge_distance(self.get_genes(), other.get_genes())
+    }
+
+    // Rotated and reversed tours have the same hash
+    fn genome_hash(&self, _ind_data: &SalesmanIndividualData) -> u64 {
+        permutation::tour_hash(self.get_genes())
+    }
+}
+
+impl RemoteIndividual<SalesmanIndividualData> for SalesmanIndividual {
+    fn genome_to_json(&self) -> Value {
+        self.tour.genome_to_json()
+    }
+
+    fn from_genome_json(genome: &Value, ind_data: &SalesmanIndividualData) -> Result<Self, String> {
+        Ok(SalesmanIndividual {
+            tour: PermutationIndividual::from_genome_json(genome, &ind_data.perm_data)?,
+        })
+    }
+
+    fn set_fitness(&mut self, fitness: f64) {
+        self.tour.set_fitness(fitness);
+    }
 }
 
 impl Visualise<SalesmanIndividualData> for SalesmanIndividual {
@@ -443,20 +342,21 @@ impl Visualise<SalesmanIndividualData> for SalesmanIndividual {
         }
 
         // Draw roads
-        for i in 0..self.genom.len() - 1 {
-            let col = ((i * 255) / (self.genom.len())) as u8;
+        let genes = self.get_genes();
+        for i in 0..genes.len() - 1 {
+            let col = ((i * 255) / (genes.len())) as u8;
             let road_color = Rgb([col, 255 - col, 0]);
 
-            let from_city = &ind_data.coords[self.genom[i] as usize];
-            let to_city = &ind_data.coords[self.genom[i + 1] as usize];
+            let from_city = &ind_data.coords[genes[i] as usize];
+            let to_city = &ind_data.coords[genes[i + 1] as usize];
 
             draw_line_segment_mut(&mut img, from_city.as_f32(), to_city.as_f32(), road_color);
         }
 
         let road_color = Rgb([0, 255, 0]);
 
-        let from_city = &ind_data.coords[self.genom[0] as usize];
-        let to_city = &ind_data.coords[self.genom[self.genom.len() - 1] as usize];
+        let from_city = &ind_data.coords[genes[0] as usize];
+        let to_city = &ind_data.coords[genes[genes.len() - 1] as usize];
         draw_line_segment_mut(&mut img, from_city.as_f32(), to_city.as_f32(), road_color);
 
         img
@@ -466,111 +366,84 @@ impl Visualise<SalesmanIndividualData> for SalesmanIndividual {
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    #[test]
-    fn test_shift_multiple() {
-        // 6 cities test
-        // [0, 1, 2, 3, 4, 5]
-        let ind_6 = SalesmanIndividual {
-            fitness: 0.0,
-            genom: vec![0, 1, 2, 3, 4, 5],
-        };
-
-        let mut ind = ind_6.clone();
-        ind.shift_multiple(0, 2, 3);
-        assert_eq!(ind.genom, vec![3, 4, 5, 0, 1, 2]);
-
-        ind = ind_6.clone();
-        ind.shift_multiple(0, 2, 1);
-        assert_eq!(ind.genom, vec![3, 0, 1, 2, 4, 5]);
-
-        ind = ind_6.clone();
-        ind.shift_multiple(0, 2, 6);
-        assert_eq!(ind.genom, vec![0, 1, 2, 3, 4, 5]);
-
-        // 7 cities test
-        // [0, 1, 2, 3, 4, 5, 6]
-        let ind_7 = SalesmanIndividual {
-            fitness: 0.0,
-            genom: vec![0, 1, 2, 3, 4, 5, 6],
-        };
-
-        ind = ind_7.clone();
-        ind.shift_multiple(1, 3, 7);
-        assert_eq!(ind.genom, vec![6, 1, 2, 3, 0, 4, 5]);
-
-        ind = ind_7.clone();
-        ind.shift_multiple(1, 3, 13);
-        assert_eq!(ind.genom, vec![1, 2, 3, 5, 6, 0, 4]);
-
-        ind = ind_7.clone();
-        ind.shift_multiple(1, 3, 0);
-        assert_eq!(ind.genom, vec![0, 1, 2, 3, 4, 5, 6]);
-
-        ind = ind_7.clone();
-        ind.shift_multiple(3, 3, 5);
-        assert_eq!(ind.genom, vec![1, 3, 2, 4, 5, 6, 0]);
-
-        ind = ind_7.clone();
-        ind.shift_multiple(3, 3, 6);
-        assert_eq!(ind.genom, vec![1, 2, 3, 4, 5, 6, 0]);
+    use rand::SeedableRng;
+    use revo::config::Config;
+    use revo::evo_individual::EvoIndividualData;
+    use revo::permutation::PermCrossoverType;
+    use std::str::FromStr;
+
+    fn test_data(shift_prob: f64, rev_prob: f64) -> SalesmanIndividualData {
+        SalesmanIndividualData::new_with_rng(
+            &mut SmallRng::seed_from_u64(1),
+            7,
+            100,
+            100,
+            shift_prob,
+            rev_prob,
+            SalesmanInitType::Naive,
+        )
     }
 
     #[test]
-    fn test_reverse_part() {
-        // [0, 1, 2, 3, 4, 5]
-        let ind_6 = SalesmanIndividual {
-            fitness: 0.0,
-            genom: vec![0, 1, 2, 3, 4, 5],
-        };
-
-        let mut ind = ind_6.clone();
-        ind.reverse_part(0, 2);
-        assert_eq!(ind.genom, vec![2, 1, 0, 3, 4, 5]);
-
-        ind = ind_6.clone();
-        ind.reverse_part(5, 0);
-        assert_eq!(ind.genom, vec![5, 1, 2, 3, 4, 0]);
-
-        ind = ind_6.clone();
-        ind.reverse_part(5, 1);
-        assert_eq!(ind.genom, vec![0, 5, 2, 3, 4, 1]);
-
-        ind = ind_6.clone();
-        ind.reverse_part(5, 2);
-        assert_eq!(ind.genom, vec![1, 0, 5, 3, 4, 2]);
-
-        ind = ind_6.clone();
-        ind.reverse_part(2, 2);
-        assert_eq!(ind.genom, vec![4, 3, 2, 1, 0, 5]);
+    fn test_mutate() {
+        let genes: Vec<u16> = (0..7).collect();
+
+        // Every operator is applied with its own probability, the positions are drawn in the order of the operators
+        for (shift_prob, rev_prob, mut_prob) in [
+            (0.0, 0.0, 0.0),
+            (1.0, 0.0, 0.0),
+            (0.0, 1.0, 0.0),
+            (0.0, 0.0, 1.0),
+            (1.0, 1.0, 1.0),
+        ] {
+            let ind_data = test_data(shift_prob, rev_prob);
+            let mut rng = SmallRng::seed_from_u64(2);
+            let mut expected_rng = rng.clone();
+
+            let mut ind = SalesmanIndividual::_from_genes(genes.clone());
+            ind.mutate(&ind_data, &mut rng, mut_prob, 0.0);
+
+            let mut expected = genes.clone();
+            if expected_rng.gen_range(0.0..1.0) < shift_prob {
+                let from = expected_rng.gen_range(0..6);
+                let to = expected_rng.gen_range(0..6);
+                let shift = expected_rng.gen_range(1..6);
+                permutation::shift_multiple(&mut expected, from, to, shift);
+            }
+            if expected_rng.gen_range(0.0..1.0) < rev_prob {
+                let from = expected_rng.gen_range(0..6);
+                let to = expected_rng.gen_range(0..6);
+                permutation::reverse_part(&mut expected, from, to);
+            }
+            if expected_rng.gen_range(0.0..1.0) < mut_prob {
+                let i = expected_rng.gen_range(0..6);
+                let j = expected_rng.gen_range(0..6);
+                expected.swap(i, j);
+            }
+            assert_eq!(ind.get_genes(), &expected[..]);
+        }
+
+        // Tour stays a permutation of the cities
+        let ind_data = test_data(0.4, 0.4);
+        let mut rng = SmallRng::seed_from_u64(3);
+        let mut ind = SalesmanIndividual::_from_genes(genes.clone());
+        for _ in 0..100 {
+            ind.mutate(&ind_data, &mut rng, 0.5, 0.0);
+        }
+        let mut sorted = ind.get_genes().to_vec();
+        sorted.sort();
+        assert_eq!(sorted, genes);
     }
 
     #[test]
-    fn test_impl_crossover_to() {
-        let mut ind_1 = SalesmanIndividual {
-            fitness: 0.0,
-            genom: vec![0, 1, 2, 3, 4, 5],
-        };
-
-        let mut ind_2 = SalesmanIndividual {
-            fitness: 0.0,
-            genom: vec![5, 4, 3, 2, 1, 0],
-        };
-
-        let mut res_ind = ind_1.clone();
-
-        ind_1.genom = vec![0, 1, 2, 3, 4, 5];
-        ind_2.genom = vec![5, 4, 3, 2, 1, 0];
-
-        ind_1._impl_crossover_to(&ind_2, &mut res_ind, 0, 2, 0);
-        assert_eq!(res_ind.genom, vec![0, 1, 2, 5, 4, 3]);
-
-        ind_1._impl_crossover_to(&ind_2, &mut res_ind, 5, 0, 1);
-        assert_eq!(res_ind.genom, vec![0, 4, 3, 2, 1, 5]);
-
-        ind_1.genom = vec![4, 1, 3, 2, 5, 0];
-        ind_2.genom = vec![3, 5, 0, 1, 4, 2];
-        ind_1._impl_crossover_to(&ind_2, &mut res_ind, 1, 3, 2);
-        assert_eq!(res_ind.genom, vec![5, 1, 3, 2, 0, 4]);
+    fn test_from_config() {
+        let config = Config::from_str(
+            "{\"n_cities\": 10, \"shift_prob\": 0.1, \"rev_prob\": 0.2, \"perm_crossover\": \"pmx\"}",
+        )
+        .unwrap();
+        let ind_data = SalesmanIndividualData::from_config(&config);
+        assert_eq!(ind_data.shift_prob, 0.1);
+        assert_eq!(ind_data.rev_prob, 0.2);
+        assert_eq!(ind_data.perm_data.crossover_type, PermCrossoverType::Pmx);
     }
 }
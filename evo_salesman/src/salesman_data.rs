@@ -1,6 +1,8 @@
-use rand::Rng;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
 use revo::config::Config;
 use revo::evo_individual::EvoIndividualData;
+use revo::permutation::PermutationData;
 use revo::utils::Coord;
 use strum_macros::{Display, EnumIter, EnumString};
 
@@ -31,11 +33,34 @@ pub struct SalesmanIndividualData {
     pub shift_prob: f64,
     pub rev_prob: f64,
     pub init_type: SalesmanInitType,
+    // Tours are permutations of the cities, their crossover is selected by the permutation data
+    pub perm_data: PermutationData<u16>,
+}
+
+// Function returns the negative length of the closed tour through the cities
+pub fn tour_fitness(coords: &[Coord], tour: &[u16]) -> f64 {
+    let mut fitness = 0.0;
+
+    for i in 0..tour.len() {
+        fitness -= Coord::distance_euclid(
+            &coords[tour[i] as usize],
+            &coords[tour[(i + 1) % tour.len()] as usize],
+        ) as f64;
+    }
+
+    fitness
 }
 
 impl EvoIndividualData for SalesmanIndividualData {
+    // Cities are placed by the seed of the config if it is set, so the run can be reproduced
     fn from_config(config: &Config) -> Self {
-        Self::new(
+        let mut rng = match config.may_get_uint("seed").unwrap() {
+            Some(seed) => SmallRng::seed_from_u64(seed),
+            None => SmallRng::from_entropy(),
+        };
+
+        let mut ind_data = Self::new_with_rng(
+            &mut rng,
             config
                 .may_get_int("n_cities")
                 .unwrap()
@@ -60,7 +85,15 @@ impl EvoIndividualData for SalesmanIndividualData {
                 .may_get_enum("init_type")
                 .unwrap()
                 .unwrap_or(DEFAULT_INIT_TYPE),
-        )
+        );
+
+        // Number of the elements is given by the cities and the mutation by the shift, reverse and swap
+        // probabilities, only the crossover is read from the config
+        let perm_data: PermutationData<u16> =
+            PermutationData::from_config(config, |_| 0.0).unwrap();
+        ind_data.perm_data.crossover_type = perm_data.crossover_type;
+
+        ind_data
     }
 }
 
@@ -73,8 +106,27 @@ impl SalesmanIndividualData {
         rev_prob: f64,
         init_type: SalesmanInitType,
     ) -> Self {
-        let mut rng = rand::thread_rng();
+        Self::new_with_rng(
+            &mut rand::thread_rng(),
+            n_cities,
+            screen_width,
+            screen_height,
+            shift_prob,
+            rev_prob,
+            init_type,
+        )
+    }
 
+    // Function creates the individual data with the cities placed by the given random number generator
+    pub fn new_with_rng<R: Rng>(
+        rng: &mut R,
+        n_cities: u32,
+        screen_width: u32,
+        screen_height: u32,
+        shift_prob: f64,
+        rev_prob: f64,
+        init_type: SalesmanInitType,
+    ) -> Self {
         let mut coords: Vec<Coord> = Vec::new();
 
         for _ in 0..n_cities {
@@ -84,6 +136,11 @@ impl SalesmanIndividualData {
             });
         }
 
+        let tour_coords = coords.clone();
+        let perm_data = PermutationData::new(coords.len(), move |tour: &[u16]| {
+            tour_fitness(&tour_coords, tour)
+        });
+
         SalesmanIndividualData {
             coords,
             screen_width,
@@ -91,6 +148,7 @@ impl SalesmanIndividualData {
             shift_prob,
             rev_prob,
             init_type,
+            perm_data,
         }
     }
 }
@@ -0,0 +1,41 @@
+// Benchmark of the evaluation of random expressions of several depths on fixed-seed inputs
+// Run by `cargo bench --bench expression`, throughput is reported in evaluations per second
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use funtree::expression::Expression;
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+const DEPTHS: [u16; 3] = [4, 8, 12];
+const N_EXPRESSIONS: usize = 100;
+const N_VALUES: usize = 100;
+
+fn bench_evaluate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("expression_evaluate");
+    let values: Vec<f64> = (0..N_VALUES).map(|i| i as f64 / 10.0 - 5.0).collect();
+
+    // Every expression is evaluated at every value
+    group.throughput(Throughput::Elements((N_EXPRESSIONS * N_VALUES) as u64));
+    for depth in DEPTHS {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let expressions: Vec<Expression> = (0..N_EXPRESSIONS)
+            .map(|_| Expression::new_randomised(&mut rng, depth))
+            .collect();
+
+        group.bench_function(BenchmarkId::from_parameter(depth), |b| {
+            b.iter(|| {
+                let mut sum = 0.0;
+                for expression in &expressions {
+                    for &x in &values {
+                        sum += expression.evaluate(black_box(x));
+                    }
+                }
+                sum
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_evaluate);
+criterion_main!(benches);
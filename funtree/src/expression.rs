@@ -3,6 +3,7 @@ use crate::operation::{Operation, OperationType};
 use rand::rngs::SmallRng;
 use rand::Rng;
 use rand_distr::{Distribution, Normal};
+use serde_json::{json, Value};
 use std::default::Default;
 use std::fmt;
 use std::mem::swap;
@@ -104,6 +105,48 @@ impl Expression {
         }
     }
 
+    // Function returns the expression as a JSON tree, unlike the printed form the constants are not rounded
+    pub fn to_json(&self) -> Value {
+        match &self.expr {
+            Expr::Leaf(leaf) => match leaf.get_constant() {
+                Ok(value) => json!({"minus": self.minus, "constant": value}),
+                Err(_) => json!({"minus": self.minus, "variable": "x"}),
+            },
+            Expr::Op(op) => json!({
+                "minus": self.minus,
+                "operation": op.get_operation_type().symbol(),
+                "left": op.get_left().to_json(),
+                "right": op.get_right().to_json(),
+            }),
+        }
+    }
+
+    // Function creates the expression from the JSON tree written by to_json
+    pub fn from_json(json: &Value) -> Result<Self, String> {
+        let minus = json.get("minus").and_then(Value::as_bool).unwrap_or(false);
+
+        if let Some(value) = json.get("constant") {
+            let value = value
+                .as_f64()
+                .ok_or_else(|| format!("Constant '{}' is not a number", value))?;
+            return Ok(Self::new_leaf(value, LeafType::Constant, minus));
+        }
+        if json.get("variable").is_some() {
+            return Ok(Self::new_variable(minus));
+        }
+
+        let operation = json
+            .get("operation")
+            .and_then(Value::as_str)
+            .ok_or_else(|| format!("Expression '{}' is not a leaf or an operation", json))?;
+        Ok(Self::new_operation(
+            Self::from_json(&json["left"])?,
+            Self::from_json(&json["right"])?,
+            OperationType::from_str(operation)?,
+            minus,
+        ))
+    }
+
     pub fn get_visuals(&self) -> (f64, f64) {
         let (mut a, mut b) = match &self.expr {
             Expr::Leaf(leaf) => leaf.get_visuals(),
@@ -460,6 +503,41 @@ impl Expression {
         }
     }
 
+    // Function returns the top-down tree edit distance between the expressions
+    // Nodes at the same position are relabelled, subtrees that are only in one of the trees are inserted or deleted
+    // as a whole, every changed node costs 1
+    pub fn tree_distance(&self, other: &Expression) -> usize {
+        let relabel = usize::from(!self._same_label(other));
+
+        match (&self.expr, &other.expr) {
+            (Expr::Op(op), Expr::Op(other_op)) => {
+                relabel
+                    + op.get_left().tree_distance(other_op.get_left())
+                    + op.get_right().tree_distance(other_op.get_right())
+            }
+            // Children of the operation are deleted or inserted
+            (Expr::Op(_), Expr::Leaf(_)) => relabel + self.get_nodes().len() - 1,
+            (Expr::Leaf(_), Expr::Op(_)) => relabel + other.get_nodes().len() - 1,
+            (Expr::Leaf(_), Expr::Leaf(_)) => relabel,
+        }
+    }
+
+    fn _same_label(&self, other: &Expression) -> bool {
+        if self.minus != other.minus {
+            return false;
+        }
+
+        match (&self.expr, &other.expr) {
+            (Expr::Op(op), Expr::Op(other_op)) => {
+                op.get_operation_type() == other_op.get_operation_type()
+            }
+            (Expr::Leaf(leaf), Expr::Leaf(other_leaf)) => {
+                leaf.to_string(false) == other_leaf.to_string(false)
+            }
+            _ => false,
+        }
+    }
+
     /// # Safety
     ///
     /// This function dereferences a raw pointer obtained by casting `self` as a `*const`
@@ -634,6 +712,21 @@ mod tests {
     use super::*;
     use rand::SeedableRng;
 
+    #[test]
+    fn test_tree_distance() {
+        let exp = Expression::from_str("((x + 1.00) * x)").unwrap();
+        assert_eq!(exp.tree_distance(&exp), 0);
+
+        // Relabelled leaf and operation
+        let other = Expression::from_str("((x + 2.00) / x)").unwrap();
+        assert_eq!(exp.tree_distance(&other), 2);
+
+        // Leaf replaced by an operation with two children
+        let other = Expression::from_str("((x + 1.00) * (x + x))").unwrap();
+        assert_eq!(exp.tree_distance(&other), 3);
+        assert_eq!(other.tree_distance(&exp), 3);
+    }
+
     #[test]
     fn from_string() {
         // Testing some edge cases
@@ -660,6 +753,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_json() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        for _ in 0..100 {
+            let exp = Expression::new_randomised(&mut rng, 5);
+            let read = Expression::from_json(&exp.to_json()).unwrap();
+
+            // Constants are kept exactly
+            assert_eq!(read.to_json(), exp.to_json());
+            assert_eq!(read.evaluate(0.7).to_bits(), exp.evaluate(0.7).to_bits());
+        }
+
+        assert!(
+            Expression::from_json(&json!({"operation": "+", "left": {"variable": "x"}})).is_err()
+        );
+        assert!(Expression::from_json(&json!({"operation": "%"})).is_err());
+    }
+
     #[test]
     fn test_get_nodes() {
         let exp = Expression::from_str("-(-x * -(-(-(-1.00 + 2.00) * 3.00) + -4.00))").unwrap();
@@ -2,7 +2,8 @@ use crate::expression::Expression;
 use crate::funtree_data::FuntreeIndividualData;
 use rand::rngs::SmallRng;
 use rand::Rng;
-use revo::evo_individual::{EvoIndividual, Visualise};
+use revo::evo_individual::{hash_value, EvoIndividual, RemoteIndividual, Visualise};
+use serde_json::Value;
 
 use image::RgbImage;
 use image::{ImageBuffer, Rgb};
@@ -178,9 +179,9 @@ impl EvoIndividual<FuntreeIndividualData> for FuntreeIndividual {
             let (x, y) = val.as_tuple();
             let y_pred = self.genom.evaluate(x);
 
-            // Handling cases like division by zero
+            // Cases like division by zero make the individual invalid, NaN fitness is the worst for the population
             if y_pred.is_nan() {
-                self.fitness = -f64::INFINITY;
+                self.fitness = f64::NAN;
                 return;
             }
 
@@ -199,6 +200,34 @@ impl EvoIndividual<FuntreeIndividualData> for FuntreeIndividual {
     fn get_visuals(&self, _ind_data: &FuntreeIndividualData) -> (f64, f64) {
         self.genom.get_visuals()
     }
+
+    // Distance is the tree edit distance between the expressions
+    fn distance(&self, other: &Self, _ind_data: &FuntreeIndividualData) -> f64 {
+        self.genom.tree_distance(&other.genom) as f64
+    }
+
+    // Expressions with the same printed form have the same hash
+    fn genome_hash(&self, _ind_data: &FuntreeIndividualData) -> u64 {
+        hash_value(&self.genom.to_string())
+    }
+}
+
+// Genome is the JSON tree of the expression
+impl RemoteIndividual<FuntreeIndividualData> for FuntreeIndividual {
+    fn genome_to_json(&self) -> Value {
+        self.genom.to_json()
+    }
+
+    fn from_genome_json(genome: &Value, _ind_data: &FuntreeIndividualData) -> Result<Self, String> {
+        Ok(FuntreeIndividual {
+            fitness: 0.0,
+            genom: Expression::from_json(genome)?,
+        })
+    }
+
+    fn set_fitness(&mut self, fitness: f64) {
+        self.fitness = fitness;
+    }
 }
 
 impl Visualise<FuntreeIndividualData> for FuntreeIndividual {
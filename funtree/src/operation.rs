@@ -27,6 +27,18 @@ impl OperationType {
     }
 }
 
+impl OperationType {
+    // Function returns the symbol of the operation, which is parsed back by from_str
+    pub fn symbol(self) -> &'static str {
+        match self {
+            OperationType::Addition => "+",
+            OperationType::Multiplication => "*",
+            OperationType::Division => "/",
+            OperationType::Power => "^",
+        }
+    }
+}
+
 impl FromStr for OperationType {
     type Err = String;
 
@@ -133,13 +145,12 @@ impl Operation {
 
 impl fmt::Display for Operation {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let op_str = match self.operation_type {
-            OperationType::Addition => "+",
-            OperationType::Multiplication => "*",
-            OperationType::Division => "/",
-            OperationType::Power => "^",
-        };
-
-        write!(f, "({} {} {})", self.left, op_str, self.right)
+        write!(
+            f,
+            "({} {} {})",
+            self.left,
+            self.operation_type.symbol(),
+            self.right
+        )
     }
 }
@@ -3,12 +3,20 @@ use crate::pop_display::PopDisplay;
 use gtk::prelude::*;
 use gtk::Box;
 use gtk::Button;
+use gtk::ComboBoxText;
+use gtk::SpinButton;
+use revo::colormap::Colormap;
 use revo::config::Config;
 use revo::evo_individual::Visualise;
 use revo::evo_individual::{EvoIndividual, EvoIndividualData};
-use revo::population::Population;
+use revo::population::{
+    NeighbourhoodType, Population, ResizeType, SelectionStrategyType, VisualisationMode,
+};
 use std::cell::RefCell;
+use std::fmt::Display;
 use std::rc::Rc;
+use std::str::FromStr;
+use strum::IntoEnumIterator;
 
 const DEFAULT_DISPLAY_WIDTH: u32 = 400;
 const DEFAULT_DISPLAY_HEIGHT: u32 = 400;
@@ -74,6 +82,51 @@ impl<
         // Show best button
         buttons_box.add(&Self::_get_show_best_button(self_pointer));
 
+        // Visualisation mode selection
+        buttons_box.add(&Self::_get_visualisation_mode_combo(self_pointer));
+
+        // Live controls of the parameters, they take effect from the next generation
+        let params_box = Box::new(gtk::Orientation::Horizontal, 0);
+        let pop = self_.pop.borrow();
+        params_box.add(&gtk::Label::new(Some("mut_prob")));
+        params_box.add(&Self::_get_prob_spin_button(
+            self_pointer,
+            pop.get_mut_prob(),
+            |pop, value| pop.set_mut_prob(value),
+        ));
+        params_box.add(&gtk::Label::new(Some("crossover_prob")));
+        params_box.add(&Self::_get_prob_spin_button(
+            self_pointer,
+            pop.get_crossover_prob(),
+            |pop, value| pop.set_crossover_prob(value),
+        ));
+        params_box.add(&Self::_get_enum_combo(
+            self_pointer,
+            pop.get_selection_strategy(),
+            |pop, value: SelectionStrategyType| pop.set_selection_strategy(value),
+        ));
+        params_box.add(&Self::_get_enum_combo(
+            self_pointer,
+            pop.get_neighbourhood(),
+            |pop, value: NeighbourhoodType| pop.set_neighbourhood(value),
+        ));
+        params_box.add(&Self::_get_colormap_combo(self_pointer, pop.get_colormap()));
+        drop(pop);
+
+        // Resizing of the grid, growing stretches the individuals, shrinking keeps the top left part
+        params_box.add(&Self::_get_resize_button(
+            self_pointer,
+            "x2 size",
+            |size| size * 2,
+            ResizeType::Interpolate,
+        ));
+        params_box.add(&Self::_get_resize_button(
+            self_pointer,
+            "/2 size",
+            |size| (size / 2).max(1),
+            ResizeType::Tile,
+        ));
+
         // Add displays next to each other
         let displays_box = Box::new(gtk::Orientation::Horizontal, 0);
         displays_box.add(&PopDisplay::get_widget(
@@ -86,6 +139,7 @@ impl<
         let box_ = Box::new(gtk::Orientation::Vertical, 0);
         box_.add(&displays_box);
         box_.add(&buttons_box);
+        box_.add(&params_box);
 
         box_
     }
@@ -94,11 +148,135 @@ impl<
         let self_pointer_clone = self_pointer.clone();
         let button = Button::with_label("Show best");
         button.connect_clicked(move |_| {
+            // Jump to the cell of the best individual
             let self_ = self_pointer_clone.borrow_mut();
-            self_.ind_display.borrow().display_individual(
-                self_.pop.borrow().get_best(),
-                self_.pop.borrow().get_individual_data(),
-            );
+            let pop = self_.pop.borrow();
+            let (x, y) = pop.best_position();
+            self_.pop_display.borrow().select(&pop, x, y);
+        });
+        button
+    }
+
+    fn _get_visualisation_mode_combo(self_pointer: &Rc<RefCell<Self>>) -> ComboBoxText {
+        let combo = ComboBoxText::new();
+        for mode in VisualisationMode::iter() {
+            combo.append(Some(&mode.to_string()), &mode.to_string());
+        }
+        combo.set_active_id(Some(
+            &self_pointer
+                .borrow()
+                .pop
+                .borrow()
+                .get_visualisation_mode()
+                .to_string(),
+        ));
+
+        let self_pointer_clone = self_pointer.clone();
+        combo.connect_changed(move |combo| {
+            let mode = match combo
+                .active_id()
+                .and_then(|id| VisualisationMode::from_str(id.as_str()).ok())
+            {
+                Some(mode) => mode,
+                None => return,
+            };
+
+            let self_ = self_pointer_clone.borrow_mut();
+            self_.pop.borrow_mut().set_visualisation_mode(mode);
+            self_
+                .pop_display
+                .borrow_mut()
+                .display_pop(&self_.pop.borrow());
+        });
+        combo
+    }
+
+    // Colormap of the heatmap modes, the population is redrawn right away
+    fn _get_colormap_combo(self_pointer: &Rc<RefCell<Self>>, colormap: Colormap) -> ComboBoxText {
+        let combo = Self::_get_enum_combo(self_pointer, colormap, |pop, value: Colormap| {
+            pop.set_colormap(value)
+        });
+
+        let self_pointer_clone = self_pointer.clone();
+        combo.connect_changed(move |_| {
+            let self_ = self_pointer_clone.borrow();
+            self_
+                .pop_display
+                .borrow_mut()
+                .display_pop(&self_.pop.borrow());
+        });
+        combo
+    }
+
+    fn _get_prob_spin_button(
+        self_pointer: &Rc<RefCell<Self>>,
+        value: f32,
+        set_value: fn(&mut Population<Individual, IndividualData>, f32),
+    ) -> SpinButton {
+        let spin_button = SpinButton::with_range(0.0, 1.0, 0.01);
+        spin_button.set_value(value as f64);
+
+        let self_pointer_clone = self_pointer.clone();
+        spin_button.connect_value_changed(move |spin_button| {
+            let self_ = self_pointer_clone.borrow_mut();
+            set_value(&mut self_.pop.borrow_mut(), spin_button.value() as f32);
+        });
+        spin_button
+    }
+
+    fn _get_enum_combo<T>(
+        self_pointer: &Rc<RefCell<Self>>,
+        value: T,
+        set_value: fn(&mut Population<Individual, IndividualData>, T),
+    ) -> ComboBoxText
+    where
+        T: IntoEnumIterator + Display + FromStr + 'static,
+    {
+        let combo = ComboBoxText::new();
+        for item in T::iter() {
+            combo.append(Some(&item.to_string()), &item.to_string());
+        }
+        combo.set_active_id(Some(&value.to_string()));
+
+        let self_pointer_clone = self_pointer.clone();
+        combo.connect_changed(move |combo| {
+            let value = match combo
+                .active_id()
+                .and_then(|id| T::from_str(id.as_str()).ok())
+            {
+                Some(value) => value,
+                None => return,
+            };
+
+            let self_ = self_pointer_clone.borrow_mut();
+            set_value(&mut self_.pop.borrow_mut(), value);
+        });
+        combo
+    }
+
+    fn _get_resize_button(
+        self_pointer: &Rc<RefCell<Self>>,
+        label: &str,
+        new_size: fn(usize) -> usize,
+        resize_type: ResizeType,
+    ) -> Button {
+        let self_pointer_clone = self_pointer.clone();
+        let button = Button::with_label(label);
+        button.connect_clicked(move |_| {
+            let self_ = self_pointer_clone.borrow_mut();
+            let (width, height) = {
+                let pop = self_.pop.borrow();
+                (new_size(pop.get_width()), new_size(pop.get_height()))
+            };
+            if let Err(err) = self_.pop.borrow_mut().resize(width, height, resize_type) {
+                println!("{}", err);
+                return;
+            }
+
+            self_
+                .pop_display
+                .borrow_mut()
+                .display_pop(&self_.pop.borrow());
         });
         button
     }
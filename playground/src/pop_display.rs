@@ -83,6 +83,23 @@ impl PopDisplay {
         self.image.set_from_pixbuf(Some(&pixbuf));
     }
 
+    // Function shows the individual at (x, y) in the individual display and its position and rank in the label
+    pub fn select<Individual, IndividualData>(
+        &self,
+        pop: &Population<Individual, IndividualData>,
+        x: usize,
+        y: usize,
+    ) where
+        Individual: EvoIndividual<IndividualData> + Visualise<IndividualData>,
+        IndividualData: EvoIndividualData,
+    {
+        self.ind_display
+            .borrow()
+            .display_individual(pop.get_at(x, y), pop.get_individual_data());
+        self.label
+            .set_text(&format!("x: {},y: {}, rank: {}", x, y, pop.rank_of(x, y)));
+    }
+
     pub fn get_widget<Individual, IndividualData>(
         self_pointer: &Rc<RefCell<Self>>,
         pop: Rc<RefCell<Population<Individual, IndividualData>>>,
@@ -112,14 +129,7 @@ impl PopDisplay {
                     return Inhibit(false);
                 }
 
-                self_.ind_display.borrow().display_individual(
-                    pop.borrow().get_at(x as usize, y as usize),
-                    pop.borrow().get_individual_data(),
-                );
-
-                self_
-                    .label
-                    .set_text(&format!("x: {},y: {}", x as usize, y as usize));
+                self_.select(&pop.borrow(), x as usize, y as usize);
 
                 Inhibit(false)
             });
@@ -0,0 +1,126 @@
+// Benchmark of the allocations done by the generation step on a 1000x100 grid of permutations
+// Run by `cargo bench --bench allocations`, it prints the allocations per generation of the permutation individual,
+// which writes the offspring into the buffers of the population, and of an individual that clones every offspring
+
+use rand::rngs::SmallRng;
+use revo::config::Config;
+use revo::evo_individual::EvoIndividual;
+use revo::permutation::{PermutationData, PermutationIndividual};
+use revo::population::Population;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+const N_ELEMENTS: usize = 100;
+const N_GENERATIONS: usize = 5;
+
+// Allocator that counts the allocations and allocated bytes of all threads
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+// Permutation individual with the default copy_from and crossover_into, so every offspring is a new clone
+#[derive(Clone)]
+struct CloningIndividual(PermutationIndividual<u16>);
+
+impl EvoIndividual<PermutationData<u16>> for CloningIndividual {
+    fn new_randomised(ind_data: &PermutationData<u16>, rng: &mut SmallRng) -> Self {
+        CloningIndividual(PermutationIndividual::new_randomised(ind_data, rng))
+    }
+
+    fn mutate(
+        &mut self,
+        ind_data: &PermutationData<u16>,
+        rng: &mut SmallRng,
+        mut_prob: f32,
+        mut_amount: f32,
+    ) {
+        self.0.mutate(ind_data, rng, mut_prob, mut_amount);
+    }
+
+    fn crossover(
+        &self,
+        another_ind: &Self,
+        ind_data: &PermutationData<u16>,
+        rng: &mut SmallRng,
+    ) -> Self {
+        CloningIndividual(self.0.crossover(&another_ind.0, ind_data, rng))
+    }
+
+    fn count_fitness(&mut self, ind_data: &PermutationData<u16>) {
+        self.0.count_fitness(ind_data);
+    }
+
+    fn get_fitness(&self) -> f64 {
+        self.0.get_fitness()
+    }
+
+    fn get_visuals(&self, ind_data: &PermutationData<u16>) -> (f64, f64) {
+        self.0.get_visuals(ind_data)
+    }
+}
+
+// Fitness is the negative sum of the displacements of the elements
+fn displacement(genes: &[u16]) -> f64 {
+    -genes
+        .iter()
+        .enumerate()
+        .map(|(i, &gene)| (i as f64 - gene as f64).abs())
+        .sum::<f64>()
+}
+
+fn bench<Individual: EvoIndividual<PermutationData<u16>>>(name: &str) {
+    let config = Config::from_str(
+        "{\"pop_width\": 1000, \"pop_height\": 100, \"seed\": 1, \"mut_prob\": 0.5, \"crossover_prob\": 0.3}",
+    )
+    .unwrap();
+    let mut pop: Population<Individual, PermutationData<u16>> =
+        Population::new_with_data(&config, PermutationData::new(N_ELEMENTS, displacement));
+
+    // The first generation creates the buffers of the population
+    pop.next_gen();
+
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let allocated_bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..N_GENERATIONS {
+        pop.next_gen();
+    }
+    let elapsed = start.elapsed() / N_GENERATIONS as u32;
+
+    println!(
+        "{}: {} allocations, {} kB allocated, {:?} per generation",
+        name,
+        (ALLOCATIONS.load(Ordering::Relaxed) - allocations) / N_GENERATIONS,
+        (ALLOCATED_BYTES.load(Ordering::Relaxed) - allocated_bytes) / N_GENERATIONS / 1024,
+        elapsed
+    );
+}
+
+fn main() {
+    bench::<PermutationIndividual<u16>>("in place");
+    bench::<CloningIndividual>("cloning");
+}
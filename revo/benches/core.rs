@@ -0,0 +1,82 @@
+// Benchmarks of the generation step and the visualisation of the population on fixed-seed inputs
+// Run by `cargo bench --bench core`, throughput is reported in evaluations (or cells) per second
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use revo::config::Config;
+use revo::population::{Population, VisualisationMode};
+use revo::real_vector::{RealVectorData, RealVectorIndividual};
+use revo::utils::{normalize_component, IndexedLabData};
+use std::str::FromStr;
+
+const GRID_SIZES: [usize; 3] = [32, 128, 256];
+const N_GENES: usize = 10;
+
+fn sphere(genes: &[f64]) -> f64 {
+    -genes.iter().map(|x| x * x).sum::<f64>()
+}
+
+fn population(size: usize) -> Population<RealVectorIndividual, RealVectorData> {
+    let config = Config::from_str(&format!(
+        "{{\"pop_width\": {}, \"pop_height\": {}, \"seed\": 1, \"mut_prob\": 0.5, \"crossover_prob\": 0.3}}",
+        size, size
+    ))
+    .unwrap();
+    Population::new_with_data(&config, RealVectorData::new(N_GENES, -5.0, 5.0, sphere))
+}
+
+fn bench_next_gen(c: &mut Criterion) {
+    let mut group = c.benchmark_group("next_gen");
+    for size in GRID_SIZES {
+        // Every cell of the grid is evaluated once per generation
+        group.throughput(Throughput::Elements((size * size) as u64));
+        let mut pop = population(size);
+        group.bench_function(BenchmarkId::from_parameter(size), |b| {
+            b.iter(|| pop.next_gen())
+        });
+    }
+    group.finish();
+}
+
+fn bench_visualise(c: &mut Criterion) {
+    let mut group = c.benchmark_group("visualise");
+    for size in GRID_SIZES {
+        group.throughput(Throughput::Elements((size * size) as u64));
+        let pop = population(size);
+        for mode in [VisualisationMode::Lab, VisualisationMode::Fitness] {
+            group.bench_function(BenchmarkId::new(mode.to_string(), size), |b| {
+                b.iter(|| pop.visualise_with(mode))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_normalize_component(c: &mut Criterion) {
+    let mut group = c.benchmark_group("normalize_component");
+    for size in GRID_SIZES {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let data: Vec<IndexedLabData> = (0..size * size)
+            .map(|i| IndexedLabData::new(rng.gen_range(0.0..1.0), 0.0, 0.0, i))
+            .collect();
+
+        group.throughput(Throughput::Elements((size * size) as u64));
+        group.bench_function(BenchmarkId::from_parameter(size), |b| {
+            b.iter_batched_ref(
+                || data.clone(),
+                |data| normalize_component(data, |d| d.l, |d, val| d.l = val, 10.0, 90.0),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_next_gen,
+    bench_visualise,
+    bench_normalize_component
+);
+criterion_main!(benches);
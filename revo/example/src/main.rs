@@ -1,36 +1,40 @@
 use example::basic_individual::{BasicIndividual, BasicIndividualData};
-use revo::config::{Config, DEFAULT_CONFIG_FILENAME};
+use revo::cli::{or_exit, CliArgs};
 use revo::evo_individual::Visualise;
-use revo::population::Population;
+use revo::population::{Population, VisualisationMode};
 
 fn main() {
-    // Load the configuration from the config.json5 file
-    let config = Config::new(DEFAULT_CONFIG_FILENAME);
-    let output_dir = "./";
+    // Parse the command line arguments, see --help for the options
+    // The configuration is loaded from the config.json5 file unless another one is given by --config
+    let args = CliArgs::from_env();
+    or_exit(args.prepare_output_dir());
+    let config = or_exit(args.load_config());
 
     // Create the population
     let mut pop: Population<BasicIndividual, BasicIndividualData> = Population::new(&config);
 
     // Evolve the population
     // This will apply the evolution rules to the population and create a new generation
-    // You can run next_gen() in a loop to evolve the population
-    pop.next_gen();
+    // You can run next_gen() in a loop to evolve the population, here it runs for --generations or a single generation
+    for _ in 0..args.max_generations.unwrap_or(1) {
+        pop.next_gen();
+    }
 
     // Get the best individual from the population
     let pop_best = pop.get_best();
 
     // Get visualise flag from the configuration
-    let visualise: bool = config.may_get_bool("visualise").unwrap().unwrap_or(false);
+    let visualise: bool = VisualisationMode::from_config(&config).unwrap().is_some();
 
     // Visualize the population
     if visualise {
         pop.visualise()
-            .save(format!("{}/pop_{}.png", output_dir, pop.get_generation()))
+            .save(args.output_path(&format!("pop_{}.png", pop.get_generation())))
             .unwrap();
     }
 
     // Print the best individual from the population - if the individual implements the Display trait
-    println!("{}", pop_best);
+    args.info(&format!("{}", pop_best));
 
     // If individual implements the Visualise trait, you can visualise it. To do this, you need to provide the individual data
     // Individual data contains things that are not specific to the individual, but are needed for the evolution
@@ -39,6 +43,6 @@ fn main() {
     // visualise returns an RgbImage that can be saved to a file or displayed
     pop_best
         .visualise(ind_data)
-        .save(format!("{}/ind_{}.png", output_dir, pop.get_generation()))
+        .save(args.output_path(&format!("ind_{}.png", pop.get_generation())))
         .unwrap();
 }
@@ -0,0 +1,36 @@
+// Example worker evaluating the built-in continuous benchmarks for a revo coordinator
+// The benchmark is taken from the "benchmark" value of the config sent by the coordinator and the genes from "n_dims"
+// Other problems write their own worker the same way, with their individual and the data created from the config
+// Usage: cargo run --release --example benchmark_worker -- <coordinator address>
+
+use revo::benchmarks::ContinuousBenchmark;
+use revo::config::Config;
+use revo::distributed::run_worker;
+use revo::real_vector::{RealVectorData, RealVectorIndividual};
+use std::env;
+use std::process;
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:7878";
+
+fn create_data(config: &Config) -> Result<RealVectorData, String> {
+    let function: ContinuousBenchmark = config.get_enum("benchmark")?;
+    RealVectorData::from_config(config, move |x| -function.evaluate(x))
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") || args.len() > 2 {
+        println!("Usage: {} [coordinator address]", args[0]);
+        println!("Registers with the coordinator (default {}) and evaluates its batches until it disconnects", DEFAULT_ADDRESS);
+        return;
+    }
+    let address = args.get(1).map_or(DEFAULT_ADDRESS, String::as_str);
+
+    match run_worker::<RealVectorIndividual, RealVectorData, _>(address, create_data) {
+        Ok(n_evaluated) => println!("Coordinator finished, {} genomes evaluated", n_evaluated),
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,540 @@
+use crate::config::Config;
+use crate::evo_individual::{EvoIndividual, Visualise};
+use crate::population::Population;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::imageops::FilterType;
+use image::{Delay, Frame, RgbImage};
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use strum_macros::{Display, EnumIter, EnumString};
+
+const DEFAULT_EXPORT_FORMAT: AnimationFormat = AnimationFormat::Gif;
+const DEFAULT_EXPORT_STRIDE: usize = 1;
+const DEFAULT_EXPORT_SCALE: u32 = 1;
+const DEFAULT_EXPORT_FPS: u32 = 10;
+const GIF_ENCODER_SPEED: i32 = 10;
+
+const PNG_SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+// Offset of the acTL chunk, it follows the signature and the IHDR chunk with 13 bytes of data
+const APNG_ACTL_OFFSET: u64 = 8 + 12 + 13;
+// Length of the IEND chunk, it is overwritten by the next frame
+const PNG_IEND_LENGTH: i64 = 12;
+
+#[derive(Clone, Copy, PartialEq, Debug, EnumString, EnumIter, Display)]
+pub enum AnimationFormat {
+    #[strum(serialize = "gif")]
+    Gif,
+    #[strum(serialize = "apng")]
+    Apng,
+    #[strum(serialize = "y4m")]
+    Y4m,
+}
+
+impl AnimationFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AnimationFormat::Gif => "gif",
+            AnimationFormat::Apng => "png",
+            AnimationFormat::Y4m => "y4m",
+        }
+    }
+}
+
+enum AnimationWriter {
+    Gif(Box<GifEncoder<BufWriter<File>>>),
+    // APNG is written frame by frame, the number of frames in the header is updated after every frame
+    Apng(ApngWriter),
+    Y4m(BufWriter<File>),
+}
+
+// Exporter streams images like population maps or renders of individuals into a single animation file
+pub struct AnimationExporter {
+    writer: AnimationWriter,
+
+    // Only every stride-th generation is recorded
+    stride: usize,
+    // Integer upscaling factor of the frames
+    scale: u32,
+    fps: u32,
+
+    // Dimensions of the first frame, all frames must have the same dimensions
+    dimensions: Option<(u32, u32)>,
+    n_frames: usize,
+}
+
+impl AnimationExporter {
+    pub fn new(
+        path: &str,
+        format: AnimationFormat,
+        stride: usize,
+        scale: u32,
+        fps: u32,
+    ) -> Result<Self, String> {
+        if stride == 0 || scale == 0 || fps == 0 {
+            return Err("Stride, scale and fps of the animation must be positive".to_string());
+        }
+
+        let file = File::create(path)
+            .map_err(|err| format!("Creating animation file '{}' failed: {}", path, err))?;
+        let file = BufWriter::new(file);
+
+        let writer = match format {
+            AnimationFormat::Gif => {
+                let mut encoder = GifEncoder::new_with_speed(file, GIF_ENCODER_SPEED);
+                encoder
+                    .set_repeat(Repeat::Infinite)
+                    .map_err(|err| format!("Writing GIF header failed: {}", err))?;
+                AnimationWriter::Gif(Box::new(encoder))
+            }
+            AnimationFormat::Apng => AnimationWriter::Apng(ApngWriter::new(file, fps)),
+            AnimationFormat::Y4m => AnimationWriter::Y4m(file),
+        };
+
+        Ok(AnimationExporter {
+            writer,
+            stride,
+            scale,
+            fps,
+            dimensions: None,
+            n_frames: 0,
+        })
+    }
+
+    // Function creates an exporter with the export parameters from the config
+    // The file extension matching the format is appended to the path stem
+    pub fn from_config(config: &Config, path_stem: &str) -> Result<Self, String> {
+        let format: AnimationFormat = config
+            .may_get_enum("export_format")?
+            .unwrap_or(DEFAULT_EXPORT_FORMAT);
+
+        Self::new(
+            &format!("{}.{}", path_stem, format.extension()),
+            format,
+            config
+                .may_get_uint("export_stride")?
+                .unwrap_or(DEFAULT_EXPORT_STRIDE),
+            config
+                .may_get_uint("export_scale")?
+                .unwrap_or(DEFAULT_EXPORT_SCALE),
+            config
+                .may_get_uint("export_fps")?
+                .unwrap_or(DEFAULT_EXPORT_FPS),
+        )
+    }
+
+    // Function returns true if the frame of the given generation will be recorded
+    pub fn should_record(&self, generation: usize) -> bool {
+        generation.is_multiple_of(self.stride)
+    }
+
+    pub fn get_n_frames(&self) -> usize {
+        self.n_frames
+    }
+
+    // Function adds the image as a frame of the animation if the generation matches the stride
+    pub fn add_frame(&mut self, generation: usize, img: &RgbImage) -> Result<(), String> {
+        if !self.should_record(generation) {
+            return Ok(());
+        }
+
+        let img = if self.scale > 1 {
+            image::imageops::resize(
+                img,
+                img.width() * self.scale,
+                img.height() * self.scale,
+                FilterType::Nearest,
+            )
+        } else {
+            img.clone()
+        };
+
+        match self.dimensions {
+            None => self.dimensions = Some(img.dimensions()),
+            Some(dimensions) if dimensions != img.dimensions() => {
+                return Err(format!(
+                    "Frame dimensions {:?} don't match the animation dimensions {:?}",
+                    img.dimensions(),
+                    dimensions
+                ))
+            }
+            Some(_) => {}
+        }
+
+        match &mut self.writer {
+            AnimationWriter::Gif(encoder) => {
+                let rgba = image::DynamicImage::ImageRgb8(img).into_rgba8();
+                let frame =
+                    Frame::from_parts(rgba, 0, 0, Delay::from_numer_denom_ms(1000, self.fps));
+                encoder
+                    .encode_frame(frame)
+                    .map_err(|err| format!("Writing GIF frame failed: {}", err))?;
+            }
+            AnimationWriter::Apng(writer) => writer.write_frame(&img)?,
+            AnimationWriter::Y4m(file) => {
+                if self.n_frames == 0 {
+                    Self::_write_y4m_header(file, img.width(), img.height(), self.fps)?;
+                }
+                Self::_write_y4m_frame(file, &img)?;
+            }
+        }
+
+        self.n_frames += 1;
+        Ok(())
+    }
+
+    // Function adds the visualisation of the population as a frame if the generation matches the stride
+    pub fn add_population<Individual, IndividualData>(
+        &mut self,
+        pop: &Population<Individual, IndividualData>,
+    ) -> Result<(), String>
+    where
+        Individual: EvoIndividual<IndividualData>,
+        IndividualData: Send + Sync,
+    {
+        // Skip the visualisation if the frame won't be recorded
+        if !self.should_record(pop.get_generation()) {
+            return Ok(());
+        }
+
+        self.add_frame(pop.get_generation(), &pop.visualise())
+    }
+
+    // Function adds the render of the best individual as a frame if the generation matches the stride
+    pub fn add_best<Individual, IndividualData>(
+        &mut self,
+        pop: &Population<Individual, IndividualData>,
+    ) -> Result<(), String>
+    where
+        Individual: EvoIndividual<IndividualData> + Visualise<IndividualData>,
+        IndividualData: Send + Sync,
+    {
+        if !self.should_record(pop.get_generation()) {
+            return Ok(());
+        }
+
+        self.add_frame(
+            pop.get_generation(),
+            &pop.get_best().visualise(pop.get_individual_data()),
+        )
+    }
+
+    // Function writes the rest of the animation and closes the file
+    pub fn finish(self) -> Result<(), String> {
+        match self.writer {
+            // GIF trailer is written when the encoder is dropped
+            AnimationWriter::Gif(encoder) => drop(encoder),
+            // APNG is complete after every frame, the file stays empty without frames like the Y4M stream
+            AnimationWriter::Apng(_) => {}
+            AnimationWriter::Y4m(mut file) => {
+                file.flush()
+                    .map_err(|err| format!("Writing Y4M stream failed: {}", err))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Private functions
+
+    fn _write_y4m_header(
+        file: &mut BufWriter<File>,
+        width: u32,
+        height: u32,
+        fps: u32,
+    ) -> Result<(), String> {
+        writeln!(
+            file,
+            "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C444 XCOLORRANGE=FULL",
+            width, height, fps
+        )
+        .map_err(|err| format!("Writing Y4M header failed: {}", err))
+    }
+
+    // Function writes the frame as full range BT.601 YUV 4:4:4 planes
+    fn _write_y4m_frame(file: &mut BufWriter<File>, img: &RgbImage) -> Result<(), String> {
+        let n_pixels = (img.width() * img.height()) as usize;
+        let mut planes = vec![0u8; n_pixels * 3];
+
+        for (i, pixel) in img.pixels().enumerate() {
+            let [r, g, b] = pixel.0.map(|c| c as f64);
+
+            planes[i] = (0.299 * r + 0.587 * g + 0.114 * b).round() as u8;
+            planes[n_pixels + i] = (128.0 - 0.168736 * r - 0.331264 * g + 0.5 * b)
+                .round()
+                .clamp(0.0, 255.0) as u8;
+            planes[2 * n_pixels + i] = (128.0 + 0.5 * r - 0.418688 * g - 0.081312 * b)
+                .round()
+                .clamp(0.0, 255.0) as u8;
+        }
+
+        file.write_all(b"FRAME\n")
+            .and_then(|_| file.write_all(&planes))
+            .map_err(|err| format!("Writing Y4M frame failed: {}", err))
+    }
+}
+
+// Writer of APNG that keeps the file a valid animation after every frame, so it is usable even if the run is killed
+// Every frame is compressed by the PNG encoder as a standalone image, its image data are written as the frame data
+struct ApngWriter {
+    file: BufWriter<File>,
+    fps: u32,
+    n_frames: u32,
+    // Sequence number of the next fcTL or fdAT chunk
+    sequence: u32,
+}
+
+impl ApngWriter {
+    fn new(file: BufWriter<File>, fps: u32) -> Self {
+        ApngWriter {
+            file,
+            fps,
+            n_frames: 0,
+            sequence: 0,
+        }
+    }
+
+    fn write_frame(&mut self, img: &RgbImage) -> Result<(), String> {
+        self._write_frame(img)
+            .map_err(|err| format!("Writing APNG frame failed: {}", err))
+    }
+
+    fn _write_frame(&mut self, img: &RgbImage) -> std::io::Result<()> {
+        let (ihdr, idat) = Self::_encode(img)?;
+
+        if self.n_frames == 0 {
+            self.file.write_all(&PNG_SIGNATURE)?;
+            Self::_write_chunk(&mut self.file, b"IHDR", &ihdr)?;
+            Self::_write_chunk(&mut self.file, b"acTL", &Self::_actl(1))?;
+        } else {
+            self.file.seek(SeekFrom::End(-PNG_IEND_LENGTH))?;
+        }
+
+        // Frame control with the delay of 1/fps s, the frame replaces the whole previous frame
+        let mut fctl = Vec::with_capacity(26);
+        fctl.extend_from_slice(&self.sequence.to_be_bytes());
+        fctl.extend_from_slice(&img.width().to_be_bytes());
+        fctl.extend_from_slice(&img.height().to_be_bytes());
+        fctl.extend_from_slice(&[0; 8]);
+        fctl.extend_from_slice(&1u16.to_be_bytes());
+        fctl.extend_from_slice(&(self.fps.min(u16::MAX as u32) as u16).to_be_bytes());
+        fctl.extend_from_slice(&[0, 0]);
+        Self::_write_chunk(&mut self.file, b"fcTL", &fctl)?;
+        self.sequence += 1;
+
+        // First frame is the default image, the others are frame data chunks with the sequence number
+        for data in idat {
+            if self.n_frames == 0 {
+                Self::_write_chunk(&mut self.file, b"IDAT", &data)?;
+            } else {
+                let mut fdat = self.sequence.to_be_bytes().to_vec();
+                fdat.extend_from_slice(&data);
+                Self::_write_chunk(&mut self.file, b"fdAT", &fdat)?;
+                self.sequence += 1;
+            }
+        }
+        Self::_write_chunk(&mut self.file, b"IEND", &[])?;
+        self.n_frames += 1;
+
+        self.file.seek(SeekFrom::Start(APNG_ACTL_OFFSET))?;
+        Self::_write_chunk(&mut self.file, b"acTL", &Self::_actl(self.n_frames))?;
+        self.file.seek(SeekFrom::End(0))?;
+        self.file.flush()
+    }
+
+    // Function returns the data of the animation control chunk, the animation is repeated infinitely
+    fn _actl(n_frames: u32) -> Vec<u8> {
+        let mut actl = n_frames.to_be_bytes().to_vec();
+        actl.extend_from_slice(&0u32.to_be_bytes());
+        actl
+    }
+
+    // Function encodes the image as PNG and returns the data of its IHDR chunk and of all of its IDAT chunks
+    fn _encode(img: &RgbImage) -> std::io::Result<(Vec<u8>, Vec<Vec<u8>>)> {
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, img.width(), img.height());
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(img.as_raw()))
+            .map_err(std::io::Error::other)?;
+
+        let mut ihdr = Vec::new();
+        let mut idat = Vec::new();
+        let mut pos = PNG_SIGNATURE.len();
+        while pos + 8 <= png.len() {
+            let length = u32::from_be_bytes(png[pos..pos + 4].try_into().unwrap()) as usize;
+            let data = png[pos + 8..pos + 8 + length].to_vec();
+            match &png[pos + 4..pos + 8] {
+                b"IHDR" => ihdr = data,
+                b"IDAT" => idat.push(data),
+                _ => {}
+            }
+            pos += length + 12;
+        }
+
+        Ok((ihdr, idat))
+    }
+
+    fn _write_chunk(
+        file: &mut BufWriter<File>,
+        chunk_type: &[u8; 4],
+        data: &[u8],
+    ) -> std::io::Result<()> {
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(chunk_type);
+        hasher.update(data);
+
+        file.write_all(&(data.len() as u32).to_be_bytes())?;
+        file.write_all(chunk_type)?;
+        file.write_all(data)?;
+        file.write_all(&hasher.finalize().to_be_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::Cursor;
+    use std::str::FromStr;
+
+    fn test_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("revo_animation_{}_{}", std::process::id(), name))
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    fn test_frames() -> Vec<RgbImage> {
+        (0..4)
+            .map(|i| RgbImage::from_pixel(3, 2, image::Rgb([i * 60, 255 - i * 60, 0])))
+            .collect()
+    }
+
+    #[test]
+    fn test_y4m() {
+        let path = test_path("test.y4m");
+        let mut exporter = AnimationExporter::new(&path, AnimationFormat::Y4m, 2, 2, 25).unwrap();
+
+        for (generation, frame) in test_frames().iter().enumerate() {
+            exporter.add_frame(generation, frame).unwrap();
+        }
+
+        // Only every second generation is recorded
+        assert_eq!(exporter.get_n_frames(), 2);
+        exporter.finish().unwrap();
+
+        let data = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let header = "YUV4MPEG2 W6 H4 F25:1 Ip A1:1 C444 XCOLORRANGE=FULL\n";
+        assert!(data.starts_with(header.as_bytes()));
+
+        // Header and two frames with 3 planes of the scaled size
+        let frame_len = "FRAME\n".len() + 6 * 4 * 3;
+        assert_eq!(data.len(), header.len() + 2 * frame_len);
+
+        // Luma of the first frame colour (0, 255, 0)
+        assert_eq!(data[header.len() + "FRAME\n".len()], 150);
+    }
+
+    #[test]
+    fn test_gif_and_apng() {
+        for (format, name) in [
+            (AnimationFormat::Gif, "test.gif"),
+            (AnimationFormat::Apng, "test.png"),
+        ] {
+            let path = test_path(name);
+            let mut exporter = AnimationExporter::new(&path, format, 1, 1, 10).unwrap();
+
+            for (generation, frame) in test_frames().iter().enumerate() {
+                exporter.add_frame(generation, frame).unwrap();
+            }
+            exporter.finish().unwrap();
+
+            let data = fs::read(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+
+            // The result is a valid image of the frame size
+            let img = image::load(
+                Cursor::new(data),
+                image::ImageFormat::from_path(name).unwrap(),
+            )
+            .unwrap();
+            assert_eq!((img.width(), img.height()), (3, 2));
+        }
+    }
+
+    #[test]
+    fn test_apng_streaming() {
+        let path = test_path("streaming.png");
+        let mut exporter = AnimationExporter::new(&path, AnimationFormat::Apng, 1, 2, 10).unwrap();
+
+        // File is a complete animation after every frame, without finishing the exporter
+        for (generation, frame) in test_frames().iter().enumerate() {
+            exporter.add_frame(generation, frame).unwrap();
+
+            let decoder = png::Decoder::new(File::open(&path).unwrap());
+            let mut reader = decoder.read_info().unwrap();
+            let n_frames = reader.info().animation_control().unwrap().num_frames;
+            assert_eq!(n_frames as usize, generation + 1);
+
+            let mut buf = vec![0; reader.output_buffer_size()];
+            for _ in 0..n_frames {
+                let info = reader.next_frame(&mut buf).unwrap();
+                assert_eq!((info.width, info.height), (6, 4));
+            }
+            // Last frame has the colour of the frame added last
+            assert_eq!(&buf[..3], &frame.get_pixel(0, 0).0);
+        }
+        exporter.finish().unwrap();
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_no_frames() {
+        // Run that ended before the first recorded generation finishes every format
+        for (format, name) in [
+            (AnimationFormat::Gif, "empty.gif"),
+            (AnimationFormat::Apng, "empty.png"),
+            (AnimationFormat::Y4m, "empty.y4m"),
+        ] {
+            let path = test_path(name);
+            let exporter = AnimationExporter::new(&path, format, 1, 1, 10).unwrap();
+            assert_eq!(exporter.get_n_frames(), 0);
+            exporter.finish().unwrap();
+            fs::remove_file(&path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_from_config() {
+        let config = Config::from_str(
+            "{\"export_format\": \"y4m\", \"export_stride\": 3, \"export_scale\": 2}",
+        )
+        .unwrap();
+        let path_stem = test_path("config");
+
+        let exporter = AnimationExporter::from_config(&config, &path_stem).unwrap();
+        assert!(exporter.should_record(6));
+        assert!(!exporter.should_record(7));
+        exporter.finish().unwrap();
+
+        // Extension is added based on the format
+        fs::remove_file(format!("{}.y4m", path_stem)).unwrap();
+    }
+
+    #[test]
+    fn test_frame_dimensions_mismatch() {
+        let path = test_path("mismatch.y4m");
+        let mut exporter = AnimationExporter::new(&path, AnimationFormat::Y4m, 1, 1, 10).unwrap();
+
+        exporter.add_frame(0, &RgbImage::new(3, 2)).unwrap();
+        assert!(exporter.add_frame(1, &RgbImage::new(2, 3)).is_err());
+
+        exporter.finish().unwrap();
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,215 @@
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+// Evaluator of the fitness by workers that get the genomes as JSON in batches, e.g. ExternalEvaluator or Coordinator
+// It can be called from many threads at once, e.g. from count_fitness, the waiting genomes are sent together
+pub trait BatchEvaluator: Send + Sync + 'static {
+    // Function returns the fitness of the genomes in their order, NaN if the worker answered null
+    fn evaluate_batch(&self, genomes: Vec<Value>) -> Vec<Result<f64, String>>;
+
+    fn evaluate(&self, genome: Value) -> Result<f64, String> {
+        self.evaluate_batch(vec![genome]).pop().unwrap()
+    }
+
+    // Function returns an objective of the built-in real-valued vector individual, the genes are sent as a JSON array
+    // Genomes that can't be evaluated get NaN fitness, so they are counted as invalid by the population
+    fn real_objective(self: Arc<Self>) -> impl Fn(&[f64]) -> f64 + Send + Sync + 'static
+    where
+        Self: Sized,
+    {
+        move |genes| self.evaluate(json!(genes)).unwrap_or(f64::NAN)
+    }
+}
+
+// Genome waiting for the evaluation, the fitness is sent back with the index of the genome in its batch
+pub(crate) struct Request {
+    pub genome: Value,
+    // Number of workers that got the genome and didn't answer its fitness
+    pub attempts: usize,
+    index: usize,
+    reply: Sender<(usize, Result<f64, String>)>,
+}
+
+impl Request {
+    pub fn answer(&self, result: Result<f64, String>) {
+        let _ = self.reply.send((self.index, result));
+    }
+}
+
+struct Queue {
+    requests: VecDeque<Request>,
+    closed: bool,
+}
+
+// Queue of the genomes shared by the callers and the threads serving the workers
+pub(crate) struct BatchQueue {
+    queue: Mutex<Queue>,
+    available: Condvar,
+    batch_size: usize,
+}
+
+impl BatchQueue {
+    pub fn new(batch_size: usize) -> Self {
+        BatchQueue {
+            queue: Mutex::new(Queue {
+                requests: VecDeque::new(),
+                closed: false,
+            }),
+            available: Condvar::new(),
+            batch_size,
+        }
+    }
+
+    // Function queues the genomes and waits for their fitness, genomes left when the queue is dropped get the error
+    pub fn evaluate_batch(&self, genomes: Vec<Value>, stopped: &str) -> Vec<Result<f64, String>> {
+        let n_genomes = genomes.len();
+        let (reply, replies) = mpsc::channel();
+        {
+            let mut queue = self.queue.lock().unwrap();
+            queue.requests.extend(
+                genomes
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, genome)| Request {
+                        genome,
+                        attempts: 0,
+                        index,
+                        reply: reply.clone(),
+                    }),
+            );
+        }
+        self.available.notify_all();
+        drop(reply);
+
+        let mut results = vec![Err(stopped.to_string()); n_genomes];
+        for (index, result) in replies.iter().take(n_genomes) {
+            results[index] = result;
+        }
+        results
+    }
+
+    // Function waits for up to batch_size waiting genomes, None when the queue is closed and empty
+    // The batch is empty if no genome came in the timeout
+    pub fn take_batch(&self, timeout: Option<Duration>) -> Option<Vec<Request>> {
+        let mut queue = self.queue.lock().unwrap();
+        while queue.requests.is_empty() && !queue.closed {
+            match timeout {
+                Some(timeout) => {
+                    let (new_queue, waited) = self.available.wait_timeout(queue, timeout).unwrap();
+                    queue = new_queue;
+                    if waited.timed_out() && queue.requests.is_empty() && !queue.closed {
+                        return Some(Vec::new());
+                    }
+                }
+                None => queue = self.available.wait(queue).unwrap(),
+            }
+        }
+        if queue.requests.is_empty() {
+            return None;
+        }
+
+        let n_requests = self.batch_size.min(queue.requests.len());
+        Some(queue.requests.drain(..n_requests).collect())
+    }
+
+    // Function puts the requests to the front of the queue in their order, so they are taken first
+    pub fn requeue(&self, requests: Vec<Request>) {
+        {
+            let mut queue = self.queue.lock().unwrap();
+            for request in requests.into_iter().rev() {
+                queue.requests.push_front(request);
+            }
+        }
+        self.available.notify_all();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.queue.lock().unwrap().closed
+    }
+
+    // Waiting genomes are still taken, then take_batch returns None
+    pub fn close(&self) {
+        self.queue.lock().unwrap().closed = true;
+        self.available.notify_all();
+    }
+}
+
+// Function reads the fitness of n_genomes answered by the worker, null is the fitness of an invalid genome
+pub(crate) fn parse_fitness(
+    fitness: &Value,
+    n_genomes: usize,
+    worker: &str,
+) -> Result<Vec<f64>, String> {
+    let fitness = match fitness.as_array() {
+        Some(fitness) if fitness.len() == n_genomes => fitness,
+        _ => {
+            return Err(format!(
+                "{} did not answer {} fitness values",
+                worker, n_genomes
+            ))
+        }
+    };
+
+    fitness
+        .iter()
+        .map(|value| match value {
+            Value::Null => Ok(f64::NAN),
+            value => value
+                .as_f64()
+                .ok_or_else(|| format!("Fitness '{}' is not a number", value)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_batch_queue() {
+        let queue = Arc::new(BatchQueue::new(2));
+        assert!(queue
+            .take_batch(Some(Duration::from_millis(10)))
+            .unwrap()
+            .is_empty());
+
+        // Genomes are taken in batches, requeued ones first
+        let caller = queue.clone();
+        let evaluation = thread::spawn(move || {
+            caller.evaluate_batch((0..3).map(|i| json!(i)).collect(), "Stopped")
+        });
+        let batch = queue.take_batch(None).unwrap();
+        assert_eq!(batch.len(), 2);
+        queue.requeue(batch);
+        let batch = queue.take_batch(None).unwrap();
+        assert_eq!(batch[0].genome, json!(0));
+        assert_eq!(batch[1].genome, json!(1));
+        for request in &batch {
+            request.answer(Ok(request.genome.as_f64().unwrap()));
+        }
+        drop(batch);
+
+        // Request dropped without the answer gets the error of the stopped queue
+        queue.close();
+        assert_eq!(queue.take_batch(None).unwrap().len(), 1);
+        assert!(queue.take_batch(None).is_none());
+        let results = evaluation.join().unwrap();
+        assert_eq!(results[0], Ok(0.0));
+        assert_eq!(results[1], Ok(1.0));
+        assert_eq!(results[2], Err("Stopped".to_string()));
+    }
+
+    #[test]
+    fn test_parse_fitness() {
+        let fitness = parse_fitness(&json!([1.5, null]), 2, "Worker").unwrap();
+        assert_eq!(fitness[0], 1.5);
+        assert!(fitness[1].is_nan());
+        assert!(parse_fitness(&json!([1.5]), 2, "Worker").is_err());
+        assert!(parse_fitness(&json!(["1.5"]), 1, "Worker").is_err());
+        assert!(parse_fitness(&Value::Null, 0, "Worker").is_err());
+    }
+}
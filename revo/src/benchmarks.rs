@@ -0,0 +1,581 @@
+use crate::bit_string::BitStringData;
+use crate::config::Config;
+use crate::evo_individual::EvoIndividual;
+use crate::population::Population;
+use crate::real_vector::RealVectorData;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::{Distribution, StandardNormal};
+use std::f64::consts::{E, PI};
+use std::fmt;
+use strum_macros::{Display, EnumIter, EnumString};
+
+const DEFAULT_TOLERANCE: f64 = 1e-4;
+
+// NK landscapes with more bits can't be searched exhaustively for the optimum
+const MAX_NK_EXHAUSTIVE_BITS: usize = 20;
+
+// Values of the MMDP subfunction by the number of ones in the block of six bits
+const MMDP_VALUES: [f64; 7] = [1.0, 0.0, 0.360384, 0.640576, 0.360384, 0.0, 1.0];
+const MMDP_BLOCK: usize = 6;
+
+// Classic continuous benchmark functions, all of them are minimised and have the minimum value of 0
+#[derive(Clone, Copy, PartialEq, Debug, EnumString, EnumIter, Display)]
+pub enum ContinuousBenchmark {
+    #[strum(serialize = "sphere")]
+    Sphere,
+    #[strum(serialize = "rastrigin")]
+    Rastrigin,
+    #[strum(serialize = "rosenbrock")]
+    Rosenbrock,
+    #[strum(serialize = "ackley")]
+    Ackley,
+    #[strum(serialize = "griewank")]
+    Griewank,
+    #[strum(serialize = "schwefel")]
+    Schwefel,
+    #[strum(serialize = "levy")]
+    Levy,
+}
+
+impl ContinuousBenchmark {
+    // Function returns the value of the benchmark function at the point x
+    pub fn evaluate(&self, x: &[f64]) -> f64 {
+        let n = x.len() as f64;
+
+        match self {
+            ContinuousBenchmark::Sphere => x.iter().map(|xi| xi * xi).sum(),
+            ContinuousBenchmark::Rastrigin => {
+                10.0 * n
+                    + x.iter()
+                        .map(|xi| xi * xi - 10.0 * (2.0 * PI * xi).cos())
+                        .sum::<f64>()
+            }
+            ContinuousBenchmark::Rosenbrock => x
+                .windows(2)
+                .map(|w| 100.0 * (w[1] - w[0] * w[0]).powi(2) + (1.0 - w[0]).powi(2))
+                .sum(),
+            ContinuousBenchmark::Ackley => {
+                if x.is_empty() {
+                    return 0.0;
+                }
+                let mean_square = x.iter().map(|xi| xi * xi).sum::<f64>() / n;
+                let mean_cos = x.iter().map(|xi| (2.0 * PI * xi).cos()).sum::<f64>() / n;
+                -20.0 * (-0.2 * mean_square.sqrt()).exp() - mean_cos.exp() + 20.0 + E
+            }
+            ContinuousBenchmark::Griewank => {
+                let sum = x.iter().map(|xi| xi * xi).sum::<f64>() / 4000.0;
+                let product = x
+                    .iter()
+                    .enumerate()
+                    .map(|(i, xi)| (xi / ((i + 1) as f64).sqrt()).cos())
+                    .product::<f64>();
+                1.0 + sum - product
+            }
+            ContinuousBenchmark::Schwefel => {
+                418.982_887_272_433_8 * n
+                    - x.iter().map(|xi| xi * xi.abs().sqrt().sin()).sum::<f64>()
+            }
+            ContinuousBenchmark::Levy => {
+                if x.is_empty() {
+                    return 0.0;
+                }
+                let w: Vec<f64> = x.iter().map(|xi| 1.0 + (xi - 1.0) / 4.0).collect();
+                let last = w[w.len() - 1];
+                let middle =
+                    |wi: &f64| (wi - 1.0).powi(2) * (1.0 + 10.0 * (PI * wi + 1.0).sin().powi(2));
+
+                (PI * w[0]).sin().powi(2)
+                    + w[..w.len() - 1].iter().map(middle).sum::<f64>()
+                    + (last - 1.0).powi(2) * (1.0 + (2.0 * PI * last).sin().powi(2))
+            }
+        }
+    }
+
+    // Function returns the usual search domain of the benchmark, the same for every dimension
+    pub fn bounds(&self) -> (f64, f64) {
+        match self {
+            ContinuousBenchmark::Sphere => (-5.12, 5.12),
+            ContinuousBenchmark::Rastrigin => (-5.12, 5.12),
+            ContinuousBenchmark::Rosenbrock => (-5.0, 10.0),
+            ContinuousBenchmark::Ackley => (-32.768, 32.768),
+            ContinuousBenchmark::Griewank => (-600.0, 600.0),
+            ContinuousBenchmark::Schwefel => (-500.0, 500.0),
+            ContinuousBenchmark::Levy => (-10.0, 10.0),
+        }
+    }
+
+    // Function returns the value of every coordinate of the global minimum
+    pub fn optimum_coordinate(&self) -> f64 {
+        match self {
+            ContinuousBenchmark::Rosenbrock | ContinuousBenchmark::Levy => 1.0,
+            ContinuousBenchmark::Schwefel => 420.968_746_359_982,
+            _ => 0.0,
+        }
+    }
+}
+
+// Function returns the value of the block of bits of the deceptive trap function of order k
+fn _trap_block(bits: &[bool], k: usize) -> f64 {
+    let ones = bits.iter().filter(|bit| **bit).count();
+    if ones == k {
+        k as f64
+    } else {
+        (k - 1 - ones) as f64
+    }
+}
+
+// Random NK landscape, every bit contributes to the fitness together with its k random neighbours
+#[derive(Clone)]
+pub struct NkLandscape {
+    pub n: usize,
+    pub k: usize,
+    neighbours: Vec<Vec<usize>>,
+    // Contribution of every bit for all combinations of its value and the values of its neighbours
+    contributions: Vec<Vec<f64>>,
+}
+
+impl NkLandscape {
+    pub fn new(n: usize, k: usize, seed: u64) -> Result<Self, String> {
+        if k >= n {
+            return Err(format!("K ({}) must be lower than N ({})", k, n));
+        }
+
+        let mut rng = SmallRng::seed_from_u64(seed);
+
+        let neighbours = (0..n)
+            .map(|i| {
+                let mut others: Vec<usize> = (0..n).filter(|&j| j != i).collect();
+                let mut chosen = Vec::with_capacity(k);
+                for _ in 0..k {
+                    chosen.push(others.swap_remove(rng.gen_range(0..others.len())));
+                }
+                chosen
+            })
+            .collect();
+
+        let contributions = (0..n)
+            .map(|_| (0..1 << (k + 1)).map(|_| rng.gen_range(0.0..1.0)).collect())
+            .collect();
+
+        Ok(NkLandscape {
+            n,
+            k,
+            neighbours,
+            contributions,
+        })
+    }
+
+    // Function returns the mean contribution of the bits
+    pub fn evaluate(&self, bits: &[bool]) -> f64 {
+        let mut sum = 0.0;
+
+        for i in 0..self.n {
+            let mut index = bits[i] as usize;
+            for &j in &self.neighbours[i] {
+                index = (index << 1) | bits[j] as usize;
+            }
+            sum += self.contributions[i][index];
+        }
+
+        sum / self.n as f64
+    }
+
+    // Function finds the optimum by evaluating all of the bit strings
+    pub fn exhaustive_optimum(&self) -> Result<f64, String> {
+        if self.n > MAX_NK_EXHAUSTIVE_BITS {
+            return Err(format!(
+                "Optimum of NK landscape with more than {} bits can't be searched exhaustively",
+                MAX_NK_EXHAUSTIVE_BITS
+            ));
+        }
+
+        let mut bits = vec![false; self.n];
+        let mut best = f64::MIN;
+        for value in 0..1usize << self.n {
+            for (i, bit) in bits.iter_mut().enumerate() {
+                *bit = (value >> i) & 1 == 1;
+            }
+            best = best.max(self.evaluate(&bits));
+        }
+
+        Ok(best)
+    }
+}
+
+// Benchmark problem with the data of the individuals and the best reachable fitness
+#[derive(Clone)]
+pub struct Benchmark<IndividualData> {
+    pub name: String,
+    pub ind_data: IndividualData,
+    pub optimum: f64,
+}
+
+impl Benchmark<RealVectorData> {
+    // Function creates the benchmark maximising the negative value of the function
+    pub fn continuous(function: ContinuousBenchmark, n_dims: usize) -> Self {
+        let (lower, upper) = function.bounds();
+
+        Benchmark {
+            name: format!("{}-{}", function, n_dims),
+            ind_data: RealVectorData::new(n_dims, lower, upper, move |x| -function.evaluate(x)),
+            optimum: 0.0,
+        }
+    }
+
+    // Function creates the CEC-style shifted and rotated variant of the benchmark
+    // The optimum is moved to a random point and the coordinates are rotated by a random orthogonal matrix,
+    // so the function is not separable anymore
+    pub fn shifted_rotated(function: ContinuousBenchmark, n_dims: usize, seed: u64) -> Self {
+        let (lower, upper) = function.bounds();
+        let mut rng = SmallRng::seed_from_u64(seed);
+
+        // Shift is kept within 80 % of the domain, so the optimum stays inside the bounds
+        let shift: Vec<f64> = (0..n_dims)
+            .map(|_| rng.gen_range(lower * 0.8..upper * 0.8))
+            .collect();
+        let rotation = Self::_random_rotation(n_dims, &mut rng);
+        let optimum_coordinate = function.optimum_coordinate();
+
+        let objective = move |x: &[f64]| {
+            // Rotated point is moved to the optimum of the original function and kept in its domain
+            let z: Vec<f64> = rotation
+                .iter()
+                .map(|row| {
+                    let rotated: f64 = row
+                        .iter()
+                        .zip(x.iter().zip(shift.iter()))
+                        .map(|(r, (xi, oi))| r * (xi - oi))
+                        .sum();
+                    (rotated + optimum_coordinate).clamp(lower, upper)
+                })
+                .collect();
+            -function.evaluate(&z)
+        };
+
+        Benchmark {
+            name: format!("shifted-rotated-{}-{}", function, n_dims),
+            ind_data: RealVectorData::new(n_dims, lower, upper, objective),
+            optimum: 0.0,
+        }
+    }
+
+    // Function returns random orthogonal matrix made by Gram-Schmidt process from normally distributed vectors
+    fn _random_rotation(n_dims: usize, rng: &mut SmallRng) -> Vec<Vec<f64>> {
+        let mut rows: Vec<Vec<f64>> = Vec::with_capacity(n_dims);
+
+        while rows.len() < n_dims {
+            let mut row: Vec<f64> = (0..n_dims).map(|_| StandardNormal.sample(rng)).collect();
+
+            for other in &rows {
+                let dot: f64 = row.iter().zip(other.iter()).map(|(a, b)| a * b).sum();
+                for (value, other_value) in row.iter_mut().zip(other.iter()) {
+                    *value -= dot * other_value;
+                }
+            }
+
+            let norm = row.iter().map(|value| value * value).sum::<f64>().sqrt();
+            // Vectors that are almost linearly dependent are drawn again
+            if norm > 1e-6 {
+                rows.push(row.iter().map(|value| value / norm).collect());
+            }
+        }
+
+        rows
+    }
+}
+
+impl Benchmark<BitStringData> {
+    pub fn one_max(n_bits: usize) -> Self {
+        Benchmark {
+            name: format!("onemax-{}", n_bits),
+            ind_data: BitStringData::new(n_bits, |bits| {
+                bits.iter().filter(|bit| **bit).count() as f64
+            }),
+            optimum: n_bits as f64,
+        }
+    }
+
+    // Concatenated deceptive traps of order k, the number of bits must be divisible by k
+    pub fn trap(n_bits: usize, k: usize) -> Result<Self, String> {
+        if k == 0 || !n_bits.is_multiple_of(k) {
+            return Err(format!(
+                "Number of bits {} is not divisible by {}",
+                n_bits, k
+            ));
+        }
+
+        Ok(Benchmark {
+            name: format!("trap{}-{}", k, n_bits),
+            ind_data: BitStringData::new(n_bits, move |bits| {
+                bits.chunks(k).map(|block| _trap_block(block, k)).sum()
+            }),
+            optimum: n_bits as f64,
+        })
+    }
+
+    // Massively multimodal deceptive problem, the number of bits must be divisible by 6
+    pub fn mmdp(n_bits: usize) -> Result<Self, String> {
+        if !n_bits.is_multiple_of(MMDP_BLOCK) {
+            return Err(format!(
+                "Number of bits {} is not divisible by {}",
+                n_bits, MMDP_BLOCK
+            ));
+        }
+
+        Ok(Benchmark {
+            name: format!("mmdp-{}", n_bits),
+            ind_data: BitStringData::new(n_bits, |bits| {
+                bits.chunks(MMDP_BLOCK)
+                    .map(|block| MMDP_VALUES[block.iter().filter(|bit| **bit).count()])
+                    .sum()
+            }),
+            optimum: (n_bits / MMDP_BLOCK) as f64,
+        })
+    }
+
+    // Random NK landscape, its optimum is found exhaustively, so N is limited to 20 bits
+    pub fn nk_landscape(n: usize, k: usize, seed: u64) -> Result<Self, String> {
+        let landscape = NkLandscape::new(n, k, seed)?;
+        let optimum = landscape.exhaustive_optimum()?;
+
+        Ok(Benchmark {
+            name: format!("nk-{}-{}", n, k),
+            ind_data: BitStringData::new(n, move |bits| landscape.evaluate(bits)),
+            optimum,
+        })
+    }
+}
+
+impl<IndividualData> Benchmark<IndividualData>
+where
+    IndividualData: Clone + Send + Sync,
+{
+    // Function runs the evolution for every seed for at most max_generations generations
+    // Run is successful when the best fitness gets within the tolerance from the optimum
+    pub fn run<Individual>(
+        &self,
+        config: &Config,
+        seeds: &[u64],
+        max_generations: usize,
+    ) -> BenchmarkReport
+    where
+        Individual: EvoIndividual<IndividualData>,
+    {
+        let tolerance = config
+            .may_get_float("benchmark_tolerance")
+            .unwrap()
+            .unwrap_or(DEFAULT_TOLERANCE);
+        let target = self.optimum - tolerance;
+
+        let runs = seeds
+            .iter()
+            .map(|&seed| {
+                let mut run_config = config.clone();
+                run_config.set("seed", seed);
+
+                let mut pop: Population<Individual, IndividualData> =
+                    Population::new_with_data(&run_config, self.ind_data.clone());
+                let mut evaluations_to_target = None;
+
+                loop {
+                    if pop.get_best().get_fitness() >= target {
+                        evaluations_to_target = Some(pop.get_evaluations());
+                        break;
+                    }
+                    if pop.get_generation() >= max_generations {
+                        break;
+                    }
+                    pop.next_gen();
+                }
+
+                BenchmarkRun {
+                    seed,
+                    best_fitness: pop.get_best().get_fitness(),
+                    generations: pop.get_generation(),
+                    evaluations_to_target,
+                }
+            })
+            .collect();
+
+        BenchmarkReport {
+            name: self.name.clone(),
+            optimum: self.optimum,
+            runs,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BenchmarkRun {
+    pub seed: u64,
+    pub best_fitness: f64,
+    pub generations: usize,
+    // Number of fitness evaluations when the target was reached, None if the run was not successful
+    pub evaluations_to_target: Option<usize>,
+}
+
+#[derive(Clone, Debug)]
+pub struct BenchmarkReport {
+    pub name: String,
+    pub optimum: f64,
+    pub runs: Vec<BenchmarkRun>,
+}
+
+impl BenchmarkReport {
+    pub fn get_n_successes(&self) -> usize {
+        self.runs
+            .iter()
+            .filter(|run| run.evaluations_to_target.is_some())
+            .count()
+    }
+
+    pub fn get_success_rate(&self) -> f64 {
+        if self.runs.is_empty() {
+            return 0.0;
+        }
+        self.get_n_successes() as f64 / self.runs.len() as f64
+    }
+
+    // Function returns the mean number of evaluations of the successful runs
+    pub fn get_mean_evaluations_to_target(&self) -> Option<f64> {
+        let n_successes = self.get_n_successes();
+        if n_successes == 0 {
+            return None;
+        }
+
+        let sum: usize = self
+            .runs
+            .iter()
+            .filter_map(|run| run.evaluations_to_target)
+            .sum();
+        Some(sum as f64 / n_successes as f64)
+    }
+
+    pub fn get_mean_best_fitness(&self) -> f64 {
+        self.runs.iter().map(|run| run.best_fitness).sum::<f64>() / self.runs.len() as f64
+    }
+}
+
+impl fmt::Display for BenchmarkReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: success rate {}/{} ({:.1} %), mean best fitness {}, optimum {}",
+            self.name,
+            self.get_n_successes(),
+            self.runs.len(),
+            self.get_success_rate() * 100.0,
+            self.get_mean_best_fitness(),
+            self.optimum
+        )?;
+
+        match self.get_mean_evaluations_to_target() {
+            Some(evaluations) => write!(f, ", mean evaluations to target {:.0}", evaluations),
+            None => write!(f, ", target not reached"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit_string::BitStringIndividual;
+    use crate::real_vector::RealVectorIndividual;
+    use std::str::FromStr;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn test_continuous_optima() {
+        for function in ContinuousBenchmark::iter() {
+            let optimum = vec![function.optimum_coordinate(); 5];
+            assert!(function.evaluate(&optimum).abs() < 1e-6, "{}", function);
+
+            // Any other point is worse
+            let other = vec![function.optimum_coordinate() + 0.5; 5];
+            assert!(function.evaluate(&other) > 1e-3, "{}", function);
+        }
+
+        assert_eq!(ContinuousBenchmark::Sphere.evaluate(&[1.0, 2.0]), 5.0);
+        assert_eq!(ContinuousBenchmark::Rosenbrock.evaluate(&[0.0, 0.0]), 1.0);
+        assert!((ContinuousBenchmark::Rastrigin.evaluate(&[1.0, 1.0]) - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_shifted_rotated() {
+        for function in ContinuousBenchmark::iter() {
+            let benchmark = Benchmark::shifted_rotated(function, 4, 7);
+            let (lower, upper) = function.bounds();
+
+            // Random rotation is orthogonal
+            let mut rng = SmallRng::seed_from_u64(1);
+            let rotation = Benchmark::<RealVectorData>::_random_rotation(4, &mut rng);
+            for i in 0..4 {
+                for j in 0..4 {
+                    let dot: f64 = (0..4).map(|c| rotation[i][c] * rotation[j][c]).sum();
+                    let expected = if i == j { 1.0 } else { 0.0 };
+                    assert!((dot - expected).abs() < 1e-9);
+                }
+            }
+
+            // The fitness never exceeds the optimum
+            for _ in 0..100 {
+                let x: Vec<f64> = (0..4).map(|_| rng.gen_range(lower..upper)).collect();
+                assert!((benchmark.ind_data.objective)(&x) <= benchmark.optimum + 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_discrete() {
+        let trap = Benchmark::trap(8, 4).unwrap();
+        assert_eq!((trap.ind_data.objective)(&[true; 8]), 8.0);
+        assert_eq!((trap.ind_data.objective)(&[false; 8]), 6.0);
+        assert_eq!(
+            (trap.ind_data.objective)(&[true, false, false, false, true, true, true, true]),
+            6.0
+        );
+        assert!(Benchmark::trap(10, 4).is_err());
+
+        let mmdp = Benchmark::mmdp(12).unwrap();
+        assert_eq!(mmdp.optimum, 2.0);
+        assert_eq!((mmdp.ind_data.objective)(&[false; 12]), 2.0);
+        assert!(Benchmark::mmdp(10).is_err());
+
+        // NK landscape with K = 0 is separable, so the optimum is the sum of the best contributions
+        let landscape = NkLandscape::new(6, 0, 3).unwrap();
+        let expected = landscape
+            .contributions
+            .iter()
+            .map(|c| c[0].max(c[1]))
+            .sum::<f64>()
+            / 6.0;
+        assert!((landscape.exhaustive_optimum().unwrap() - expected).abs() < 1e-12);
+        assert!(NkLandscape::new(3, 3, 0).is_err());
+        assert!(NkLandscape::new(30, 2, 0)
+            .unwrap()
+            .exhaustive_optimum()
+            .is_err());
+    }
+
+    #[test]
+    fn test_run() {
+        let config = Config::from_str(
+            "{\"pop_width\": 16, \"pop_height\": 16, \"mut_prob\": 0.02, \"mut_amount\": 0.0, \"crossover_prob\": 0.3}",
+        )
+        .unwrap();
+
+        let report = Benchmark::one_max(16).run::<BitStringIndividual>(&config, &[1, 2, 3], 50);
+        assert_eq!(report.runs.len(), 3);
+        assert_eq!(report.get_success_rate(), 1.0);
+        assert!(report.get_mean_evaluations_to_target().unwrap() >= 256.0);
+        assert_eq!(report.runs[0].seed, 1);
+
+        // Target can't be reached without any generations
+        let report = Benchmark::continuous(ContinuousBenchmark::Rastrigin, 10)
+            .run::<RealVectorIndividual>(&config, &[1], 0);
+        assert_eq!(report.get_success_rate(), 0.0);
+        assert!(report.get_mean_evaluations_to_target().is_none());
+        assert!(report.to_string().contains("target not reached"));
+    }
+}
@@ -0,0 +1,256 @@
+use crate::config::Config;
+use crate::evo_individual::{hash_value, EvoIndividual, RemoteIndividual};
+use crate::vector_ops::VectorCrossoverType;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use serde_json::{json, Value};
+use std::fmt;
+use std::sync::Arc;
+
+const DEFAULT_N_BITS: usize = 32;
+const DEFAULT_VECTOR_CROSSOVER_TYPE: VectorCrossoverType = VectorCrossoverType::Uniform;
+
+// Objective function over the bits, its value is the fitness that is maximised
+pub type BitObjective = Arc<dyn Fn(&[bool]) -> f64 + Send + Sync>;
+
+#[derive(Clone)]
+pub struct BitStringData {
+    pub n_bits: usize,
+    pub objective: BitObjective,
+    pub crossover_type: VectorCrossoverType,
+}
+
+impl BitStringData {
+    pub fn new<F>(n_bits: usize, objective: F) -> Self
+    where
+        F: Fn(&[bool]) -> f64 + Send + Sync + 'static,
+    {
+        BitStringData {
+            n_bits,
+            objective: Arc::new(objective),
+            crossover_type: DEFAULT_VECTOR_CROSSOVER_TYPE,
+        }
+    }
+
+    // Function creates data with the genome parameters from the config and the given objective
+    pub fn from_config<F>(config: &Config, objective: F) -> Result<Self, String>
+    where
+        F: Fn(&[bool]) -> f64 + Send + Sync + 'static,
+    {
+        let mut ind_data = Self::new(
+            config.may_get_uint("n_bits")?.unwrap_or(DEFAULT_N_BITS),
+            objective,
+        );
+        ind_data.crossover_type = config
+            .may_get_enum("vector_crossover")?
+            .unwrap_or(DEFAULT_VECTOR_CROSSOVER_TYPE);
+
+        Ok(ind_data)
+    }
+}
+
+#[derive(Clone)]
+pub struct BitStringIndividual {
+    pub fitness: f64,
+    pub bits: Vec<bool>,
+}
+
+impl BitStringIndividual {
+    pub fn new(bits: Vec<bool>) -> Self {
+        BitStringIndividual { fitness: 0.0, bits }
+    }
+
+    pub fn get_bits(&self) -> &[bool] {
+        &self.bits
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.bits.iter().filter(|bit| **bit).count()
+    }
+}
+
+impl EvoIndividual<BitStringData> for BitStringIndividual {
+    fn new_randomised(ind_data: &BitStringData, rng: &mut SmallRng) -> Self {
+        Self::new((0..ind_data.n_bits).map(|_| rng.gen_bool(0.5)).collect())
+    }
+
+    // Every bit is flipped with the probability of mutation, amount of mutation is not used
+    fn mutate(
+        &mut self,
+        _ind_data: &BitStringData,
+        rng: &mut SmallRng,
+        mut_prob: f32,
+        _mut_amount: f32,
+    ) {
+        for bit in self.bits.iter_mut() {
+            if rng.gen_range(0.0..1.0) < mut_prob {
+                *bit = !*bit;
+            }
+        }
+    }
+
+    fn crossover(
+        &self,
+        another_ind: &BitStringIndividual,
+        ind_data: &BitStringData,
+        rng: &mut SmallRng,
+    ) -> BitStringIndividual {
+        Self::new(
+            ind_data
+                .crossover_type
+                .apply(&self.bits, &another_ind.bits, rng),
+        )
+    }
+
+    // Child is written into the bits of dest
+    fn crossover_into(
+        &self,
+        another_ind: &BitStringIndividual,
+        ind_data: &BitStringData,
+        rng: &mut SmallRng,
+        dest: &mut BitStringIndividual,
+    ) {
+        dest.fitness = 0.0;
+        ind_data
+            .crossover_type
+            .apply_into(&self.bits, &another_ind.bits, rng, &mut dest.bits);
+    }
+
+    fn count_fitness(&mut self, ind_data: &BitStringData) {
+        self.fitness = (ind_data.objective)(&self.bits);
+    }
+
+    fn get_fitness(&self) -> f64 {
+        self.fitness
+    }
+
+    // Genome is copied into the allocation of this individual
+    fn copy_from(&mut self, other: &Self) {
+        self.fitness = other.fitness;
+        self.bits.clone_from(&other.bits);
+    }
+
+    // Visuals are the ratios of ones in the first and the second half of the bits
+    // Individuals with a small hamming distance have similar visuals
+    fn get_visuals(&self, _ind_data: &BitStringData) -> (f64, f64) {
+        let half = self.bits.len() / 2;
+        let ratio = |bits: &[bool]| {
+            if bits.is_empty() {
+                0.0
+            } else {
+                bits.iter().filter(|bit| **bit).count() as f64 / bits.len() as f64
+            }
+        };
+
+        (ratio(&self.bits[..half]), ratio(&self.bits[half..]))
+    }
+
+    // Distance is the hamming distance between the bits
+    fn distance(&self, other: &Self, _ind_data: &BitStringData) -> f64 {
+        self.bits
+            .iter()
+            .zip(other.bits.iter())
+            .filter(|(a, b)| a != b)
+            .count() as f64
+    }
+
+    fn genome_hash(&self, _ind_data: &BitStringData) -> u64 {
+        hash_value(&self.bits)
+    }
+}
+
+// Genome is sent as the array of the bits
+impl RemoteIndividual<BitStringData> for BitStringIndividual {
+    fn genome_to_json(&self) -> Value {
+        json!(self.bits)
+    }
+
+    fn from_genome_json(genome: &Value, ind_data: &BitStringData) -> Result<Self, String> {
+        let bits = genome
+            .as_array()
+            .and_then(|bits| {
+                bits.iter()
+                    .map(Value::as_bool)
+                    .collect::<Option<Vec<bool>>>()
+            })
+            .ok_or_else(|| format!("Genome '{}' is not an array of booleans", genome))?;
+        if bits.len() != ind_data.n_bits {
+            return Err(format!(
+                "Genome has {} bits instead of {}",
+                bits.len(),
+                ind_data.n_bits
+            ));
+        }
+
+        Ok(Self::new(bits))
+    }
+
+    fn set_fitness(&mut self, fitness: f64) {
+        self.fitness = fitness;
+    }
+}
+
+impl fmt::Display for BitStringIndividual {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for bit in &self.bits {
+            write!(f, "{}", if *bit { '1' } else { '0' })?;
+        }
+        write!(f, ", fitness: {}", self.fitness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::population::Population;
+    use rand::SeedableRng;
+    use std::str::FromStr;
+
+    fn one_max(bits: &[bool]) -> f64 {
+        bits.iter().filter(|bit| **bit).count() as f64
+    }
+
+    #[test]
+    fn test_operators() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let ind_data = BitStringData::new(16, one_max);
+
+        let mut ind = BitStringIndividual::new(vec![false; 16]);
+        ind.mutate(&ind_data, &mut rng, 0.0, 1.0);
+        assert_eq!(ind.count_ones(), 0);
+        ind.mutate(&ind_data, &mut rng, 1.0, 1.0);
+        assert_eq!(ind.count_ones(), 16);
+
+        let first = BitStringIndividual::new_randomised(&ind_data, &mut rng);
+        let child = first.crossover(&first, &ind_data, &mut rng);
+        assert_eq!(child.bits, first.bits);
+
+        // Crossover written into an individual reuses its bits
+        let mut dest = BitStringIndividual::new(vec![false; 4]);
+        first.crossover_into(&first, &ind_data, &mut rng, &mut dest);
+        assert_eq!(dest.bits, first.bits);
+
+        let ind = BitStringIndividual::new(vec![true, true, false, true]);
+        assert_eq!(ind.get_visuals(&ind_data), (1.0, 0.5));
+        assert_eq!(ind.to_string(), "1101, fitness: 0");
+    }
+
+    #[test]
+    fn test_population() {
+        let config = Config::from_str(
+            "{\"pop_width\": 16, \"pop_height\": 16, \"mut_prob\": 0.02, \"mut_amount\": 0.0, \"crossover_prob\": 0.3, \"seed\": 1, \"n_bits\": 24, \"vector_crossover\": \"two_point\"}",
+        )
+        .unwrap();
+        let ind_data = BitStringData::from_config(&config, one_max).unwrap();
+        assert_eq!(ind_data.crossover_type, VectorCrossoverType::TwoPoint);
+
+        let mut pop: Population<BitStringIndividual, BitStringData> =
+            Population::new_with_data(&config, ind_data);
+        for _ in 0..50 {
+            pop.next_gen();
+        }
+
+        assert_eq!(pop.get_best().get_fitness(), 24.0);
+        pop.visualise();
+    }
+}
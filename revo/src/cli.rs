@@ -0,0 +1,606 @@
+use crate::animation::AnimationFormat;
+use crate::config::{Config, DEFAULT_CONFIG_FILENAME};
+use crate::evo_individual::{EvoIndividual, RemoteIndividual};
+use crate::population::Population;
+use crate::run_logger::{LogFormat, RunLogger};
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use strum::IntoEnumIterator;
+
+const DEFAULT_OUTPUT_DIR: &str = "./out";
+const MANIFEST_FILENAME: &str = "manifest.json";
+const LOG_STEM: &str = "log";
+pub const HALL_OF_FAME_FILENAME: &str = "hall_of_fame.json";
+// Config keys of the external and distributed evaluators, their fitness can't be replayed when the run is resumed
+const NON_REPLAYABLE_KEY_PREFIXES: [&str; 3] = ["external_", "coordinator_", "distributed_"];
+// Files written by the runs of the binaries built on revo, --overwrite removes only these
+const RUN_OUTPUT_FILENAMES: [&str; 6] = [
+    MANIFEST_FILENAME,
+    "log.csv",
+    "log.jsonl",
+    HALL_OF_FAME_FILENAME,
+    "genealogy.dot",
+    "genealogy.json",
+];
+// Animations are named by the stem and the extension of their format, segments of resumed runs by the stem and their
+// first generation
+const ANIMATION_STEMS: [&str; 2] = ["population", "best"];
+// Images of single generations are named by the prefix and the generation
+const IMAGE_PREFIXES: [&str; 3] = ["best", "pop", "ind"];
+
+// Set by Ctrl-C, the run stops after the current generation, so its outputs are finished properly
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Verbosity {
+    // Only errors are printed
+    Quiet,
+    // New records and other progress
+    Normal,
+    // Statistics of every generation
+    Verbose,
+}
+
+// Command line arguments shared by the binaries built on revo
+#[derive(Clone, Debug, PartialEq)]
+pub struct CliArgs {
+    pub config_path: String,
+    pub output_dir: String,
+    // Seed overriding the seed of the config
+    pub seed: Option<u64>,
+    // Number of generations after which the run stops, the run never stops if it is None
+    pub max_generations: Option<usize>,
+    // Continue the run stored in the output directory
+    pub resume: bool,
+    // Log format overriding the log_format of the config
+    pub log_format: Option<LogFormat>,
+    pub verbosity: Verbosity,
+    // Outputs of the previous run in the output directory are replaced instead of failing
+    pub overwrite: bool,
+    pub help: bool,
+}
+
+impl Default for CliArgs {
+    fn default() -> Self {
+        CliArgs {
+            config_path: DEFAULT_CONFIG_FILENAME.to_string(),
+            output_dir: DEFAULT_OUTPUT_DIR.to_string(),
+            seed: None,
+            max_generations: None,
+            resume: false,
+            log_format: None,
+            verbosity: Verbosity::Normal,
+            overwrite: false,
+            help: false,
+        }
+    }
+}
+
+impl CliArgs {
+    // Function parses the arguments without the program name
+    pub fn parse<I, S>(args: I) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut cli_args = CliArgs::default();
+        let mut args = args.into_iter().map(|arg| arg.into());
+
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or(format!("Missing value of argument '{}'", name))
+            };
+
+            match arg.as_str() {
+                "-c" | "--config" => cli_args.config_path = value(&arg)?,
+                "-o" | "--output" => cli_args.output_dir = value(&arg)?,
+                "-s" | "--seed" => cli_args.seed = Some(Self::_parse_number(&arg, &value(&arg)?)?),
+                "-g" | "--generations" => {
+                    cli_args.max_generations = Some(Self::_parse_number(&arg, &value(&arg)?)?)
+                }
+                "--log-format" => {
+                    let format = value(&arg)?;
+                    cli_args.log_format = Some(LogFormat::from_str(&format).map_err(|_| {
+                        format!("Unknown log format '{}', options are: csv, jsonl", format)
+                    })?)
+                }
+                "-r" | "--resume" => cli_args.resume = true,
+                "-q" | "--quiet" => cli_args.verbosity = Verbosity::Quiet,
+                "-v" | "--verbose" => cli_args.verbosity = Verbosity::Verbose,
+                "--overwrite" => cli_args.overwrite = true,
+                "-h" | "--help" => cli_args.help = true,
+                _ => return Err(format!("Unknown argument '{}'", arg)),
+            }
+        }
+
+        if cli_args.resume && cli_args.overwrite {
+            return Err("Arguments --resume and --overwrite cannot be used together".to_string());
+        }
+        if cli_args.resume && (cli_args.seed.is_some() || cli_args.log_format.is_some()) {
+            return Err(
+                "Seed and log format of a resumed run are taken from its manifest".to_string(),
+            );
+        }
+
+        Ok(cli_args)
+    }
+
+    // Function parses the arguments of the process, prints the usage and exits on --help or an error
+    pub fn from_env() -> Self {
+        let mut args = std::env::args();
+        let program = args.next().unwrap_or_else(|| "revo".to_string());
+
+        match Self::parse(args) {
+            Ok(cli_args) if cli_args.help => {
+                println!("{}", Self::usage(&program));
+                std::process::exit(0);
+            }
+            Ok(cli_args) => cli_args,
+            Err(err) => {
+                eprintln!("{}\n\n{}", err, Self::usage(&program));
+                std::process::exit(2);
+            }
+        }
+    }
+
+    pub fn usage(program: &str) -> String {
+        format!(
+            "Usage: {} [OPTIONS]
+
+Options:
+  -c, --config <PATH>        Config file [default: {}]
+  -o, --output <DIR>         Output directory [default: {}]
+  -s, --seed <SEED>          Seed overriding the seed of the config
+  -g, --generations <N>      Stop after N generations, the run never stops by default
+      --log-format <FORMAT>  Format of the log, csv or jsonl
+  -r, --resume               Continue the run stored in the output directory
+      --overwrite            Replace the outputs of the previous run in the output directory
+  -q, --quiet                Print only errors
+  -v, --verbose              Print statistics of every generation
+  -h, --help                 Print this help",
+            program, DEFAULT_CONFIG_FILENAME, DEFAULT_OUTPUT_DIR
+        )
+    }
+
+    // Function returns the path of the file in the output directory
+    pub fn output_path(&self, filename: &str) -> String {
+        Path::new(&self.output_dir)
+            .join(filename)
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    // Function loads the config and applies the overrides of the arguments
+    // The seed is always written into the config, so the individual data can use it and the run can be resumed
+    // Resumed run uses the config stored in the manifest of the output directory
+    pub fn load_config(&self) -> Result<Config, String> {
+        if self.resume {
+            let manifest_path = self.output_path(MANIFEST_FILENAME);
+            let data = fs::read_to_string(&manifest_path)
+                .map_err(|err| format!("Reading manifest '{}' failed: {}", manifest_path, err))?;
+            let manifest: Value = serde_json::from_str(&data)
+                .map_err(|err| format!("Parsing manifest '{}' failed: {}", manifest_path, err))?;
+
+            return Ok(Config {
+                json: manifest["config"].clone(),
+            });
+        }
+
+        let mut config = Config::from_file(&self.config_path)?;
+
+        let seed = match self.seed {
+            Some(seed) => seed,
+            None => config
+                .may_get_uint("seed")?
+                .unwrap_or_else(|| SmallRng::from_entropy().gen()),
+        };
+        config.set("seed", seed);
+
+        if let Some(format) = self.log_format {
+            config.set("log_format", format.to_string());
+        }
+
+        Ok(config)
+    }
+
+    // Function returns the path stem of the animation with the given name in the output directory
+    // Resumed run writes a new segment named by its first generation, so the animation of the previous run is kept
+    pub fn animation_stem(&self, name: &str, generation: usize) -> String {
+        match self.resume {
+            true => self.output_path(&format!("{}_from_{}", name, generation)),
+            false => self.output_path(name),
+        }
+    }
+
+    // Function prepares the output directory, a non-empty directory is used only with --overwrite or --resume
+    // Overwriting removes only the outputs of the previous run, other files of the directory are kept
+    pub fn prepare_output_dir(&self) -> Result<(), String> {
+        let path = Path::new(&self.output_dir);
+
+        if self.resume {
+            if !Path::new(&self.output_path(MANIFEST_FILENAME)).exists() {
+                return Err(format!(
+                    "Output directory '{}' has no run to resume",
+                    self.output_dir
+                ));
+            }
+            return Ok(());
+        }
+
+        let is_empty = match fs::read_dir(path) {
+            Ok(mut entries) => entries.next().is_none(),
+            Err(_) => true,
+        };
+
+        if !is_empty {
+            if !self.overwrite {
+                return Err(format!(
+                    "Output directory '{}' is not empty, use --overwrite to replace it or --resume to continue the run",
+                    self.output_dir
+                ));
+            }
+            self._remove_run_outputs()?;
+        }
+
+        fs::create_dir_all(path).map_err(|err| {
+            format!(
+                "Creating output directory '{}' failed: {}",
+                self.output_dir, err
+            )
+        })
+    }
+
+    // Function starts the run of the population and returns the logger of the output directory
+    // New run writes the manifest, resumed run replays the generations of the log and appends to it
+    // Replaying gives the same population, because the evolution is determined by the seed and the config, so runs
+    // evaluated by the external or distributed evaluators are not resumed
+    // From now on, Ctrl-C stops the run after the current generation, see should_continue
+    pub fn start_run<Individual, IndividualData>(
+        &self,
+        config: &Config,
+        pop: &mut Population<Individual, IndividualData>,
+    ) -> Result<RunLogger, String>
+    where
+        Individual: EvoIndividual<IndividualData>,
+        IndividualData: Send + Sync,
+    {
+        let log_stem = self.output_path(LOG_STEM);
+        if self.resume {
+            Self::_check_replayable(config)?;
+        }
+        // Handler can be set only once per process, it is already set if another run was started
+        let _ = ctrlc::set_handler(Self::_interrupt);
+
+        if !self.resume {
+            RunLogger::write_manifest(&self.output_path(MANIFEST_FILENAME), config, pop)?;
+            return RunLogger::from_config(config, &log_stem);
+        }
+
+        let (logger, last_generation) = RunLogger::append_from_config(config, &log_stem)?;
+        if let Some(last_generation) = last_generation {
+            self.info(&format!("Replaying {} generations", last_generation + 1));
+            while pop.get_generation() <= last_generation {
+                pop.next_gen();
+            }
+        }
+
+        Ok(logger)
+    }
+
+    // Function adds the entries exported by the interrupted run to the hall of fame of the resumed run, so they are kept
+    // even if the replay doesn't find them again, e.g. if the individual data are not created deterministically
+    // Nothing is done for a new run or if the interrupted run has not exported the hall of fame yet
+    pub fn resume_hall_of_fame<Individual, IndividualData>(
+        &self,
+        pop: &mut Population<Individual, IndividualData>,
+    ) -> Result<(), String>
+    where
+        Individual: RemoteIndividual<IndividualData>,
+        IndividualData: Send + Sync,
+    {
+        let path = self.output_path(HALL_OF_FAME_FILENAME);
+        if !self.resume || !Path::new(&path).exists() {
+            return Ok(());
+        }
+        pop.read_hall_of_fame(&path)
+    }
+
+    // Function returns true if the run has not reached the maximum number of generations and was not interrupted
+    pub fn should_continue(&self, generation: usize) -> bool {
+        !INTERRUPTED.load(Ordering::SeqCst)
+            && self
+                .max_generations
+                .is_none_or(|max_generations| generation < max_generations)
+    }
+
+    // Function prints the message unless the output is quiet
+    pub fn info(&self, message: &str) {
+        if self.verbosity != Verbosity::Quiet {
+            println!("{}", message);
+        }
+    }
+
+    // Function prints the message only if the output is verbose
+    pub fn debug(&self, message: &str) {
+        if self.verbosity == Verbosity::Verbose {
+            println!("{}", message);
+        }
+    }
+
+    fn _remove_run_outputs(&self) -> Result<(), String> {
+        let entries = fs::read_dir(&self.output_dir).map_err(|err| {
+            format!(
+                "Reading output directory '{}' failed: {}",
+                self.output_dir, err
+            )
+        })?;
+
+        for entry in entries.map_while(Result::ok) {
+            let is_file = entry.file_type().is_ok_and(|file_type| file_type.is_file());
+            let filename = entry.file_name();
+            if !is_file || !filename.to_str().is_some_and(Self::_is_run_output) {
+                continue;
+            }
+
+            fs::remove_file(entry.path()).map_err(|err| {
+                format!(
+                    "Removing output '{}' failed: {}",
+                    entry.path().display(),
+                    err
+                )
+            })?;
+        }
+
+        Ok(())
+    }
+
+    // Function returns an error if the fitness may be evaluated outside of the process, the replay would differ
+    fn _check_replayable(config: &Config) -> Result<(), String> {
+        let keys = match config.json.as_object() {
+            Some(object) => object.keys(),
+            None => return Ok(()),
+        };
+        for key in keys {
+            if NON_REPLAYABLE_KEY_PREFIXES
+                .iter()
+                .any(|prefix| key.starts_with(prefix))
+            {
+                return Err(format!(
+                    "Run with the external or distributed evaluation (config value '{}') can't be resumed",
+                    key
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // Function returns true if the file of the output directory is written by a run
+    fn _is_run_output(filename: &str) -> bool {
+        if RUN_OUTPUT_FILENAMES.contains(&filename) {
+            return true;
+        }
+
+        let Some((stem, extension)) = filename.rsplit_once('.') else {
+            return false;
+        };
+        if !AnimationFormat::iter().any(|format| format.extension() == extension) {
+            return false;
+        }
+
+        let is_numbered = |stem: &str, prefix: &str| {
+            stem.strip_prefix(prefix)
+                .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+        };
+
+        ANIMATION_STEMS
+            .iter()
+            .any(|name| stem == *name || is_numbered(stem, &format!("{}_from_", name)))
+            || (extension == "png"
+                && IMAGE_PREFIXES
+                    .iter()
+                    .any(|prefix| is_numbered(stem, &format!("{}_", prefix))))
+    }
+
+    // Second Ctrl-C exits right away, e.g. if the generation takes too long
+    fn _interrupt() {
+        if INTERRUPTED.swap(true, Ordering::SeqCst) {
+            std::process::exit(130);
+        }
+        eprintln!("Stopping after the current generation, press Ctrl-C again to exit immediately");
+    }
+
+    fn _parse_number<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+        value
+            .parse()
+            .map_err(|_| format!("Value '{}' of argument '{}' is not a number", value, name))
+    }
+}
+
+// Function returns the value, or prints the error and exits the process
+pub fn or_exit<T>(result: Result<T, String>) -> T {
+    result.unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockIndividual, MockIndividualData};
+
+    fn test_dir(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("revo_cli_{}_{}", std::process::id(), name))
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_parse() {
+        let args = CliArgs::parse(Vec::<String>::new()).unwrap();
+        assert_eq!(args, CliArgs::default());
+
+        let args = CliArgs::parse([
+            "-c",
+            "salesman.json5",
+            "--output",
+            "runs/1",
+            "--seed",
+            "7",
+            "-g",
+            "100",
+            "--log-format",
+            "jsonl",
+            "-q",
+        ])
+        .unwrap();
+        assert_eq!(args.config_path, "salesman.json5");
+        assert_eq!(args.output_dir, "runs/1");
+        assert_eq!(args.seed, Some(7));
+        assert_eq!(args.max_generations, Some(100));
+        assert_eq!(args.log_format, Some(LogFormat::Jsonl));
+        assert_eq!(args.verbosity, Verbosity::Quiet);
+        assert!(!args.should_continue(100));
+        assert!(args.should_continue(99));
+
+        assert!(CliArgs::parse(["--seed"]).is_err());
+        assert!(CliArgs::parse(["--seed", "x"]).is_err());
+        assert!(CliArgs::parse(["--log-format", "xml"]).is_err());
+        assert!(CliArgs::parse(["--unknown"]).is_err());
+        assert!(CliArgs::parse(["--resume", "--overwrite"]).is_err());
+        assert!(CliArgs::parse(["--resume", "--seed", "1"]).is_err());
+    }
+
+    #[test]
+    fn test_output_dir_protection() {
+        let output_dir = test_dir("protection");
+        let _ = fs::remove_dir_all(&output_dir);
+        let args = CliArgs::parse(["-o", output_dir.as_str()]).unwrap();
+
+        // Missing or empty directory can be used
+        args.prepare_output_dir().unwrap();
+        args.prepare_output_dir().unwrap();
+
+        // Non-empty directory is kept unless overwriting is allowed
+        fs::write(args.output_path("best_0.png"), "").unwrap();
+        assert!(args.prepare_output_dir().is_err());
+        assert!(Path::new(&args.output_path("best_0.png")).exists());
+
+        // Directory without manifest cannot be resumed
+        let resume_args = CliArgs::parse(["-o", output_dir.as_str(), "-r"]).unwrap();
+        assert!(resume_args.prepare_output_dir().is_err());
+
+        // Overwriting removes the outputs of the run only
+        for filename in [
+            "manifest.json",
+            "log.csv",
+            "population.gif",
+            "best.y4m",
+            "best_from_12.png",
+        ] {
+            fs::write(args.output_path(filename), "").unwrap();
+        }
+        fs::write(args.output_path("notes.txt"), "").unwrap();
+        fs::write(args.output_path("best_plot.png"), "").unwrap();
+        fs::create_dir(args.output_path("data")).unwrap();
+
+        let overwrite_args = CliArgs::parse(["-o", output_dir.as_str(), "--overwrite"]).unwrap();
+        overwrite_args.prepare_output_dir().unwrap();
+        let mut kept: Vec<String> = fs::read_dir(&output_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        kept.sort();
+        assert_eq!(kept, vec!["best_plot.png", "data", "notes.txt"]);
+
+        fs::remove_dir_all(&output_dir).unwrap();
+    }
+
+    #[test]
+    fn test_resume() {
+        let output_dir = test_dir("resume");
+        let _ = fs::remove_dir_all(&output_dir);
+        let config_path = format!("{}.json5", output_dir);
+        fs::write(&config_path, "{\"pop_width\": 3, \"pop_height\": 2}").unwrap();
+
+        // New run gets a seed and logs 3 generations
+        let args = CliArgs::parse(["-c", config_path.as_str(), "-o", output_dir.as_str()]).unwrap();
+        args.prepare_output_dir().unwrap();
+        let config = args.load_config().unwrap();
+        assert!(config.may_get_uint::<u64>("seed").unwrap().is_some());
+
+        let mut pop: Population<MockIndividual, MockIndividualData> = Population::new(&config);
+        let mut logger = args.start_run(&config, &mut pop).unwrap();
+        for _ in 0..3 {
+            logger.log(&pop).unwrap();
+            pop.next_gen();
+        }
+        drop(logger);
+
+        // Exported hall of fame contains an entry the replay won't find
+        let mut hall_of_fame = pop.get_hall_of_fame().to_json(pop.get_individual_data());
+        hall_of_fame
+            .as_array_mut()
+            .unwrap()
+            .push(serde_json::json!({
+                "generation": 1,
+                "genome": {"value": 100.0, "visuals": [0.0, 100.0]},
+            }));
+        fs::write(
+            args.output_path(HALL_OF_FAME_FILENAME),
+            hall_of_fame.to_string(),
+        )
+        .unwrap();
+        args.resume_hall_of_fame(&mut pop).unwrap();
+        assert!(pop.get_hall_of_fame().get_best().unwrap().fitness < 100.0);
+
+        // Resumed run continues after the last logged generation with the same config
+        let resume_args = CliArgs::parse(["-o", output_dir.as_str(), "--resume", "-q"]).unwrap();
+        resume_args.prepare_output_dir().unwrap();
+        let resumed_config = resume_args.load_config().unwrap();
+        assert_eq!(resumed_config.json, config.json);
+
+        let mut resumed_pop: Population<MockIndividual, MockIndividualData> =
+            Population::new(&resumed_config);
+        let mut logger = resume_args
+            .start_run(&resumed_config, &mut resumed_pop)
+            .unwrap();
+        assert_eq!(resumed_pop.get_generation(), 3);
+        resume_args.resume_hall_of_fame(&mut resumed_pop).unwrap();
+        let best = resumed_pop.get_hall_of_fame().get_best().unwrap();
+        assert_eq!((best.fitness, best.generation), (100.0, 1));
+        assert_eq!(args.animation_stem("best", 3), args.output_path("best"));
+        assert_eq!(
+            resume_args.animation_stem("best", 3),
+            resume_args.output_path("best_from_3")
+        );
+        assert_eq!(resumed_pop.get_seed(), pop.get_seed());
+        logger.log(&resumed_pop).unwrap();
+        drop(logger);
+
+        let log = fs::read_to_string(args.output_path("log.csv")).unwrap();
+        assert_eq!(log.lines().count(), 5);
+
+        // Run with the external evaluation can't be replayed
+        let mut external_config = resumed_config.clone();
+        external_config.json["external_command"] = serde_json::json!("python3 worker.py");
+        let mut external_pop: Population<MockIndividual, MockIndividualData> =
+            Population::new(&external_config);
+        assert!(resume_args
+            .start_run(&external_config, &mut external_pop)
+            .is_err());
+        assert_eq!(external_pop.get_generation(), 0);
+
+        fs::remove_dir_all(&output_dir).unwrap();
+        fs::remove_file(&config_path).unwrap();
+    }
+}
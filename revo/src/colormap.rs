@@ -0,0 +1,90 @@
+use strum_macros::{Display, EnumIter, EnumString};
+
+// Control points of the perceptual colormaps, sampled uniformly from 0.0 to 1.0
+const VIRIDIS: [[u8; 3]; 9] = [
+    [68, 1, 84],
+    [71, 44, 122],
+    [59, 81, 139],
+    [44, 113, 142],
+    [33, 144, 141],
+    [39, 173, 129],
+    [92, 200, 99],
+    [170, 220, 50],
+    [253, 231, 37],
+];
+
+const MAGMA: [[u8; 3]; 9] = [
+    [0, 0, 4],
+    [28, 16, 68],
+    [79, 18, 123],
+    [129, 37, 129],
+    [181, 54, 122],
+    [229, 80, 100],
+    [251, 135, 97],
+    [254, 194, 135],
+    [252, 253, 191],
+];
+
+const GRAYSCALE: [[u8; 3]; 2] = [[0, 0, 0], [255, 255, 255]];
+
+#[derive(Clone, Copy, PartialEq, Debug, EnumString, EnumIter, Display)]
+pub enum Colormap {
+    #[strum(serialize = "viridis")]
+    Viridis,
+    #[strum(serialize = "magma")]
+    Magma,
+    #[strum(serialize = "grayscale")]
+    Grayscale,
+}
+
+impl Colormap {
+    // Function maps value from range 0.0 to 1.0 to RGB colour
+    // Values out of the range are clamped, NaN is mapped to the lowest colour
+    pub fn map(&self, value: f64) -> [u8; 3] {
+        let points: &[[u8; 3]] = match self {
+            Colormap::Viridis => &VIRIDIS,
+            Colormap::Magma => &MAGMA,
+            Colormap::Grayscale => &GRAYSCALE,
+        };
+
+        let value = if value.is_nan() {
+            0.0
+        } else {
+            value.clamp(0.0, 1.0)
+        };
+
+        // Find the two control points around the value and interpolate between them
+        let pos = value * (points.len() - 1) as f64;
+        let i = (pos.floor() as usize).min(points.len() - 2);
+        let t = pos - i as f64;
+
+        let mut rgb = [0u8; 3];
+        for (c, item) in rgb.iter_mut().enumerate() {
+            let from = points[i][c] as f64;
+            let to = points[i + 1][c] as f64;
+            *item = (from + (to - from) * t).round() as u8;
+        }
+        rgb
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map() {
+        // Ends of the range are the first and last control points
+        assert_eq!(Colormap::Viridis.map(0.0), VIRIDIS[0]);
+        assert_eq!(Colormap::Viridis.map(1.0), VIRIDIS[8]);
+        assert_eq!(Colormap::Magma.map(0.5), MAGMA[4]);
+
+        // Values are interpolated between control points
+        assert_eq!(Colormap::Grayscale.map(0.5), [128, 128, 128]);
+
+        // Values out of the range are clamped
+        assert_eq!(Colormap::Magma.map(-1.0), MAGMA[0]);
+        assert_eq!(Colormap::Magma.map(f64::INFINITY), MAGMA[8]);
+        assert_eq!(Colormap::Grayscale.map(f64::NAN), [0, 0, 0]);
+    }
+}
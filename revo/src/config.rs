@@ -161,14 +161,27 @@ impl Config {
         }
     }
 
+    // Set the value in the JSON data, existing value of the key is replaced
+    pub fn set<T: Into<Value>>(&mut self, key: &str, value: T) {
+        self.json[key] = value.into();
+    }
+
     pub fn new(config_filename: &str) -> Self {
-        let mut file = File::open(config_filename).unwrap();
+        Self::from_file(config_filename).unwrap()
+    }
+
+    // Load the config from the file, errors are returned instead of panicking
+    pub fn from_file(config_filename: &str) -> Result<Self, String> {
+        let mut file = File::open(config_filename)
+            .map_err(|err| format!("Opening config '{}' failed: {}", config_filename, err))?;
         let mut data = String::new();
-        file.read_to_string(&mut data).unwrap();
+        file.read_to_string(&mut data)
+            .map_err(|err| format!("Reading config '{}' failed: {}", config_filename, err))?;
 
-        let json = json5::from_str(&data).unwrap();
+        let json = json5::from_str(&data)
+            .map_err(|err| format!("Parsing config '{}' failed: {}", config_filename, err))?;
 
-        Config { json }
+        Ok(Config { json })
     }
 }
 
@@ -219,6 +232,16 @@ mod tests {
         assert_eq!(test_int, -2);
     }
 
+    #[test]
+    fn test_set() {
+        let mut config = Config::from_str("{\"pop_width\": 3}").unwrap();
+        config.set("pop_width", 5);
+        config.set("seed", 42u64);
+
+        assert_eq!(config.get_uint::<usize>("pop_width").unwrap(), 5);
+        assert_eq!(config.get_uint::<u64>("seed").unwrap(), 42);
+    }
+
     #[test]
     fn test_val() {
         let config =
@@ -246,6 +269,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_bool() {
         let config = Config::from_str("{\"test_bool\":true, \"another_test_bool\":false}").unwrap();
 
@@ -0,0 +1,521 @@
+use crate::batch_queue::{parse_fitness, BatchEvaluator, BatchQueue, Request};
+use crate::config::Config;
+pub use crate::evo_individual::RemoteIndividual;
+use rayon::prelude::*;
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+const DEFAULT_COORDINATOR_ADDRESS: &str = "127.0.0.1:7878";
+const DEFAULT_DISTRIBUTED_BATCH_SIZE: usize = 16;
+const DEFAULT_DISTRIBUTED_TIMEOUT: f64 = 30.0;
+const DEFAULT_DISTRIBUTED_RETRIES: usize = 2;
+// Interval of checking whether the idle workers are still connected
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_millis(200);
+
+// State shared by the callers, the accepting thread and the threads serving the workers
+struct Shared {
+    batches: BatchQueue,
+    // Config sent to the workers when they register, they create the data of the individuals from it
+    config: Value,
+    timeout: Duration,
+    max_retries: usize,
+    n_workers: AtomicUsize,
+}
+
+// Line-delimited JSON connection to the other side of the protocol
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> Result<Self, String> {
+        let writer = stream
+            .try_clone()
+            .map_err(|err| format!("Cloning the connection failed: {}", err))?;
+
+        Ok(Connection {
+            reader: BufReader::new(stream),
+            writer,
+        })
+    }
+
+    fn send(&mut self, message: &Value) -> Result<(), String> {
+        writeln!(self.writer, "{}", message)
+            .and_then(|_| self.writer.flush())
+            .map_err(|err| format!("Sending message failed: {}", err))
+    }
+
+    // Function checks without blocking whether the other side closed the connection, unread messages keep it open
+    fn is_closed(&mut self) -> bool {
+        if !self.reader.buffer().is_empty() {
+            return false;
+        }
+        let stream = self.reader.get_ref();
+        if stream.set_nonblocking(true).is_err() {
+            return true;
+        }
+        let closed = match stream.peek(&mut [0]) {
+            Ok(n_read) => n_read == 0,
+            Err(err) => err.kind() != ErrorKind::WouldBlock,
+        };
+        stream.set_nonblocking(false).is_err() || closed
+    }
+
+    // Function returns the next message, None if the other side closed the connection
+    fn receive(&mut self) -> Result<Option<Value>, String> {
+        let mut line = String::new();
+        let n_read = self
+            .reader
+            .read_line(&mut line)
+            .map_err(|err| format!("Receiving message failed: {}", err))?;
+        if n_read == 0 {
+            return Ok(None);
+        }
+
+        serde_json::from_str(&line)
+            .map(Some)
+            .map_err(|err| format!("Parsing message failed: {}", err))
+    }
+}
+
+// Coordinator of the fitness evaluation on remote workers, e.g. processes calling run_worker on other machines
+// Workers register on the TCP port of the coordinator and get the batches of the waiting genomes, the batch of a
+// worker that disconnects or doesn't answer in time is put back to the queue and evaluated by another worker
+pub struct Coordinator {
+    shared: Arc<Shared>,
+    address: SocketAddr,
+    accept_thread: Option<JoinHandle<()>>,
+}
+
+impl Coordinator {
+    // Function starts listening on the address, port 0 picks a free port that is returned by get_address
+    // Up to batch_size waiting genomes are sent to a worker at once, a genome is put back to the queue after
+    // losing a worker at most max_retries times, then its evaluation fails
+    pub fn new(
+        address: &str,
+        config: &Config,
+        batch_size: usize,
+        timeout: Duration,
+        max_retries: usize,
+    ) -> Result<Self, String> {
+        if batch_size == 0 {
+            return Err("Batch size of the coordinator must not be 0".to_string());
+        }
+
+        let listener = TcpListener::bind(address)
+            .map_err(|err| format!("Listening on '{}' failed: {}", address, err))?;
+        let address = listener
+            .local_addr()
+            .map_err(|err| format!("Getting address of the coordinator failed: {}", err))?;
+
+        let shared = Arc::new(Shared {
+            batches: BatchQueue::new(batch_size),
+            config: config.json.clone(),
+            timeout,
+            max_retries,
+            n_workers: AtomicUsize::new(0),
+        });
+
+        let accept_shared = shared.clone();
+        let accept_thread = thread::spawn(move || Self::_accept_loop(accept_shared, listener));
+
+        Ok(Coordinator {
+            shared,
+            address,
+            accept_thread: Some(accept_thread),
+        })
+    }
+
+    // Function creates the coordinator from the "coordinator_address", "distributed_batch_size",
+    // "distributed_timeout" (seconds) and "distributed_retries" config values, the config is sent to the workers
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let address: String = config
+            .may_get_val("coordinator_address")?
+            .unwrap_or(DEFAULT_COORDINATOR_ADDRESS.to_string());
+        let timeout: f64 = config
+            .may_get_float("distributed_timeout")?
+            .unwrap_or(DEFAULT_DISTRIBUTED_TIMEOUT);
+
+        Self::new(
+            &address,
+            config,
+            config
+                .may_get_uint("distributed_batch_size")?
+                .unwrap_or(DEFAULT_DISTRIBUTED_BATCH_SIZE),
+            Duration::from_secs_f64(timeout),
+            config
+                .may_get_uint("distributed_retries")?
+                .unwrap_or(DEFAULT_DISTRIBUTED_RETRIES),
+        )
+    }
+
+    pub fn get_address(&self) -> SocketAddr {
+        self.address
+    }
+
+    // Function returns the number of currently registered workers
+    pub fn get_n_workers(&self) -> usize {
+        self.shared.n_workers.load(Ordering::SeqCst)
+    }
+
+    // Function waits until at least n_workers are registered, returns false if it didn't happen in time
+    pub fn wait_for_workers(&self, n_workers: usize, timeout: Duration) -> bool {
+        let start = Instant::now();
+        while self.get_n_workers() < n_workers {
+            if start.elapsed() > timeout {
+                return false;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        true
+    }
+
+    fn _accept_loop(shared: Arc<Shared>, listener: TcpListener) {
+        let mut threads = Vec::new();
+        for stream in listener.incoming() {
+            if shared.batches.is_closed() {
+                break;
+            }
+            if let Ok(stream) = stream {
+                let shared = shared.clone();
+                threads.push(thread::spawn(move || Self::_serve_worker(shared, stream)));
+            }
+        }
+        for thread in threads {
+            let _ = thread.join();
+        }
+    }
+
+    // Function registers the worker and sends it the batches until the coordinator is stopped or the worker is lost
+    fn _serve_worker(shared: Arc<Shared>, stream: TcpStream) {
+        if stream.set_read_timeout(Some(shared.timeout)).is_err() {
+            return;
+        }
+        let mut connection = match Connection::new(stream) {
+            Ok(connection) => connection,
+            Err(_) => return,
+        };
+
+        match connection.receive() {
+            Ok(Some(message)) if message["type"] == "register" => {}
+            _ => return,
+        }
+        if connection
+            .send(&json!({"type": "config", "config": shared.config}))
+            .is_err()
+        {
+            return;
+        }
+
+        shared.n_workers.fetch_add(1, Ordering::SeqCst);
+        let mut next_id: u64 = 0;
+        loop {
+            let batch = match shared.batches.take_batch(Some(IDLE_CHECK_INTERVAL)) {
+                Some(batch) => batch,
+                None => break,
+            };
+            // Idle worker that disconnected is dropped, so it doesn't take the next batch
+            if batch.is_empty() {
+                if connection.is_closed() {
+                    break;
+                }
+                continue;
+            }
+
+            // Batch that didn't reach the worker is put back without counting an attempt
+            next_id += 1;
+            let genomes: Vec<&Value> = batch.iter().map(|request| &request.genome).collect();
+            if connection.is_closed()
+                || connection
+                    .send(&json!({"type": "batch", "id": next_id, "genomes": genomes}))
+                    .is_err()
+            {
+                Self::_requeue(&shared, batch, None);
+                break;
+            }
+
+            match Self::_receive_fitness(&mut connection, next_id, batch.len()) {
+                Ok(fitness) => {
+                    for (request, fitness) in batch.iter().zip(fitness) {
+                        request.answer(Ok(fitness));
+                    }
+                }
+                Err(err) => {
+                    Self::_requeue(&shared, batch, Some(&err));
+                    break;
+                }
+            }
+        }
+        shared.n_workers.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    fn _receive_fitness(
+        connection: &mut Connection,
+        id: u64,
+        n_genomes: usize,
+    ) -> Result<Vec<f64>, String> {
+        let response = connection
+            .receive()?
+            .ok_or_else(|| "Worker disconnected".to_string())?;
+        if response["type"] != "fitness" || response["id"].as_u64() != Some(id) {
+            return Err(format!("Worker did not answer batch {}", id));
+        }
+        parse_fitness(&response["fitness"], n_genomes, "Worker")
+    }
+
+    // Function puts the batch of the lost worker to the front of the queue, so it is evaluated first by other workers
+    // The attempt is counted only with the error of a worker that got the batch
+    fn _requeue(shared: &Shared, batch: Vec<Request>, err: Option<&str>) {
+        let batch = match err {
+            Some(err) => batch
+                .into_iter()
+                .filter_map(|mut request| {
+                    request.attempts += 1;
+                    if request.attempts <= shared.max_retries {
+                        return Some(request);
+                    }
+                    request.answer(Err(format!(
+                        "Evaluating genome failed after losing {} workers: {}",
+                        request.attempts, err
+                    )));
+                    None
+                })
+                .collect(),
+            None => batch,
+        };
+        shared.batches.requeue(batch);
+    }
+}
+
+impl BatchEvaluator for Coordinator {
+    // Genomes are shared among the workers, the call waits while no worker is registered
+    fn evaluate_batch(&self, genomes: Vec<Value>) -> Vec<Result<f64, String>> {
+        self.shared
+            .batches
+            .evaluate_batch(genomes, "Coordinator stopped")
+    }
+}
+
+impl Drop for Coordinator {
+    // Waiting genomes are still evaluated by the registered workers, then the workers are disconnected
+    fn drop(&mut self) {
+        self.shared.batches.close();
+        // Accepting thread is woken up by a connection, it notices the closed queue and stops
+        let _ = TcpStream::connect(self.address);
+        if let Some(thread) = self.accept_thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+// Function registers the worker with the coordinator at the address and evaluates the batches it gets, the data of
+// the individuals are created from the config of the coordinator by create_data
+// Genomes of a batch are evaluated in parallel, the function returns the number of evaluated genomes when the
+// coordinator disconnects
+pub fn run_worker<Individual, IndividualData, F>(
+    address: &str,
+    create_data: F,
+) -> Result<usize, String>
+where
+    Individual: RemoteIndividual<IndividualData>,
+    IndividualData: Sync,
+    F: FnOnce(&Config) -> Result<IndividualData, String>,
+{
+    let stream = TcpStream::connect(address)
+        .map_err(|err| format!("Connecting to coordinator '{}' failed: {}", address, err))?;
+    let mut connection = Connection::new(stream)?;
+
+    connection.send(&json!({"type": "register", "pid": std::process::id()}))?;
+    let message = connection
+        .receive()?
+        .ok_or_else(|| "Coordinator closed the connection".to_string())?;
+    if message["type"] != "config" {
+        return Err("Coordinator did not send the config".to_string());
+    }
+    let ind_data = create_data(&Config {
+        json: message["config"].clone(),
+    })?;
+
+    let mut n_evaluated = 0;
+    while let Some(message) = connection.receive()? {
+        let genomes = message["genomes"]
+            .as_array()
+            .ok_or_else(|| "Batch without genomes received".to_string())?;
+
+        // Genome that can't be read gets null fitness, so the coordinator counts it as invalid
+        let fitness: Vec<Value> = genomes
+            .par_iter()
+            .map(
+                |genome| match Individual::from_genome_json(genome, &ind_data) {
+                    Ok(mut ind) => {
+                        ind.count_fitness(&ind_data);
+                        json!(ind.get_fitness())
+                    }
+                    Err(_) => Value::Null,
+                },
+            )
+            .collect();
+        n_evaluated += genomes.len();
+
+        connection.send(&json!({
+            "type": "fitness",
+            "id": message["id"],
+            "fitness": fitness,
+        }))?;
+    }
+
+    Ok(n_evaluated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evo_individual::EvoIndividual;
+    use crate::population::Population;
+    use crate::real_vector::{RealVectorData, RealVectorIndividual};
+    use std::str::FromStr;
+
+    fn sphere(genes: &[f64]) -> f64 {
+        -genes.iter().map(|x| x * x).sum::<f64>()
+    }
+
+    fn start_worker(address: String) -> JoinHandle<Result<usize, String>> {
+        thread::spawn(move || {
+            run_worker::<RealVectorIndividual, RealVectorData, _>(&address, |config| {
+                RealVectorData::from_config(config, sphere)
+            })
+        })
+    }
+
+    #[test]
+    fn test_coordinator() {
+        let config =
+            Config::from_str("{\"n_dims\": 2, \"pop_width\": 4, \"pop_height\": 4, \"seed\": 1}")
+                .unwrap();
+        let coordinator = Arc::new(
+            Coordinator::new("127.0.0.1:0", &config, 4, Duration::from_secs(10), 1).unwrap(),
+        );
+        let address = coordinator.get_address().to_string();
+
+        // Worker that registers, takes a batch and disconnects without answering
+        let lost_worker = {
+            let address = address.clone();
+            thread::spawn(move || {
+                let mut connection = Connection::new(TcpStream::connect(address).unwrap()).unwrap();
+                connection.send(&json!({"type": "register"})).unwrap();
+                connection.receive().unwrap().unwrap();
+                connection.receive().unwrap().unwrap()["genomes"]
+                    .as_array()
+                    .unwrap()
+                    .len()
+            })
+        };
+        assert!(coordinator.wait_for_workers(1, Duration::from_secs(10)));
+
+        // Batch taken by the lost worker is evaluated by the others
+        let evaluator = coordinator.clone();
+        let genomes: Vec<Value> = (0..40).map(|i| json!([i, 1])).collect();
+        let evaluation = thread::spawn(move || evaluator.evaluate_batch(genomes));
+        assert_eq!(lost_worker.join().unwrap(), 4);
+        let workers: Vec<_> = (0..3).map(|_| start_worker(address.clone())).collect();
+
+        let fitness: Vec<f64> = evaluation
+            .join()
+            .unwrap()
+            .into_iter()
+            .map(|fitness| fitness.unwrap())
+            .collect();
+        assert_eq!(
+            fitness,
+            (0..40).map(|i| -(i * i + 1) as f64).collect::<Vec<f64>>()
+        );
+        assert!(coordinator.wait_for_workers(3, Duration::from_secs(10)));
+
+        // Unreadable genome is invalid
+        assert!(coordinator.evaluate(json!("genome")).unwrap().is_nan());
+
+        // Population evaluates its individuals by the workers
+        let mut pop: Population<RealVectorIndividual, RealVectorData> = Population::new_with_data(
+            &config,
+            RealVectorData::from_config(&config, coordinator.clone().real_objective()).unwrap(),
+        );
+        pop.next_gen();
+        assert_eq!(pop.get_evaluations(), 32);
+        assert_eq!(pop.get_stats().n_invalid, 0);
+        assert!(pop.get_best().get_fitness() <= 0.0);
+
+        // Workers stop when the coordinator is dropped and report the evaluated genomes
+        drop(pop);
+        drop(Arc::into_inner(coordinator).unwrap());
+        let n_evaluated: usize = workers
+            .into_iter()
+            .map(|worker| worker.join().unwrap().unwrap())
+            .sum();
+        assert_eq!(n_evaluated, 40 + 1 + 32);
+
+        assert!(Coordinator::new("127.0.0.1:0", &config, 0, Duration::from_secs(1), 0).is_err());
+    }
+
+    #[test]
+    fn test_retries() {
+        // Genome fails after losing the only worker max_retries + 1 times
+        let config = Config::from_str("{\"n_dims\": 1}").unwrap();
+        let coordinator = Arc::new(
+            Coordinator::new("127.0.0.1:0", &config, 1, Duration::from_millis(200), 1).unwrap(),
+        );
+        let address = coordinator.get_address();
+        let evaluator = coordinator.clone();
+        let evaluation = thread::spawn(move || evaluator.evaluate(json!([1.0])));
+
+        // Worker that never answers is lost by the timeout
+        let mut silent = Vec::new();
+        for _ in 0..2 {
+            let mut connection = Connection::new(TcpStream::connect(address).unwrap()).unwrap();
+            connection.send(&json!({"type": "register"})).unwrap();
+            connection.receive().unwrap().unwrap();
+            assert_eq!(connection.receive().unwrap().unwrap()["type"], "batch");
+            silent.push(connection);
+        }
+        let err = evaluation.join().unwrap().unwrap_err();
+        assert!(err.contains("losing 2 workers"), "{}", err);
+    }
+
+    #[test]
+    fn test_idle_disconnect() {
+        // Worker that disconnected while idle is dropped without counting an attempt of the genome
+        let config = Config::from_str("{\"n_dims\": 1}").unwrap();
+        let coordinator = Arc::new(
+            Coordinator::new("127.0.0.1:0", &config, 1, Duration::from_secs(10), 0).unwrap(),
+        );
+        let address = coordinator.get_address();
+        let idle: Vec<Connection> = (0..2)
+            .map(|_| {
+                let mut connection = Connection::new(TcpStream::connect(address).unwrap()).unwrap();
+                connection.send(&json!({"type": "register"})).unwrap();
+                connection.receive().unwrap().unwrap();
+                connection
+            })
+            .collect();
+        assert!(coordinator.wait_for_workers(2, Duration::from_secs(10)));
+        drop(idle);
+
+        let evaluator = coordinator.clone();
+        let evaluation = thread::spawn(move || evaluator.evaluate(json!([2.0])));
+        let worker = start_worker(address.to_string());
+        assert_eq!(evaluation.join().unwrap().unwrap(), -4.0);
+        let start = Instant::now();
+        while coordinator.get_n_workers() > 1 {
+            assert!(start.elapsed() < Duration::from_secs(10));
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        drop(Arc::into_inner(coordinator).unwrap());
+        assert_eq!(worker.join().unwrap().unwrap(), 1);
+    }
+}
@@ -1,6 +1,8 @@
 use crate::config::Config;
 use image::RgbImage;
 use rand::rngs::SmallRng;
+use serde_json::Value;
+use std::hash::{Hash, Hasher};
 
 pub trait EvoIndividualData: Send + Sync {
     fn from_config(config: &Config) -> Self;
@@ -22,6 +24,24 @@ pub trait EvoIndividual<IndividualData>: Send + Sync + Clone {
     // Return new Individual with the genome that is a crossover of two individuals
     fn crossover(&self, another_ind: &Self, ind_data: &IndividualData, rng: &mut SmallRng) -> Self;
 
+    // Write the crossover of two individuals into dest, which is an individual of an older generation
+    // By default the result of crossover is moved into dest, implementations can reuse the genome of dest instead
+    fn crossover_into(
+        &self,
+        another_ind: &Self,
+        ind_data: &IndividualData,
+        rng: &mut SmallRng,
+        dest: &mut Self,
+    ) {
+        *dest = self.crossover(another_ind, ind_data, rng);
+    }
+
+    // Copy the genome and fitness of another individual into this one
+    // By default it is Clone::clone_from, implementations can reuse the genome of this individual instead
+    fn copy_from(&mut self, other: &Self) {
+        self.clone_from(other);
+    }
+
     // Count the fitness of the individual
     fn count_fitness(&mut self, ind_data: &IndividualData);
 
@@ -30,8 +50,133 @@ pub trait EvoIndividual<IndividualData>: Send + Sync + Clone {
 
     // Get the A and B values of the individual for visualisation
     fn get_visuals(&self, ind_data: &IndividualData) -> (f64, f64);
+
+    // Get the distance between the genomes of two individuals, used for the diversity metrics
+    // By default it is the euclidean distance between the visual attributes
+    fn distance(&self, other: &Self, ind_data: &IndividualData) -> f64 {
+        let (a, b) = self.get_visuals(ind_data);
+        let (other_a, other_b) = other.get_visuals(ind_data);
+        ((a - other_a).powi(2) + (b - other_b).powi(2)).sqrt()
+    }
+
+    // Get the hash of the genome, individuals with the same genome must have the same hash
+    // By default the visual attributes are hashed
+    fn genome_hash(&self, ind_data: &IndividualData) -> u64 {
+        let (a, b) = self.get_visuals(ind_data);
+        hash_value(&(a.to_bits(), b.to_bits()))
+    }
+}
+
+// Individual whose genome can be written as JSON, used by the hall of fame and sent to the remote workers
+pub trait RemoteIndividual<IndividualData>: EvoIndividual<IndividualData> {
+    fn genome_to_json(&self) -> Value;
+
+    // Function creates the individual from the genome, its fitness is counted later by count_fitness
+    fn from_genome_json(genome: &Value, ind_data: &IndividualData) -> Result<Self, String>;
+
+    // Function sets the fitness evaluated outside of count_fitness, e.g. by the batch evaluator of the population
+    fn set_fitness(&mut self, fitness: f64);
+}
+
+// Function returns the FNV-1a hash of the value, see Fnv1aHasher
+// The hash is the same in every run and build, so it can be stored, e.g. in the exported hall of fame
+pub fn hash_value<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = Fnv1aHasher::default();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+// 64-bit FNV-1a hasher, unlike DefaultHasher its algorithm is fixed
+// Integers are hashed as their little-endian bytes and usize, isize as 64-bit, so the hash doesn't depend on the platform
+pub struct Fnv1aHasher {
+    hash: u64,
+}
+
+impl Default for Fnv1aHasher {
+    fn default() -> Self {
+        Fnv1aHasher {
+            hash: FNV_OFFSET_BASIS,
+        }
+    }
+}
+
+impl Hasher for Fnv1aHasher {
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.hash = (self.hash ^ *byte as u64).wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_u16(&mut self, value: u16) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, value: u128) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, value: usize) {
+        self.write_u64(value as u64);
+    }
+
+    fn write_i16(&mut self, value: i16) {
+        self.write_u16(value as u16);
+    }
+
+    fn write_i32(&mut self, value: i32) {
+        self.write_u32(value as u32);
+    }
+
+    fn write_i64(&mut self, value: i64) {
+        self.write_u64(value as u64);
+    }
+
+    fn write_i128(&mut self, value: i128) {
+        self.write_u128(value as u128);
+    }
+
+    fn write_isize(&mut self, value: isize) {
+        self.write_u64(value as i64 as u64);
+    }
 }
 
 pub trait Visualise<IndividualData> {
     fn visualise(&self, ind_data: &IndividualData) -> RgbImage;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_value() {
+        // Reference values of 64-bit FNV-1a
+        let mut hasher = Fnv1aHasher::default();
+        assert_eq!(hasher.finish(), 0xcbf29ce484222325);
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xaf63dc4c8601ec8c);
+
+        // Integers are hashed as little-endian bytes, usize as u64
+        let mut hasher = Fnv1aHasher::default();
+        hasher.write(&[1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(hash_value(&1u64), hasher.finish());
+        assert_eq!(hash_value(&1usize), hasher.finish());
+        assert_eq!(hash_value(&-1i64), hash_value(&u64::MAX));
+        assert_ne!(hash_value(&[1u8, 2]), hash_value(&[2u8, 1]));
+    }
+}
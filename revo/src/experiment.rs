@@ -0,0 +1,468 @@
+use crate::config::Config;
+use crate::evo_individual::{EvoIndividual, EvoIndividualData};
+use crate::population::Population;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use std::fmt::Write as FmtWrite;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+const DEFAULT_N_RUNS: usize = 10;
+const DEFAULT_N_GENERATIONS: usize = 100;
+const DEFAULT_EXPERIMENT_PARALLEL: bool = true;
+
+// Experiment runs the same config multiple times with different seeds and collects the best fitness of every generation
+pub struct Experiment {
+    config: Config,
+    n_runs: usize,
+    n_generations: usize,
+    parallel: bool,
+    // Seed of the first run, the following runs use the next seeds
+    base_seed: u64,
+}
+
+impl Experiment {
+    pub fn new(config: &Config) -> Self {
+        Experiment {
+            config: config.clone(),
+            n_runs: config
+                .may_get_uint("n_runs")
+                .unwrap()
+                .unwrap_or(DEFAULT_N_RUNS),
+            n_generations: config
+                .may_get_uint("n_generations")
+                .unwrap()
+                .unwrap_or(DEFAULT_N_GENERATIONS),
+            parallel: config
+                .may_get_bool("experiment_parallel")
+                .unwrap()
+                .unwrap_or(DEFAULT_EXPERIMENT_PARALLEL),
+            base_seed: config
+                .may_get_uint("seed")
+                .unwrap()
+                .unwrap_or_else(|| SmallRng::from_entropy().gen()),
+        }
+    }
+
+    pub fn get_seeds(&self) -> Vec<u64> {
+        (0..self.n_runs as u64)
+            .map(|i| self.base_seed.wrapping_add(i))
+            .collect()
+    }
+
+    // Function runs the experiment with individual data created from the config for every run
+    pub fn run<Individual, IndividualData>(&self) -> ExperimentResult
+    where
+        Individual: EvoIndividual<IndividualData>,
+        IndividualData: EvoIndividualData,
+    {
+        self._run(|config| {
+            Population::<Individual, IndividualData>::new_with_data(
+                config,
+                IndividualData::from_config(config),
+            )
+        })
+    }
+
+    // Function runs the experiment with a copy of the given individual data for every run
+    pub fn run_with_data<Individual, IndividualData>(
+        &self,
+        ind_data: &IndividualData,
+    ) -> ExperimentResult
+    where
+        Individual: EvoIndividual<IndividualData>,
+        IndividualData: Clone + Send + Sync,
+    {
+        self._run(|config| {
+            Population::<Individual, IndividualData>::new_with_data(config, ind_data.clone())
+        })
+    }
+
+    fn _run<Individual, IndividualData, F>(&self, new_population: F) -> ExperimentResult
+    where
+        Individual: EvoIndividual<IndividualData>,
+        IndividualData: Send + Sync,
+        F: Fn(&Config) -> Population<Individual, IndividualData> + Send + Sync,
+    {
+        let run = |seed: u64| {
+            let mut config = self.config.clone();
+            config.set("seed", seed);
+
+            let mut pop = new_population(&config);
+            let mut curve = Vec::with_capacity(self.n_generations + 1);
+            curve.push(pop.get_best().get_fitness());
+
+            for _ in 0..self.n_generations {
+                pop.next_gen();
+                curve.push(pop.get_best().get_fitness());
+            }
+            curve
+        };
+
+        let seeds = self.get_seeds();
+        let curves = if self.parallel {
+            seeds.par_iter().map(|&seed| run(seed)).collect()
+        } else {
+            seeds.iter().map(|&seed| run(seed)).collect()
+        };
+
+        ExperimentResult { seeds, curves }
+    }
+}
+
+// Distribution of the fitness values described by its mean and quartiles
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FitnessDistribution {
+    pub mean: f64,
+    pub std: f64,
+    pub min: f64,
+    pub q1: f64,
+    pub median: f64,
+    pub q3: f64,
+    pub max: f64,
+}
+
+impl FitnessDistribution {
+    pub fn from_values(values: &[f64]) -> Self {
+        if values.is_empty() {
+            return FitnessDistribution {
+                mean: f64::NAN,
+                std: f64::NAN,
+                min: f64::NAN,
+                q1: f64::NAN,
+                median: f64::NAN,
+                q3: f64::NAN,
+                max: f64::NAN,
+            };
+        }
+
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+
+        FitnessDistribution {
+            mean,
+            std: variance.sqrt(),
+            min: sorted[0],
+            q1: _quantile(&sorted, 0.25),
+            median: _quantile(&sorted, 0.5),
+            q3: _quantile(&sorted, 0.75),
+            max: sorted[sorted.len() - 1],
+        }
+    }
+}
+
+// Function returns the quantile of the sorted values, linearly interpolated between the closest ranks
+fn _quantile(sorted: &[f64], q: f64) -> f64 {
+    let pos = q * (sorted.len() - 1) as f64;
+    let lower = pos.floor() as usize;
+    let upper = pos.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (pos - lower as f64)
+}
+
+#[derive(Clone, Debug)]
+pub struct ExperimentResult {
+    pub seeds: Vec<u64>,
+    // Best fitness of every generation of every run, the first value is the initial population
+    pub curves: Vec<Vec<f64>>,
+}
+
+impl ExperimentResult {
+    pub fn get_final_fitnesses(&self) -> Vec<f64> {
+        self.curves
+            .iter()
+            .filter_map(|curve| curve.last().copied())
+            .collect()
+    }
+
+    pub fn get_final_distribution(&self) -> FitnessDistribution {
+        FitnessDistribution::from_values(&self.get_final_fitnesses())
+    }
+
+    // Function returns the distribution of the best fitness over the runs for every generation
+    pub fn get_convergence(&self) -> Vec<FitnessDistribution> {
+        let n_generations = self.curves.iter().map(|c| c.len()).min().unwrap_or(0);
+
+        (0..n_generations)
+            .map(|generation| {
+                let values: Vec<f64> = self.curves.iter().map(|c| c[generation]).collect();
+                FitnessDistribution::from_values(&values)
+            })
+            .collect()
+    }
+
+    // Function compares the final fitnesses of this and the other experiment by Mann-Whitney U test
+    pub fn compare(&self, other: &ExperimentResult) -> MannWhitneyResult {
+        mann_whitney_u(&self.get_final_fitnesses(), &other.get_final_fitnesses())
+    }
+
+    // Function writes the best fitness curve of every run as a column
+    pub fn write_curves_csv(&self, path: &str) -> Result<(), String> {
+        let mut data = String::from("generation");
+        for seed in &self.seeds {
+            write!(data, ",seed_{}", seed).unwrap();
+        }
+        data.push('\n');
+
+        let n_generations = self.curves.iter().map(|c| c.len()).max().unwrap_or(0);
+        for generation in 0..n_generations {
+            write!(data, "{}", generation).unwrap();
+            for curve in &self.curves {
+                match curve.get(generation) {
+                    Some(value) => write!(data, ",{}", value).unwrap(),
+                    None => data.push(','),
+                }
+            }
+            data.push('\n');
+        }
+
+        _write_file(path, &data)
+    }
+
+    // Function writes the mean, median and quartiles of the best fitness for every generation
+    pub fn write_convergence_csv(&self, path: &str) -> Result<(), String> {
+        let mut data = String::from("generation,mean,std,min,q1,median,q3,max\n");
+        for (generation, d) in self.get_convergence().iter().enumerate() {
+            writeln!(
+                data,
+                "{},{},{},{},{},{},{},{}",
+                generation, d.mean, d.std, d.min, d.q1, d.median, d.q3, d.max
+            )
+            .unwrap();
+        }
+
+        _write_file(path, &data)
+    }
+
+    // Function writes the final best fitness of every run
+    pub fn write_final_csv(&self, path: &str) -> Result<(), String> {
+        let mut data = String::from("seed,final_fitness\n");
+        for (seed, fitness) in self.seeds.iter().zip(self.get_final_fitnesses()) {
+            writeln!(data, "{},{}", seed, fitness).unwrap();
+        }
+
+        _write_file(path, &data)
+    }
+}
+
+fn _write_file(path: &str, data: &str) -> Result<(), String> {
+    let file =
+        File::create(path).map_err(|err| format!("Creating file '{}' failed: {}", path, err))?;
+    let mut writer = BufWriter::new(file);
+    writer
+        .write_all(data.as_bytes())
+        .and_then(|_| writer.flush())
+        .map_err(|err| format!("Writing file '{}' failed: {}", path, err))
+}
+
+// Function returns the summary table of the final fitnesses of the named experiments
+pub fn summary_table(results: &[(&str, &ExperimentResult)]) -> String {
+    let mut table = format!(
+        "{:<20} {:>6} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12}\n",
+        "experiment", "runs", "mean", "std", "q1", "median", "q3", "best"
+    );
+
+    for (name, result) in results {
+        let d = result.get_final_distribution();
+        writeln!(
+            table,
+            "{:<20} {:>6} {:>12.6} {:>12.6} {:>12.6} {:>12.6} {:>12.6} {:>12.6}",
+            name,
+            result.curves.len(),
+            d.mean,
+            d.std,
+            d.q1,
+            d.median,
+            d.q3,
+            d.max
+        )
+        .unwrap();
+    }
+
+    table
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MannWhitneyResult {
+    // U statistic of the first sample
+    pub u: f64,
+    // Standardised U statistic with the tie and continuity corrections
+    pub z: f64,
+    // Two-sided p-value of the normal approximation
+    pub p_value: f64,
+    // Probability that a value of the first sample is greater than a value of the second one, ties count as half
+    pub effect_size: f64,
+}
+
+// Function does the Mann-Whitney U test (Wilcoxon rank-sum test) of two independent samples
+pub fn mann_whitney_u(first: &[f64], second: &[f64]) -> MannWhitneyResult {
+    let n1 = first.len() as f64;
+    let n2 = second.len() as f64;
+
+    let mut values: Vec<(f64, bool)> = first
+        .iter()
+        .map(|v| (*v, true))
+        .chain(second.iter().map(|v| (*v, false)))
+        .collect();
+    values.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    // Tied values get the average of their ranks
+    let mut rank_sum_first = 0.0;
+    let mut tie_correction = 0.0;
+    let mut i = 0;
+    while i < values.len() {
+        let mut j = i;
+        while j + 1 < values.len() && values[j + 1].0 == values[i].0 {
+            j += 1;
+        }
+
+        let rank = (i + j) as f64 / 2.0 + 1.0;
+        let n_tied = (j - i + 1) as f64;
+        rank_sum_first += rank * values[i..=j].iter().filter(|v| v.1).count() as f64;
+        tie_correction += n_tied.powi(3) - n_tied;
+
+        i = j + 1;
+    }
+
+    let u = rank_sum_first - n1 * (n1 + 1.0) / 2.0;
+    let mean_u = n1 * n2 / 2.0;
+    let n = n1 + n2;
+    let std_u = (n1 * n2 / 12.0 * ((n + 1.0) - tie_correction / (n * (n - 1.0)))).sqrt();
+
+    let z = if std_u > 0.0 {
+        let diff = u - mean_u;
+        (diff.abs() - 0.5).max(0.0) * diff.signum() / std_u
+    } else {
+        0.0
+    };
+
+    MannWhitneyResult {
+        u,
+        z,
+        p_value: _erfc(z.abs() / std::f64::consts::SQRT_2).min(1.0),
+        effect_size: if n1 * n2 > 0.0 { u / (n1 * n2) } else { 0.5 },
+    }
+}
+
+// Complementary error function with fractional error less than 1.2e-7
+fn _erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t
+        * (-z * z - 1.26551223
+            + t * (1.00002368
+                + t * (0.37409196
+                    + t * (0.09678418
+                        + t * (-0.18628806
+                            + t * (0.27886807
+                                + t * (-1.13520398
+                                    + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277)))))))))
+            .exp();
+
+    if x >= 0.0 {
+        r
+    } else {
+        2.0 - r
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::real_vector::{RealVectorData, RealVectorIndividual};
+    use crate::testing::{MockIndividual, MockIndividualData};
+    use std::fs;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_distribution() {
+        let d = FitnessDistribution::from_values(&[4.0, 1.0, 3.0, 2.0, 5.0]);
+        assert_eq!(d.mean, 3.0);
+        assert_eq!(d.min, 1.0);
+        assert_eq!(d.q1, 2.0);
+        assert_eq!(d.median, 3.0);
+        assert_eq!(d.q3, 4.0);
+        assert_eq!(d.max, 5.0);
+        assert_eq!(d.std, 2.0f64.sqrt());
+
+        // Quartiles are interpolated
+        let d = FitnessDistribution::from_values(&[1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(d.median, 2.5);
+        assert_eq!(d.q1, 1.75);
+    }
+
+    #[test]
+    fn test_mann_whitney_u() {
+        // Completely separated samples
+        let result = mann_whitney_u(&[6.0, 7.0, 8.0, 9.0, 10.0], &[1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert_eq!(result.u, 25.0);
+        assert_eq!(result.effect_size, 1.0);
+        assert!((result.z - 2.5067).abs() < 1e-3);
+        assert!((result.p_value - 0.01219).abs() < 1e-4);
+
+        // Identical samples
+        let result = mann_whitney_u(&[1.0, 1.0, 2.0], &[1.0, 1.0, 2.0]);
+        assert_eq!(result.u, 4.5);
+        assert_eq!(result.p_value, 1.0);
+
+        assert!((_erfc(0.0) - 1.0).abs() < 1e-7);
+        assert!((_erfc(1.0) - 0.157299207).abs() < 1e-7);
+        assert!((_erfc(-1.0) - 1.842700793).abs() < 1e-7);
+    }
+
+    #[test]
+    fn test_experiment() {
+        let config = Config::from_str(
+            "{\"pop_width\": 8, \"pop_height\": 8, \"n_runs\": 4, \"n_generations\": 20, \"seed\": 10, \"mut_prob\": 0.2, \"mut_amount\": 0.5}",
+        )
+        .unwrap();
+
+        let experiment = Experiment::new(&config);
+        assert_eq!(experiment.get_seeds(), vec![10, 11, 12, 13]);
+
+        let result = experiment.run::<MockIndividual, MockIndividualData>();
+        assert_eq!(result.curves.len(), 4);
+        assert!(result.curves.iter().all(|curve| curve.len() == 21));
+        assert_eq!(result.get_convergence().len(), 21);
+
+        // Sequential run gives the same results
+        let mut sequential_config = config.clone();
+        sequential_config.set("experiment_parallel", false);
+        let sequential =
+            Experiment::new(&sequential_config).run::<MockIndividual, MockIndividualData>();
+        assert_eq!(sequential.curves, result.curves);
+
+        let sphere = RealVectorData::new(3, -5.0, 5.0, |x| -x.iter().map(|v| v * v).sum::<f64>());
+        let good = experiment.run_with_data::<RealVectorIndividual, RealVectorData>(&sphere);
+
+        let mut short_config = config.clone();
+        short_config.set("n_generations", 0);
+        let bad = Experiment::new(&short_config)
+            .run_with_data::<RealVectorIndividual, RealVectorData>(&sphere);
+
+        // Longer evolution is better in every run
+        assert_eq!(good.compare(&bad).effect_size, 1.0);
+        assert!(summary_table(&[("good", &good), ("bad", &bad)]).contains("good"));
+
+        let path = std::env::temp_dir()
+            .join(format!("revo_experiment_{}.csv", std::process::id()))
+            .to_str()
+            .unwrap()
+            .to_string();
+        good.write_convergence_csv(&path).unwrap();
+        let data = fs::read_to_string(&path).unwrap();
+        assert_eq!(data.lines().count(), 22);
+
+        good.write_curves_csv(&path).unwrap();
+        let data = fs::read_to_string(&path).unwrap();
+        assert!(data.starts_with("generation,seed_10,seed_11,seed_12,seed_13\n"));
+
+        good.write_final_csv(&path).unwrap();
+        let data = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(data.lines().count(), 5);
+    }
+}
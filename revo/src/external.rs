@@ -0,0 +1,327 @@
+use crate::batch_queue::{parse_fitness, BatchEvaluator, BatchQueue};
+use crate::config::Config;
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+const DEFAULT_EXTERNAL_WORKERS: usize = 1;
+const DEFAULT_EXTERNAL_BATCH_SIZE: usize = 16;
+const DEFAULT_EXTERNAL_TIMEOUT: f64 = 10.0;
+const DEFAULT_EXTERNAL_RETRIES: usize = 2;
+
+// Running worker process with the lines of its stdout read by a separate thread
+struct WorkerProcess {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+}
+
+impl WorkerProcess {
+    fn spawn(command: &[String]) -> Result<Self, String> {
+        let mut child = Command::new(&command[0])
+            .args(&command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|err| format!("Starting external worker '{}' failed: {}", command[0], err))?;
+
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(WorkerProcess {
+            child,
+            stdin,
+            lines,
+        })
+    }
+
+    // Function sends the batch as one line and waits for the line with the fitness of all of its genomes
+    fn evaluate(
+        &mut self,
+        id: u64,
+        genomes: &[Value],
+        timeout: Duration,
+    ) -> Result<Vec<f64>, String> {
+        let request = json!({
+            "id": id,
+            "genomes": genomes,
+        });
+        writeln!(self.stdin, "{}", request)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|err| format!("Sending batch to external worker failed: {}", err))?;
+
+        let line = match self.lines.recv_timeout(timeout) {
+            Ok(line) => line,
+            Err(RecvTimeoutError::Timeout) => {
+                return Err(format!("External worker did not answer in {:?}", timeout))
+            }
+            Err(RecvTimeoutError::Disconnected) => return Err("External worker exited".to_string()),
+        };
+
+        let response: Value = serde_json::from_str(&line)
+            .map_err(|err| format!("Parsing answer of external worker failed: {}", err))?;
+        if response["id"].as_u64() != Some(id) {
+            return Err(format!(
+                "External worker answered batch {} instead of {}",
+                response["id"], id
+            ));
+        }
+        parse_fitness(&response["fitness"], genomes.len(), "External worker")
+    }
+}
+
+impl Drop for WorkerProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// Evaluator of the fitness by long-running local processes, e.g. simulators or scripts
+// The genomes are sent as JSON to the stdin of the workers in batches and the fitness is read from their stdout
+// Every worker is a separate process, a worker that crashes or doesn't answer in time is started again
+pub struct ExternalEvaluator {
+    batches: Arc<BatchQueue>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl ExternalEvaluator {
+    // Function starts n_workers processes of the command, the first item is the program and the rest are its arguments
+    // Up to batch_size waiting genomes are sent to a worker at once, a batch that fails is tried max_retries more times
+    pub fn new(
+        command: Vec<String>,
+        n_workers: usize,
+        batch_size: usize,
+        timeout: Duration,
+        max_retries: usize,
+    ) -> Result<Self, String> {
+        if command.is_empty() {
+            return Err("Command of the external worker is empty".to_string());
+        }
+        if n_workers == 0 || batch_size == 0 {
+            return Err("Number of external workers and batch size must not be 0".to_string());
+        }
+
+        let batches = Arc::new(BatchQueue::new(batch_size));
+
+        // Workers are started right away, so a wrong command is reported here
+        let processes = (0..n_workers)
+            .map(|_| WorkerProcess::spawn(&command))
+            .collect::<Result<Vec<WorkerProcess>, String>>()?;
+        let threads = processes
+            .into_iter()
+            .map(|process| {
+                let batches = batches.clone();
+                let command = command.clone();
+                thread::spawn(move || {
+                    Self::_worker_loop(batches, command, process, timeout, max_retries)
+                })
+            })
+            .collect();
+
+        Ok(ExternalEvaluator { batches, threads })
+    }
+
+    // Function creates the evaluator from the "external_command" config value, split by whitespace, and the
+    // "external_workers", "external_batch_size", "external_timeout" (seconds) and "external_retries" config values
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let command: String = config.get_val("external_command")?;
+        let timeout: f64 = config
+            .may_get_float("external_timeout")?
+            .unwrap_or(DEFAULT_EXTERNAL_TIMEOUT);
+
+        Self::new(
+            command.split_whitespace().map(String::from).collect(),
+            config
+                .may_get_uint("external_workers")?
+                .unwrap_or(DEFAULT_EXTERNAL_WORKERS),
+            config
+                .may_get_uint("external_batch_size")?
+                .unwrap_or(DEFAULT_EXTERNAL_BATCH_SIZE),
+            Duration::from_secs_f64(timeout),
+            config
+                .may_get_uint("external_retries")?
+                .unwrap_or(DEFAULT_EXTERNAL_RETRIES),
+        )
+    }
+
+    fn _worker_loop(
+        batches: Arc<BatchQueue>,
+        command: Vec<String>,
+        process: WorkerProcess,
+        timeout: Duration,
+        max_retries: usize,
+    ) {
+        let mut process = Some(process);
+        let mut next_id: u64 = 0;
+
+        while let Some(batch) = batches.take_batch(None) {
+            let genomes: Vec<Value> = batch.iter().map(|request| request.genome.clone()).collect();
+            let mut result = Err(String::new());
+            for _ in 0..=max_retries {
+                next_id += 1;
+                if process.is_none() {
+                    match WorkerProcess::spawn(&command) {
+                        Ok(new_process) => process = Some(new_process),
+                        Err(err) => {
+                            result = Err(err);
+                            continue;
+                        }
+                    }
+                }
+
+                result = process
+                    .as_mut()
+                    .unwrap()
+                    .evaluate(next_id, &genomes, timeout);
+                if result.is_ok() {
+                    break;
+                }
+                // Worker that crashed, hung or answered nonsense is killed and started again for the next try
+                process = None;
+            }
+
+            match result {
+                Ok(fitness) => {
+                    for (request, fitness) in batch.iter().zip(fitness) {
+                        request.answer(Ok(fitness));
+                    }
+                }
+                Err(err) => {
+                    let err = format!(
+                        "Evaluating batch failed after {} tries: {}",
+                        max_retries + 1,
+                        err
+                    );
+                    for request in &batch {
+                        request.answer(Err(err.clone()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl BatchEvaluator for ExternalEvaluator {
+    // Genomes are shared among the workers
+    fn evaluate_batch(&self, genomes: Vec<Value>) -> Vec<Result<f64, String>> {
+        self.batches
+            .evaluate_batch(genomes, "External evaluator stopped")
+    }
+}
+
+impl Drop for ExternalEvaluator {
+    // Waiting genomes are still evaluated, then the workers are stopped
+    fn drop(&mut self) {
+        self.batches.close();
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evo_individual::EvoIndividual;
+    use crate::population::Population;
+    use crate::real_vector::{RealVectorData, RealVectorIndividual};
+    use std::str::FromStr;
+
+    // Reference worker written in Python, the tests are skipped if Python is not available
+    fn worker_command() -> Option<Vec<String>> {
+        let available = Command::new("python3")
+            .arg("--version")
+            .stdout(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success());
+        if !available {
+            eprintln!("python3 not found, skipping the external evaluator test");
+            return None;
+        }
+        Some(vec![
+            "python3".to_string(),
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/external_worker.py").to_string(),
+        ])
+    }
+
+    #[test]
+    fn test_external_evaluator() {
+        let command = match worker_command() {
+            Some(command) => command,
+            None => return,
+        };
+        let evaluator =
+            ExternalEvaluator::new(command.clone(), 2, 3, Duration::from_secs(10), 1).unwrap();
+
+        let genomes: Vec<Value> = (0..10).map(|i| json!([i, 1])).collect();
+        let fitness: Vec<f64> = evaluator
+            .evaluate_batch(genomes)
+            .into_iter()
+            .map(|fitness| fitness.unwrap())
+            .collect();
+        assert_eq!(
+            fitness,
+            (0..10).map(|i| -(i * i + 1) as f64).collect::<Vec<f64>>()
+        );
+        assert!(evaluator.evaluate(json!("invalid")).unwrap().is_nan());
+
+        // Crashed worker is started again, the batch fails only if it crashes on every try
+        let flag = std::env::temp_dir().join(format!("revo_crash_once_{}", std::process::id()));
+        let _ = std::fs::remove_file(&flag);
+        assert_eq!(
+            evaluator
+                .evaluate(json!({ "crash_once": flag.to_str().unwrap() }))
+                .unwrap(),
+            0.0
+        );
+        std::fs::remove_file(&flag).unwrap();
+        assert!(evaluator.evaluate(json!("crash")).is_err());
+        assert_eq!(evaluator.evaluate(json!([2.0])).unwrap(), -4.0);
+
+        // Worker that doesn't answer in time is replaced as well
+        let evaluator =
+            ExternalEvaluator::new(command.clone(), 1, 1, Duration::from_millis(300), 0).unwrap();
+        let err = evaluator.evaluate(json!("hang")).unwrap_err();
+        assert!(err.contains("did not answer"), "{}", err);
+        assert_eq!(evaluator.evaluate(json!([3.0])).unwrap(), -9.0);
+
+        assert!(ExternalEvaluator::new(
+            vec!["revo_no_such_worker".to_string()],
+            1,
+            1,
+            Duration::from_secs(1),
+            0
+        )
+        .is_err());
+
+        // Population evaluates its individuals by the worker, calls of all threads are batched
+        let config = Config::from_str(&format!(
+            "{{\"pop_width\": 4, \"pop_height\": 4, \"seed\": 1, \"external_command\": \"{}\", \"external_workers\": 2}}",
+            command.join(" ")
+        ))
+        .unwrap();
+        let evaluator = Arc::new(ExternalEvaluator::from_config(&config).unwrap());
+        let mut pop: Population<RealVectorIndividual, RealVectorData> = Population::new_with_data(
+            &config,
+            RealVectorData::new(3, -1.0, 1.0, evaluator.real_objective()),
+        );
+        pop.next_gen();
+        assert_eq!(pop.get_evaluations(), 32);
+        assert_eq!(pop.get_stats().n_invalid, 0);
+        assert!(pop.get_best().get_fitness() <= 0.0);
+    }
+}
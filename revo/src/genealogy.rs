@@ -0,0 +1,364 @@
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use strum_macros::{Display, EnumIter, EnumString};
+
+// Minimal number of records kept before the records of extinct lineages are pruned, relative to the population size
+const PRUNE_FACTOR: usize = 4;
+
+// Operator that produced the individual
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, EnumString, EnumIter, Display)]
+pub enum Operator {
+    // Individual of the initial population, a restart or an immigrant
+    #[strum(serialize = "random")]
+    Random,
+    #[strum(serialize = "mutation")]
+    Mutation,
+    #[strum(serialize = "crossover")]
+    Crossover,
+}
+
+// Metadata of one individual
+#[derive(Clone, Debug, PartialEq)]
+pub struct GenealogyRecord {
+    pub id: u64,
+    // One parent for mutation, two for crossover (the same one twice if it was selected twice), none for random
+    pub parents: Vec<u64>,
+    pub birth_generation: usize,
+    pub operator: Operator,
+    pub fitness: f64,
+}
+
+// Number of offspring created by an operator and how many of them were fitter than all of their parents
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OperatorStats {
+    pub n_offspring: usize,
+    pub n_improved: usize,
+}
+
+impl OperatorStats {
+    // Function returns the ratio of the offspring fitter than their parents, 0 if there is no offspring
+    pub fn success_rate(&self) -> f64 {
+        if self.n_offspring == 0 {
+            0.0
+        } else {
+            self.n_improved as f64 / self.n_offspring as f64
+        }
+    }
+}
+
+// Origin of the individual that takes a cell in the next generation
+#[derive(Clone, Copy, Debug)]
+pub struct Birth {
+    // Cell of the parent, for crossover the first parent
+    pub parent: usize,
+    // Cell of the second parent, the same as parent for mutation
+    pub other_parent: usize,
+    pub operator: Operator,
+    // Offspring is fitter than all of its parents
+    pub improved: bool,
+    // Offspring took the cell, crowding can keep the parent instead
+    pub is_offspring: bool,
+    pub fitness: f64,
+}
+
+// Genealogy of the population, the records of the individuals without living descendants are pruned over time
+pub struct Genealogy {
+    records: HashMap<u64, GenealogyRecord>,
+
+    // Id of the individual in each cell, next_cell_ids is the buffer of the next generation
+    cell_ids: Vec<u64>,
+    next_cell_ids: Vec<u64>,
+    next_id: u64,
+
+    // Records are pruned when there are more of them than the threshold
+    prune_threshold: usize,
+
+    mutation_stats: OperatorStats,
+    crossover_stats: OperatorStats,
+}
+
+impl Genealogy {
+    // Function creates the genealogy of the initial population with the given fitness of its cells
+    pub fn new(fitnesses: &[f64]) -> Self {
+        let mut genealogy = Genealogy {
+            records: HashMap::new(),
+            cell_ids: Vec::with_capacity(fitnesses.len()),
+            next_cell_ids: Vec::with_capacity(fitnesses.len()),
+            next_id: 0,
+            prune_threshold: PRUNE_FACTOR * fitnesses.len(),
+            mutation_stats: OperatorStats::default(),
+            crossover_stats: OperatorStats::default(),
+        };
+
+        for &fitness in fitnesses {
+            let id = genealogy._add_record(Vec::new(), 0, Operator::Random, fitness);
+            genealogy.cell_ids.push(id);
+        }
+        genealogy
+    }
+
+    // Function returns the id of the individual in the cell
+    pub fn get_id(&self, cell: usize) -> u64 {
+        self.cell_ids[cell]
+    }
+
+    // Function returns the record of the individual, None if it was pruned
+    pub fn get_record(&self, id: u64) -> Option<&GenealogyRecord> {
+        self.records.get(&id)
+    }
+
+    pub fn get_operator_stats(&self, operator: Operator) -> OperatorStats {
+        match operator {
+            Operator::Mutation => self.mutation_stats.clone(),
+            Operator::Crossover => self.crossover_stats.clone(),
+            Operator::Random => OperatorStats::default(),
+        }
+    }
+
+    // Function counts the operator stats of all offspring of the generation and records the individuals of the cells
+    // Cell births are in the order of the cells, the cell keeps the id of the parent if no offspring took it
+    // The offspring and the cell births are the same unless crowding decides which offspring take the cells
+    pub fn add_generation(
+        &mut self,
+        generation: usize,
+        offspring: &[Birth],
+        cell_births: &[Birth],
+    ) {
+        for birth in offspring {
+            let stats = match birth.operator {
+                Operator::Crossover => &mut self.crossover_stats,
+                _ => &mut self.mutation_stats,
+            };
+            stats.n_offspring += 1;
+            stats.n_improved += birth.improved as usize;
+        }
+
+        let mut next_cell_ids = std::mem::take(&mut self.next_cell_ids);
+        next_cell_ids.clear();
+        for birth in cell_births {
+            let parent = self.cell_ids[birth.parent];
+            if !birth.is_offspring {
+                next_cell_ids.push(parent);
+                continue;
+            }
+
+            let parents = match birth.operator {
+                Operator::Crossover => vec![parent, self.cell_ids[birth.other_parent]],
+                _ => vec![parent],
+            };
+            next_cell_ids.push(self._add_record(
+                parents,
+                generation,
+                birth.operator,
+                birth.fitness,
+            ));
+        }
+
+        std::mem::swap(&mut self.cell_ids, &mut next_cell_ids);
+        self.next_cell_ids = next_cell_ids;
+        if self.records.len() > self.prune_threshold {
+            self._prune();
+        }
+    }
+
+    // Function records the new random individual in the cell, e.g. after a restart
+    pub fn add_random(&mut self, cell: usize, generation: usize, fitness: f64) {
+        self.cell_ids[cell] = self._add_record(Vec::new(), generation, Operator::Random, fitness);
+    }
+
+    // Function changes the cells after the population was resized, the new cell i holds the individual of the old
+    // cell sources[i]
+    pub fn remap_cells(&mut self, sources: &[usize]) {
+        self.cell_ids = sources.iter().map(|&cell| self.cell_ids[cell]).collect();
+        self.prune_threshold = self.prune_threshold.max(PRUNE_FACTOR * self.cell_ids.len());
+    }
+
+    // Function returns the records of the individual and all of its known ancestors, from the youngest
+    pub fn get_ancestry(&self, id: u64) -> Vec<&GenealogyRecord> {
+        let mut ancestry = Vec::new();
+        let mut visited = HashSet::new();
+        let mut queue = vec![id];
+
+        while let Some(id) = queue.pop() {
+            if !visited.insert(id) {
+                continue;
+            }
+            if let Some(record) = self.records.get(&id) {
+                ancestry.push(record);
+                queue.extend(&record.parents);
+            }
+        }
+
+        ancestry.sort_by_key(|record| std::cmp::Reverse(record.id));
+        ancestry
+    }
+
+    // Function returns the ancestry of the individual as a graph in the DOT format, edges go from parents to children
+    pub fn ancestry_to_dot(&self, id: u64) -> String {
+        let ancestry = self.get_ancestry(id);
+        let mut dot = String::from("digraph genealogy {\n");
+
+        for record in &ancestry {
+            writeln!(
+                dot,
+                "  {} [label=\"{}\\ngeneration {}\\n{}\\n{}\"];",
+                record.id, record.id, record.birth_generation, record.operator, record.fitness
+            )
+            .unwrap();
+        }
+        for record in &ancestry {
+            for parent in &record.parents {
+                if self.records.contains_key(parent) {
+                    writeln!(dot, "  {} -> {};", parent, record.id).unwrap();
+                }
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    // Function returns the ancestry of the individual as JSON with the id of the individual and the list of records
+    pub fn ancestry_to_json(&self, id: u64) -> Value {
+        let individuals: Vec<Value> = self
+            .get_ancestry(id)
+            .iter()
+            .map(|record| {
+                json!({
+                    "id": record.id,
+                    "parents": record.parents,
+                    "birth_generation": record.birth_generation,
+                    "operator": record.operator.to_string(),
+                    "fitness": if record.fitness.is_finite() { json!(record.fitness) } else { Value::Null },
+                })
+            })
+            .collect();
+
+        json!({
+            "id": id,
+            "individuals": individuals,
+        })
+    }
+
+    // Function writes the ancestry of the individual to the file, DOT if the path ends with ".dot", JSON otherwise
+    pub fn write_ancestry(&self, path: &str, id: u64) -> Result<(), String> {
+        let data = if path.ends_with(".dot") {
+            self.ancestry_to_dot(id)
+        } else {
+            serde_json::to_string_pretty(&self.ancestry_to_json(id))
+                .map_err(|err| format!("Serialising genealogy failed: {}", err))?
+        };
+
+        std::fs::write(path, data)
+            .map_err(|err| format!("Writing genealogy '{}' failed: {}", path, err))
+    }
+
+    fn _add_record(
+        &mut self,
+        parents: Vec<u64>,
+        birth_generation: usize,
+        operator: Operator,
+        fitness: f64,
+    ) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.records.insert(
+            id,
+            GenealogyRecord {
+                id,
+                parents,
+                birth_generation,
+                operator,
+                fitness,
+            },
+        );
+        id
+    }
+
+    // Function removes the records that are not ancestors of any living individual
+    fn _prune(&mut self) {
+        let mut alive: HashSet<u64> = HashSet::new();
+        let mut queue: Vec<u64> = self.cell_ids.clone();
+
+        while let Some(id) = queue.pop() {
+            if alive.insert(id) {
+                if let Some(record) = self.records.get(&id) {
+                    queue.extend(&record.parents);
+                }
+            }
+        }
+
+        self.records.retain(|id, _| alive.contains(id));
+        self.prune_threshold = self
+            .prune_threshold
+            .max(PRUNE_FACTOR * self.cell_ids.len())
+            .max(2 * self.records.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn birth(parent: usize, other_parent: usize, operator: Operator, fitness: f64) -> Birth {
+        Birth {
+            parent,
+            other_parent,
+            operator,
+            improved: fitness > 1.0,
+            is_offspring: true,
+            fitness,
+        }
+    }
+
+    #[test]
+    fn test_genealogy() {
+        let mut genealogy = Genealogy::new(&[1.0, 1.0, 1.0]);
+        assert_eq!(genealogy.get_id(2), 2);
+
+        let births = [
+            birth(0, 1, Operator::Crossover, 2.0),
+            birth(1, 1, Operator::Mutation, 0.0),
+            Birth {
+                is_offspring: false,
+                ..birth(2, 2, Operator::Mutation, 0.5)
+            },
+        ];
+        genealogy.add_generation(1, &births, &births);
+        assert_eq!(genealogy.get_id(0), 3);
+        assert_eq!(genealogy.get_id(2), 2);
+        assert_eq!(genealogy.get_record(3).unwrap().parents, vec![0, 1]);
+        assert_eq!(
+            genealogy.get_record(4).unwrap().operator,
+            Operator::Mutation
+        );
+
+        let crossover = genealogy.get_operator_stats(Operator::Crossover);
+        assert_eq!(crossover.success_rate(), 1.0);
+        let mutation = genealogy.get_operator_stats(Operator::Mutation);
+        assert_eq!((mutation.n_offspring, mutation.n_improved), (2, 0));
+
+        // Ancestry goes from the individual to the initial population
+        let births = [birth(0, 0, Operator::Mutation, 3.0); 3];
+        genealogy.add_generation(2, &births, &births);
+        let ids: Vec<u64> = genealogy.get_ancestry(5).iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec![5, 3, 1, 0]);
+
+        let dot = genealogy.ancestry_to_dot(5);
+        assert!(dot.contains("3 -> 5;"));
+        assert!(dot.contains("0 -> 3;"));
+        let json = genealogy.ancestry_to_json(5);
+        assert_eq!(json["individuals"].as_array().unwrap().len(), 4);
+        assert_eq!(json["individuals"][1]["operator"], "crossover");
+
+        // Records without living descendants are pruned
+        genealogy._prune();
+        assert!(genealogy.get_record(4).is_none());
+        assert!(genealogy.get_record(2).is_none());
+        assert!(genealogy.get_record(0).is_some());
+
+        genealogy.add_random(1, 2, 0.0);
+        assert_eq!(genealogy.get_record(8).unwrap().operator, Operator::Random);
+    }
+}
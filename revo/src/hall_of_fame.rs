@@ -0,0 +1,365 @@
+use crate::config::Config;
+use crate::evo_individual::{EvoIndividual, RemoteIndividual, Visualise};
+use serde_json::{json, Value};
+use strum_macros::{Display, EnumIter, EnumString};
+
+const DEFAULT_HALL_OF_FAME_SIZE: usize = 10;
+const DEFAULT_HALL_OF_FAME_DEDUP: DedupType = DedupType::Hash;
+const DEFAULT_HALL_OF_FAME_RADIUS: f64 = 0.0;
+
+#[derive(Clone, Copy, PartialEq, Debug, EnumString, EnumIter, Display)]
+pub enum DedupType {
+    // Individuals with the same genome_hash are the same
+    #[strum(serialize = "hash")]
+    Hash,
+    // Individuals closer than the radius by their distance are the same
+    #[strum(serialize = "distance")]
+    Distance,
+}
+
+#[derive(Clone, Debug)]
+pub struct HallOfFameEntry<Individual> {
+    pub individual: Individual,
+    pub fitness: f64,
+    // Generation in which the individual entered the hall of fame
+    pub generation: usize,
+    pub genome_hash: u64,
+}
+
+// Archive of the best distinct individuals ever seen, sorted from the best
+pub struct HallOfFame<Individual> {
+    entries: Vec<HallOfFameEntry<Individual>>,
+    capacity: usize,
+    dedup_type: DedupType,
+    radius: f64,
+
+    // Indices of the individuals that can enter, reused by every update
+    candidates: Vec<usize>,
+}
+
+impl<Individual: Clone> HallOfFame<Individual> {
+    // Function creates an empty hall of fame, capacity 0 disables it
+    pub fn new(capacity: usize, dedup_type: DedupType, radius: f64) -> Self {
+        HallOfFame {
+            entries: Vec::with_capacity(capacity),
+            capacity,
+            dedup_type,
+            radius,
+            candidates: Vec::new(),
+        }
+    }
+
+    // Function creates the hall of fame from the "hall_of_fame_size", "hall_of_fame_dedup" and "hall_of_fame_radius"
+    // config values
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(
+            config
+                .may_get_uint("hall_of_fame_size")
+                .unwrap()
+                .unwrap_or(DEFAULT_HALL_OF_FAME_SIZE),
+            config
+                .may_get_enum("hall_of_fame_dedup")
+                .unwrap()
+                .unwrap_or(DEFAULT_HALL_OF_FAME_DEDUP),
+            config
+                .may_get_float("hall_of_fame_radius")
+                .unwrap()
+                .unwrap_or(DEFAULT_HALL_OF_FAME_RADIUS),
+        )
+    }
+
+    pub fn get_entries(&self) -> &[HallOfFameEntry<Individual>] {
+        &self.entries
+    }
+
+    // Function returns the best individual ever seen, None if the hall of fame is empty
+    pub fn get_best(&self) -> Option<&HallOfFameEntry<Individual>> {
+        self.entries.first()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // Function adds the individuals of the generation that are better than the worst entry and not yet present
+    // An entry is replaced by a fitter individual that is the same by the dedup type
+    pub fn update<IndividualData>(
+        &mut self,
+        inds: &[Individual],
+        ind_data: &IndividualData,
+        generation: usize,
+    ) where
+        Individual: EvoIndividual<IndividualData>,
+        IndividualData: Send + Sync,
+    {
+        if self.capacity == 0 {
+            return;
+        }
+
+        // Only the individuals that can enter are sorted, which is usually just a few of them
+        let threshold = self._threshold();
+        let mut candidates = std::mem::take(&mut self.candidates);
+        candidates.clear();
+        candidates.extend((0..inds.len()).filter(|&i| inds[i].get_fitness() > threshold));
+        candidates.sort_by(|&a, &b| inds[b].get_fitness().total_cmp(&inds[a].get_fitness()));
+
+        for &i in &candidates {
+            if inds[i].get_fitness() <= self._threshold() {
+                break;
+            }
+            self._insert(&inds[i], ind_data, generation);
+        }
+        self.candidates = candidates;
+    }
+
+    // Function returns the entries as JSON with their rank, fitness, generation, genome hash, visuals and genome
+    pub fn to_json<IndividualData>(&self, ind_data: &IndividualData) -> Value
+    where
+        Individual: RemoteIndividual<IndividualData>,
+    {
+        let entries: Vec<Value> = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(rank, entry)| {
+                let (visual_x, visual_y) = entry.individual.get_visuals(ind_data);
+                json!({
+                    "rank": rank,
+                    "fitness": entry.fitness,
+                    "generation": entry.generation,
+                    "genome_hash": entry.genome_hash,
+                    "visuals": [visual_x, visual_y],
+                    "genome": entry.individual.genome_to_json(),
+                })
+            })
+            .collect();
+
+        Value::Array(entries)
+    }
+
+    pub fn write_json<IndividualData>(
+        &self,
+        path: &str,
+        ind_data: &IndividualData,
+    ) -> Result<(), String>
+    where
+        Individual: RemoteIndividual<IndividualData>,
+    {
+        let data = serde_json::to_string_pretty(&self.to_json(ind_data))
+            .map_err(|err| format!("Serialising hall of fame failed: {}", err))?;
+        std::fs::write(path, data)
+            .map_err(|err| format!("Writing hall of fame '{}' failed: {}", path, err))
+    }
+
+    // Function adds the entries of the JSON written by to_json, e.g. by an interrupted run
+    // Fitness of the genomes is counted again and the entries keep their generation, duplicates are merged as usual
+    pub fn load_json<IndividualData>(
+        &mut self,
+        json: &Value,
+        ind_data: &IndividualData,
+    ) -> Result<(), String>
+    where
+        Individual: RemoteIndividual<IndividualData>,
+    {
+        let entries = json
+            .as_array()
+            .ok_or_else(|| "Hall of fame is not an array of entries".to_string())?;
+
+        for entry in entries {
+            let genome = entry
+                .get("genome")
+                .ok_or_else(|| format!("Hall of fame entry '{}' has no genome", entry))?;
+            let generation = entry
+                .get("generation")
+                .and_then(Value::as_u64)
+                .ok_or_else(|| format!("Hall of fame entry '{}' has no generation", entry))?;
+
+            let mut ind = Individual::from_genome_json(genome, ind_data)?;
+            ind.count_fitness(ind_data);
+            if self.capacity > 0 && ind.get_fitness() > self._threshold() {
+                self._insert(&ind, ind_data, generation as usize);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn read_json<IndividualData>(
+        &mut self,
+        path: &str,
+        ind_data: &IndividualData,
+    ) -> Result<(), String>
+    where
+        Individual: RemoteIndividual<IndividualData>,
+    {
+        let data = std::fs::read_to_string(path)
+            .map_err(|err| format!("Reading hall of fame '{}' failed: {}", path, err))?;
+        let json: Value = serde_json::from_str(&data)
+            .map_err(|err| format!("Parsing hall of fame '{}' failed: {}", path, err))?;
+        self.load_json(&json, ind_data)
+    }
+
+    // Function saves the image of each entry as "<path_stem>_<rank>.png"
+    pub fn save_images<IndividualData>(
+        &self,
+        path_stem: &str,
+        ind_data: &IndividualData,
+    ) -> Result<(), String>
+    where
+        Individual: Visualise<IndividualData>,
+    {
+        for (rank, entry) in self.entries.iter().enumerate() {
+            let path = format!("{}_{}.png", path_stem, rank);
+            entry
+                .individual
+                .visualise(ind_data)
+                .save(&path)
+                .map_err(|err| format!("Saving image '{}' failed: {}", path, err))?;
+        }
+        Ok(())
+    }
+
+    // Function returns the fitness an individual has to exceed to enter
+    fn _threshold(&self) -> f64 {
+        if self.entries.len() < self.capacity {
+            f64::NEG_INFINITY
+        } else {
+            self.entries[self.entries.len() - 1].fitness
+        }
+    }
+
+    fn _insert<IndividualData>(
+        &mut self,
+        ind: &Individual,
+        ind_data: &IndividualData,
+        generation: usize,
+    ) where
+        Individual: EvoIndividual<IndividualData>,
+    {
+        let fitness = ind.get_fitness();
+        let genome_hash = ind.genome_hash(ind_data);
+
+        let duplicate = self.entries.iter().position(|entry| match self.dedup_type {
+            DedupType::Hash => entry.genome_hash == genome_hash,
+            DedupType::Distance => ind.distance(&entry.individual, ind_data) <= self.radius,
+        });
+        if let Some(i) = duplicate {
+            if fitness <= self.entries[i].fitness {
+                return;
+            }
+            self.entries.remove(i);
+        }
+
+        // Older entries stay in front of the new ones with the same fitness
+        let position = self
+            .entries
+            .partition_point(|entry| entry.fitness >= fitness);
+        self.entries.insert(
+            position,
+            HallOfFameEntry {
+                individual: ind.clone(),
+                fitness,
+                generation,
+                genome_hash,
+            },
+        );
+        self.entries.truncate(self.capacity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockIndividual, MockIndividualData};
+
+    fn mock(fitness: f64, visual: f64) -> MockIndividual {
+        MockIndividual {
+            fitness,
+            visuals: (visual, 0.0),
+            value: fitness,
+        }
+    }
+
+    #[test]
+    fn test_update() {
+        let ind_data = MockIndividualData {};
+        let mut hall_of_fame = HallOfFame::new(3, DedupType::Hash, 0.0);
+
+        // Duplicates of the same genome enter only once
+        let inds = vec![
+            mock(1.0, 1.0),
+            mock(1.0, 1.0),
+            mock(3.0, 3.0),
+            mock(0.0, 0.0),
+        ];
+        hall_of_fame.update(&inds, &ind_data, 0);
+        let fitnesses: Vec<f64> = hall_of_fame
+            .get_entries()
+            .iter()
+            .map(|e| e.fitness)
+            .collect();
+        assert_eq!(fitnesses, vec![3.0, 1.0, 0.0]);
+
+        // Better individuals push out the worst ones, the best is kept across generations
+        let inds = vec![mock(2.0, 2.0), mock(-1.0, 5.0)];
+        hall_of_fame.update(&inds, &ind_data, 1);
+        let fitnesses: Vec<f64> = hall_of_fame
+            .get_entries()
+            .iter()
+            .map(|e| e.fitness)
+            .collect();
+        assert_eq!(fitnesses, vec![3.0, 2.0, 1.0]);
+        assert_eq!(hall_of_fame.get_best().unwrap().generation, 0);
+        assert_eq!(hall_of_fame.get_entries()[1].generation, 1);
+
+        let json = hall_of_fame.to_json(&ind_data);
+        assert_eq!(json[1]["fitness"], 2.0);
+        assert_eq!(json[1]["visuals"][0], 2.0);
+
+        // Loaded entries are merged with the present ones and keep their generation
+        let mut loaded = HallOfFame::new(3, DedupType::Hash, 0.0);
+        loaded.update(&[mock(2.5, 2.5)], &ind_data, 4);
+        loaded.load_json(&json, &ind_data).unwrap();
+        let entries: Vec<(f64, usize)> = loaded
+            .get_entries()
+            .iter()
+            .map(|e| (e.fitness, e.generation))
+            .collect();
+        assert_eq!(entries, vec![(3.0, 0), (2.5, 4), (2.0, 1)]);
+        assert!(loaded
+            .load_json(&json!([{"generation": 0}]), &ind_data)
+            .is_err());
+    }
+
+    #[test]
+    fn test_distance_dedup() {
+        let ind_data = MockIndividualData {};
+        let mut hall_of_fame = HallOfFame::new(3, DedupType::Distance, 1.0);
+
+        // Individuals within the radius are the same, the fitter one replaces the entry
+        hall_of_fame.update(&[mock(1.0, 0.0), mock(2.0, 0.5)], &ind_data, 0);
+        assert_eq!(hall_of_fame.len(), 1);
+        assert_eq!(hall_of_fame.get_best().unwrap().fitness, 2.0);
+
+        hall_of_fame.update(&[mock(1.5, 3.0), mock(3.0, 0.2)], &ind_data, 1);
+        let fitnesses: Vec<f64> = hall_of_fame
+            .get_entries()
+            .iter()
+            .map(|e| e.fitness)
+            .collect();
+        assert_eq!(fitnesses, vec![3.0, 1.5]);
+
+        // Disabled hall of fame stays empty
+        let mut hall_of_fame = HallOfFame::new(0, DedupType::Hash, 0.0);
+        hall_of_fame.update(&[mock(1.0, 0.0)], &ind_data, 0);
+        assert!(hall_of_fame.is_empty());
+    }
+}
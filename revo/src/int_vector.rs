@@ -0,0 +1,346 @@
+use crate::config::Config;
+use crate::evo_individual::{hash_value, EvoIndividual, RemoteIndividual};
+use crate::vector_ops::VectorCrossoverType;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use serde_json::{json, Value};
+use std::fmt;
+use std::sync::Arc;
+use strum_macros::{Display, EnumIter, EnumString};
+
+const DEFAULT_N_GENES: usize = 10;
+const DEFAULT_INT_LOWER_BOUND: i64 = 0;
+const DEFAULT_INT_UPPER_BOUND: i64 = 9;
+const DEFAULT_INT_MUTATION_TYPE: IntMutationType = IntMutationType::Creep;
+const DEFAULT_VECTOR_CROSSOVER_TYPE: VectorCrossoverType = VectorCrossoverType::Uniform;
+
+// Objective function over the genes, its value is the fitness that is maximised
+pub type IntObjective = Arc<dyn Fn(&[i64]) -> f64 + Send + Sync>;
+
+#[derive(Clone, Copy, PartialEq, Debug, EnumString, EnumIter, Display)]
+pub enum IntMutationType {
+    // Random value from -mut_amount to mut_amount is added to the gene, the step is at least 1
+    #[strum(serialize = "creep")]
+    Creep,
+    // Gene is reset to a random value within the bounds
+    #[strum(serialize = "random_reset")]
+    RandomReset,
+}
+
+#[derive(Clone)]
+pub struct IntVectorData {
+    // Inclusive lower and upper bound of each gene
+    pub bounds: Vec<(i64, i64)>,
+    pub objective: IntObjective,
+
+    pub mutation_type: IntMutationType,
+    pub crossover_type: VectorCrossoverType,
+}
+
+impl IntVectorData {
+    // Function creates data for genomes of n_genes genes with the same bounds
+    pub fn new<F>(n_genes: usize, lower_bound: i64, upper_bound: i64, objective: F) -> Self
+    where
+        F: Fn(&[i64]) -> f64 + Send + Sync + 'static,
+    {
+        Self::with_bounds(vec![(lower_bound, upper_bound); n_genes], objective)
+    }
+
+    // Function creates data for genomes with separate bounds for each gene
+    pub fn with_bounds<F>(bounds: Vec<(i64, i64)>, objective: F) -> Self
+    where
+        F: Fn(&[i64]) -> f64 + Send + Sync + 'static,
+    {
+        IntVectorData {
+            bounds,
+            objective: Arc::new(objective),
+            mutation_type: DEFAULT_INT_MUTATION_TYPE,
+            crossover_type: DEFAULT_VECTOR_CROSSOVER_TYPE,
+        }
+    }
+
+    // Function creates data with the genome parameters from the config and the given objective
+    pub fn from_config<F>(config: &Config, objective: F) -> Result<Self, String>
+    where
+        F: Fn(&[i64]) -> f64 + Send + Sync + 'static,
+    {
+        let lower_bound = config
+            .may_get_int("int_lower_bound")?
+            .unwrap_or(DEFAULT_INT_LOWER_BOUND);
+        let upper_bound = config
+            .may_get_int("int_upper_bound")?
+            .unwrap_or(DEFAULT_INT_UPPER_BOUND);
+        // Genes couldn't be sampled from inverted bounds
+        if lower_bound > upper_bound {
+            return Err(format!(
+                "Lower bound {} is greater than upper bound {}",
+                lower_bound, upper_bound
+            ));
+        }
+
+        let mut ind_data = Self::new(
+            config.may_get_uint("n_genes")?.unwrap_or(DEFAULT_N_GENES),
+            lower_bound,
+            upper_bound,
+            objective,
+        );
+
+        ind_data.mutation_type = config
+            .may_get_enum("int_mutation")?
+            .unwrap_or(DEFAULT_INT_MUTATION_TYPE);
+        ind_data.crossover_type = config
+            .may_get_enum("vector_crossover")?
+            .unwrap_or(DEFAULT_VECTOR_CROSSOVER_TYPE);
+
+        Ok(ind_data)
+    }
+}
+
+#[derive(Clone)]
+pub struct IntVectorIndividual {
+    pub fitness: f64,
+    pub genes: Vec<i64>,
+}
+
+impl IntVectorIndividual {
+    pub fn new(genes: Vec<i64>) -> Self {
+        IntVectorIndividual {
+            fitness: 0.0,
+            genes,
+        }
+    }
+
+    pub fn get_genes(&self) -> &[i64] {
+        &self.genes
+    }
+}
+
+impl EvoIndividual<IntVectorData> for IntVectorIndividual {
+    fn new_randomised(ind_data: &IntVectorData, rng: &mut SmallRng) -> Self {
+        let genes = ind_data
+            .bounds
+            .iter()
+            .map(|&(lower, upper)| rng.gen_range(lower..=upper))
+            .collect();
+
+        Self::new(genes)
+    }
+
+    fn mutate(
+        &mut self,
+        ind_data: &IntVectorData,
+        rng: &mut SmallRng,
+        mut_prob: f32,
+        mut_amount: f32,
+    ) {
+        let max_step = (mut_amount.abs().round() as i64).max(1);
+
+        for (gene, &bounds) in self.genes.iter_mut().zip(ind_data.bounds.iter()) {
+            if rng.gen_range(0.0..1.0) >= mut_prob {
+                continue;
+            }
+
+            *gene = match ind_data.mutation_type {
+                IntMutationType::Creep => {
+                    let step = rng.gen_range(1..=max_step);
+                    if rng.gen_bool(0.5) {
+                        *gene + step
+                    } else {
+                        *gene - step
+                    }
+                }
+                IntMutationType::RandomReset => rng.gen_range(bounds.0..=bounds.1),
+            }
+            .clamp(bounds.0, bounds.1);
+        }
+    }
+
+    fn crossover(
+        &self,
+        another_ind: &IntVectorIndividual,
+        ind_data: &IntVectorData,
+        rng: &mut SmallRng,
+    ) -> IntVectorIndividual {
+        Self::new(
+            ind_data
+                .crossover_type
+                .apply(&self.genes, &another_ind.genes, rng),
+        )
+    }
+
+    // Child is written into the genes of dest
+    fn crossover_into(
+        &self,
+        another_ind: &IntVectorIndividual,
+        ind_data: &IntVectorData,
+        rng: &mut SmallRng,
+        dest: &mut IntVectorIndividual,
+    ) {
+        dest.fitness = 0.0;
+        ind_data
+            .crossover_type
+            .apply_into(&self.genes, &another_ind.genes, rng, &mut dest.genes);
+    }
+
+    fn count_fitness(&mut self, ind_data: &IntVectorData) {
+        self.fitness = (ind_data.objective)(&self.genes);
+    }
+
+    fn get_fitness(&self) -> f64 {
+        self.fitness
+    }
+
+    // Genome is copied into the allocation of this individual
+    fn copy_from(&mut self, other: &Self) {
+        self.fitness = other.fitness;
+        self.genes.clone_from(&other.genes);
+    }
+
+    // Visuals are the sums of the genes at even and odd positions
+    fn get_visuals(&self, _ind_data: &IntVectorData) -> (f64, f64) {
+        let mut a = 0.0;
+        let mut b = 0.0;
+
+        for (i, gene) in self.genes.iter().enumerate() {
+            if i % 2 == 0 {
+                a += *gene as f64;
+            } else {
+                b += *gene as f64;
+            }
+        }
+
+        (a, b)
+    }
+
+    // Distance is the manhattan distance between the genes
+    fn distance(&self, other: &Self, _ind_data: &IntVectorData) -> f64 {
+        self.genes
+            .iter()
+            .zip(other.genes.iter())
+            .map(|(a, b)| (a - b).unsigned_abs() as f64)
+            .sum()
+    }
+
+    fn genome_hash(&self, _ind_data: &IntVectorData) -> u64 {
+        hash_value(&self.genes)
+    }
+}
+
+impl RemoteIndividual<IntVectorData> for IntVectorIndividual {
+    fn genome_to_json(&self) -> Value {
+        json!(self.genes)
+    }
+
+    // Genes have to be within the bounds of the data
+    fn from_genome_json(genome: &Value, ind_data: &IntVectorData) -> Result<Self, String> {
+        let genes = genome
+            .as_array()
+            .and_then(|genes| {
+                genes
+                    .iter()
+                    .map(Value::as_i64)
+                    .collect::<Option<Vec<i64>>>()
+            })
+            .ok_or_else(|| format!("Genome '{}' is not an array of integers", genome))?;
+        if genes.len() != ind_data.bounds.len() {
+            return Err(format!(
+                "Genome has {} genes instead of {}",
+                genes.len(),
+                ind_data.bounds.len()
+            ));
+        }
+        if genes
+            .iter()
+            .zip(ind_data.bounds.iter())
+            .any(|(gene, (lower, upper))| gene < lower || gene > upper)
+        {
+            return Err(format!("Genome '{}' is out of the bounds", genome));
+        }
+
+        Ok(Self::new(genes))
+    }
+
+    fn set_fitness(&mut self, fitness: f64) {
+        self.fitness = fitness;
+    }
+}
+
+impl fmt::Display for IntVectorIndividual {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}, fitness: {}", self.genes, self.fitness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::population::Population;
+    use rand::SeedableRng;
+    use std::str::FromStr;
+    use strum::IntoEnumIterator;
+
+    fn sum(genes: &[i64]) -> f64 {
+        genes.iter().sum::<i64>() as f64
+    }
+
+    #[test]
+    fn test_operators_respect_bounds() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut ind_data = IntVectorData::with_bounds(vec![(0, 1), (-5, 5), (10, 20)], sum);
+
+        for mutation_type in IntMutationType::iter() {
+            ind_data.mutation_type = mutation_type;
+
+            for _ in 0..100 {
+                let mut ind = IntVectorIndividual::new_randomised(&ind_data, &mut rng);
+                ind.mutate(&ind_data, &mut rng, 1.0, 100.0);
+
+                for (gene, bounds) in ind.genes.iter().zip(ind_data.bounds.iter()) {
+                    assert!(*gene >= bounds.0 && *gene <= bounds.1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_creep() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let ind_data = IntVectorData::new(8, -100, 100, sum);
+
+        // Creep mutation changes every gene by at most mut_amount
+        let mut ind = IntVectorIndividual::new(vec![0; 8]);
+        ind.mutate(&ind_data, &mut rng, 1.0, 2.0);
+        assert!(ind.genes.iter().all(|gene| *gene != 0 && gene.abs() <= 2));
+    }
+
+    #[test]
+    fn test_population() {
+        let config = Config::from_str(
+            "{\"pop_width\": 16, \"pop_height\": 16, \"mut_prob\": 0.1, \"mut_amount\": 1.0, \"crossover_prob\": 0.3, \"seed\": 2, \"n_genes\": 6, \"int_upper_bound\": 5, \"int_mutation\": \"random_reset\"}",
+        )
+        .unwrap();
+        let ind_data = IntVectorData::from_config(&config, sum).unwrap();
+        assert_eq!(ind_data.mutation_type, IntMutationType::RandomReset);
+
+        // Inverted bounds are refused
+        let inverted =
+            Config::from_str("{\"int_lower_bound\": 3, \"int_upper_bound\": 2}").unwrap();
+        assert!(IntVectorData::from_config(&inverted, sum).is_err());
+
+        // Genome is read back only within the bounds
+        let genome = json!([0, 1, 2, 3, 4, 5]);
+        let ind = IntVectorIndividual::from_genome_json(&genome, &ind_data).unwrap();
+        assert_eq!(ind.genome_to_json(), genome);
+        assert!(
+            IntVectorIndividual::from_genome_json(&json!([0, 1, 2, 3, 4, 6]), &ind_data).is_err()
+        );
+
+        let mut pop: Population<IntVectorIndividual, IntVectorData> =
+            Population::new_with_data(&config, ind_data);
+        for _ in 0..50 {
+            pop.next_gen();
+        }
+
+        assert_eq!(pop.get_best().get_fitness(), 30.0);
+    }
+}
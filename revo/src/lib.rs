@@ -1,9 +1,29 @@
 extern crate rand;
 extern crate rustc_serialize;
 
+pub mod animation;
+pub mod batch_queue;
+pub mod benchmarks;
+pub mod bit_string;
+pub mod cli;
+pub mod colormap;
 pub mod config;
+pub mod distributed;
 pub mod evo_individual;
+pub mod experiment;
+pub mod external;
+pub mod genealogy;
+pub mod hall_of_fame;
+pub mod int_vector;
+pub mod meta;
+#[cfg(feature = "monitor")]
+pub mod monitor;
+pub mod permutation;
 pub mod population;
+pub mod real_vector;
+pub mod run_logger;
+pub mod sweep;
 pub mod utils;
+pub mod vector_ops;
 
 mod testing;
@@ -0,0 +1,290 @@
+use crate::config::Config;
+use crate::evo_individual::{EvoIndividual, EvoIndividualData};
+use crate::experiment::Experiment;
+use crate::population::{NeighbourhoodType, SelectionStrategyType};
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_distr::{Distribution, Normal};
+use std::fmt;
+use std::sync::Arc;
+use strum::IntoEnumIterator;
+
+const DEFAULT_META_MAX_MUT_AMOUNT: f64 = 1.0;
+
+// Function runs the inner evolution with the parameters written into the config and returns its score
+pub type MetaObjective = Arc<dyn Fn(&Config) -> f64 + Send + Sync>;
+
+// Data of the meta individuals, the inner config describes the target problem and the budget of the inner runs
+// The budget is given by the n_runs and n_generations values of the inner config, as for the Experiment
+#[derive(Clone)]
+pub struct MetaData {
+    pub inner_config: Config,
+    pub objective: MetaObjective,
+    // Upper bound of the tuned mut_amount, its scale depends on the target problem
+    pub max_mut_amount: f64,
+}
+
+impl MetaData {
+    // Function creates the meta data with the objective given by the caller
+    // The objective is called with the inner config that contains the parameters of the individual
+    pub fn new<F>(inner_config: &Config, objective: F) -> Self
+    where
+        F: Fn(&Config) -> f64 + Send + Sync + 'static,
+    {
+        let mut inner_config = inner_config.clone();
+
+        // Every individual is evaluated by the same seeds, so the parameters are compared fairly
+        if inner_config.may_get_uint::<u64>("seed").unwrap().is_none() {
+            inner_config.set("seed", SmallRng::from_entropy().gen::<u64>());
+        }
+
+        MetaData {
+            max_mut_amount: inner_config
+                .may_get_float("meta_max_mut_amount")
+                .unwrap()
+                .unwrap_or(DEFAULT_META_MAX_MUT_AMOUNT),
+            inner_config,
+            objective: Arc::new(objective),
+        }
+    }
+
+    // Function creates the meta data scoring the parameters by the mean final best fitness of the inner experiment
+    // Individual data are created from the inner config for every inner run
+    pub fn for_individual<Individual, IndividualData>(inner_config: &Config) -> Self
+    where
+        Individual: EvoIndividual<IndividualData> + 'static,
+        IndividualData: EvoIndividualData + 'static,
+    {
+        Self::new(inner_config, |config| {
+            Experiment::new(config)
+                .run::<Individual, IndividualData>()
+                .get_final_distribution()
+                .mean
+        })
+    }
+
+    // Function creates the meta data scoring the parameters by the mean final best fitness of the inner experiment
+    // Every inner run uses a copy of the given individual data, e.g. the same cities of the salesman
+    pub fn for_individual_with_data<Individual, IndividualData>(
+        inner_config: &Config,
+        ind_data: IndividualData,
+    ) -> Self
+    where
+        Individual: EvoIndividual<IndividualData> + 'static,
+        IndividualData: Clone + Send + Sync + 'static,
+    {
+        Self::new(inner_config, move |config| {
+            Experiment::new(config)
+                .run_with_data::<Individual, IndividualData>(&ind_data)
+                .get_final_distribution()
+                .mean
+        })
+    }
+}
+
+// Individual whose genome is a set of population parameters
+#[derive(Clone, Debug)]
+pub struct MetaIndividual {
+    pub fitness: f64,
+    pub mut_prob: f32,
+    pub mut_amount: f32,
+    pub crossover_prob: f32,
+    pub selection_strategy: SelectionStrategyType,
+    pub neighbourhood: NeighbourhoodType,
+}
+
+impl MetaIndividual {
+    // Function writes the parameters of the individual into the config
+    pub fn apply_to(&self, config: &mut Config) {
+        config.set("mut_prob", self.mut_prob);
+        config.set("mut_amount", self.mut_amount);
+        config.set("crossover_prob", self.crossover_prob);
+        config.set("selection_strategy", self.selection_strategy.to_string());
+        config.set("neighbourhood", self.neighbourhood.to_string());
+    }
+
+    // Function returns a copy of the config with the parameters of the individual
+    pub fn to_config(&self, base: &Config) -> Config {
+        let mut config = base.clone();
+        self.apply_to(&mut config);
+        config
+    }
+
+    fn _random_variant<T: IntoEnumIterator + Copy>(rng: &mut SmallRng) -> T {
+        let variants: Vec<T> = T::iter().collect();
+        *variants.choose(rng).unwrap()
+    }
+
+    // Function adds gaussian noise with the deviation relative to the range of the parameter
+    fn _mutate_value(rng: &mut SmallRng, value: f32, max: f64, mut_amount: f32) -> f32 {
+        let normal = Normal::new(0.0, mut_amount.abs() as f64 * max).unwrap();
+        (value as f64 + normal.sample(rng)).clamp(0.0, max) as f32
+    }
+}
+
+impl EvoIndividual<MetaData> for MetaIndividual {
+    fn new_randomised(ind_data: &MetaData, rng: &mut SmallRng) -> Self {
+        MetaIndividual {
+            fitness: 0.0,
+            mut_prob: rng.gen_range(0.0..=1.0),
+            mut_amount: rng.gen_range(0.0..=ind_data.max_mut_amount) as f32,
+            crossover_prob: rng.gen_range(0.0..=1.0),
+            selection_strategy: Self::_random_variant(rng),
+            neighbourhood: Self::_random_variant(rng),
+        }
+    }
+
+    // mut_amount is the deviation of the noise relative to the range of each parameter
+    fn mutate(&mut self, ind_data: &MetaData, rng: &mut SmallRng, mut_prob: f32, mut_amount: f32) {
+        if rng.gen_range(0.0..1.0) < mut_prob {
+            self.mut_prob = Self::_mutate_value(rng, self.mut_prob, 1.0, mut_amount);
+        }
+        if rng.gen_range(0.0..1.0) < mut_prob {
+            self.mut_amount =
+                Self::_mutate_value(rng, self.mut_amount, ind_data.max_mut_amount, mut_amount);
+        }
+        if rng.gen_range(0.0..1.0) < mut_prob {
+            self.crossover_prob = Self::_mutate_value(rng, self.crossover_prob, 1.0, mut_amount);
+        }
+        if rng.gen_range(0.0..1.0) < mut_prob {
+            self.selection_strategy = Self::_random_variant(rng);
+        }
+        if rng.gen_range(0.0..1.0) < mut_prob {
+            self.neighbourhood = Self::_random_variant(rng);
+        }
+    }
+
+    // Each parameter is taken from one of the parents
+    fn crossover(&self, another_ind: &Self, _ind_data: &MetaData, rng: &mut SmallRng) -> Self {
+        MetaIndividual {
+            fitness: 0.0,
+            mut_prob: *[self.mut_prob, another_ind.mut_prob].choose(rng).unwrap(),
+            mut_amount: *[self.mut_amount, another_ind.mut_amount]
+                .choose(rng)
+                .unwrap(),
+            crossover_prob: *[self.crossover_prob, another_ind.crossover_prob]
+                .choose(rng)
+                .unwrap(),
+            selection_strategy: *[self.selection_strategy, another_ind.selection_strategy]
+                .choose(rng)
+                .unwrap(),
+            neighbourhood: *[self.neighbourhood, another_ind.neighbourhood]
+                .choose(rng)
+                .unwrap(),
+        }
+    }
+
+    fn count_fitness(&mut self, ind_data: &MetaData) {
+        self.fitness = (ind_data.objective)(&self.to_config(&ind_data.inner_config));
+    }
+
+    fn get_fitness(&self) -> f64 {
+        self.fitness
+    }
+
+    fn get_visuals(&self, _ind_data: &MetaData) -> (f64, f64) {
+        (self.mut_prob as f64, self.crossover_prob as f64)
+    }
+}
+
+impl fmt::Display for MetaIndividual {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "fitness: {:.4}, mut_prob: {:.4}, mut_amount: {:.4}, crossover_prob: {:.4}, selection_strategy: {}, neighbourhood: {}",
+            self.fitness,
+            self.mut_prob,
+            self.mut_amount,
+            self.crossover_prob,
+            self.selection_strategy,
+            self.neighbourhood
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::population::Population;
+    use crate::real_vector::{RealVectorData, RealVectorIndividual};
+    use std::str::FromStr;
+
+    fn sphere(genes: &[f64]) -> f64 {
+        -genes.iter().map(|x| x * x).sum::<f64>()
+    }
+
+    fn inner_config() -> Config {
+        Config::from_str(
+            "{\"pop_width\": 6, \"pop_height\": 6, \"n_runs\": 2, \"n_generations\": 5, \"seed\": 3, \"experiment_parallel\": false, \"meta_max_mut_amount\": 0.5}",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_apply_to() {
+        let ind = MetaIndividual {
+            fitness: 0.0,
+            mut_prob: 0.25,
+            mut_amount: 0.5,
+            crossover_prob: 0.75,
+            selection_strategy: SelectionStrategyType::Roulette,
+            neighbourhood: NeighbourhoodType::C9,
+        };
+
+        // Population reads the parameters written by the individual
+        let config = ind.to_config(&inner_config());
+        let pop: Population<RealVectorIndividual, RealVectorData> =
+            Population::new_with_data(&config, RealVectorData::new(2, -1.0, 1.0, sphere));
+        assert_eq!(pop.get_mut_prob(), 0.25);
+        assert_eq!(pop.get_mut_amount(), 0.5);
+        assert_eq!(pop.get_crossover_prob(), 0.75);
+        assert_eq!(
+            pop.get_selection_strategy(),
+            SelectionStrategyType::Roulette
+        );
+        assert_eq!(pop.get_neighbourhood(), NeighbourhoodType::C9);
+    }
+
+    #[test]
+    fn test_operators_respect_bounds() {
+        let ind_data = MetaData::new(&inner_config(), |_| 0.0);
+        assert_eq!(ind_data.max_mut_amount, 0.5);
+
+        let mut rng = SmallRng::seed_from_u64(0);
+        for _ in 0..100 {
+            let first = MetaIndividual::new_randomised(&ind_data, &mut rng);
+            let mut child = first.crossover(&first, &ind_data, &mut rng);
+            assert_eq!(child.mut_prob, first.mut_prob);
+            assert_eq!(child.neighbourhood, first.neighbourhood);
+
+            child.mutate(&ind_data, &mut rng, 1.0, 1.0);
+            assert!((0.0..=1.0).contains(&child.mut_prob));
+            assert!((0.0..=0.5).contains(&child.mut_amount));
+            assert!((0.0..=1.0).contains(&child.crossover_prob));
+        }
+    }
+
+    #[test]
+    fn test_meta_population() {
+        let ind_data = MetaData::for_individual_with_data::<RealVectorIndividual, RealVectorData>(
+            &inner_config(),
+            RealVectorData::new(2, -1.0, 1.0, sphere),
+        );
+        let config = Config::from_str(
+            "{\"pop_width\": 3, \"pop_height\": 3, \"mut_prob\": 0.5, \"mut_amount\": 0.2, \"seed\": 1}",
+        )
+        .unwrap();
+
+        let mut pop: Population<MetaIndividual, MetaData> =
+            Population::new_with_data(&config, ind_data);
+        pop.next_gen();
+
+        // Fitness is the score of the inner runs, which are seeded by the inner config
+        let mut best = pop.get_best().clone();
+        assert!(best.get_fitness() <= 0.0);
+        let fitness = best.get_fitness();
+        best.count_fitness(pop.get_individual_data());
+        assert_eq!(best.get_fitness(), fitness);
+    }
+}
@@ -0,0 +1,448 @@
+use crate::config::Config;
+use crate::evo_individual::{EvoIndividual, Visualise};
+use crate::population::Population;
+use crate::run_logger::RunLogger;
+use image::{DynamicImage, ImageOutputFormat, RgbImage};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::io::Cursor;
+use std::net::SocketAddr;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+const DEFAULT_MONITOR_ADDRESS: &str = "127.0.0.1:8080";
+const DEFAULT_MONITOR_HISTORY: usize = 10000;
+
+// Period of checking the requests for images while the run is paused
+const PAUSED_POLL: Duration = Duration::from_millis(100);
+
+const INDEX_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head><title>revo monitor</title></head>
+<body>
+<h1>revo monitor</h1>
+<pre id="stats"></pre>
+<form method="post" action="/pause" style="display: inline"><button>Pause</button></form>
+<form method="post" action="/resume" style="display: inline"><button>Resume</button></form>
+<form method="post" action="/checkpoint" style="display: inline"><button>Checkpoint</button></form>
+<p><img id="population" width="512"> <img id="best" width="512"></p>
+<p><a href="/stats">stats</a> <a href="/history">history</a></p>
+<script>
+async function refresh() {
+    const stats = await (await fetch("/stats")).json();
+    document.getElementById("stats").textContent = JSON.stringify(stats, null, 2);
+    document.getElementById("population").src = "/population.png?" + Date.now();
+    document.getElementById("best").src = "/best.png?" + Date.now();
+}
+refresh();
+setInterval(refresh, 5000);
+</script>
+</body>
+</html>
+"#;
+
+// State of the run published by the run loop and read by the HTTP server
+struct MonitorState {
+    // Record of the last update, the same as the record of the JSONL log
+    stats: Value,
+    history: VecDeque<Value>,
+    population_png: Option<Vec<u8>>,
+    best_png: Option<Vec<u8>>,
+    // Number of renders of the images
+    n_renders: u64,
+    render_requested: bool,
+    paused: bool,
+    checkpoint_requested: bool,
+}
+
+struct Shared {
+    state: Mutex<MonitorState>,
+    changed: Condvar,
+}
+
+// Embedded HTTP server for observing and controlling a running evolution from a browser or curl
+// The run loop publishes the population by update, which also blocks while the run is paused
+// Images are rendered only when they are requested, so an unobserved run is not slowed down
+pub struct Monitor {
+    shared: Arc<Shared>,
+    server: Arc<Server>,
+    address: SocketAddr,
+    history_size: usize,
+    start: Instant,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Monitor {
+    // Function starts the server on the address, port 0 picks a free port that is returned by get_address
+    // Only the last history_size records are kept in the fitness history
+    pub fn new(address: &str, history_size: usize) -> Result<Self, String> {
+        let server = Arc::new(
+            Server::http(address)
+                .map_err(|err| format!("Starting monitor on '{}' failed: {}", address, err))?,
+        );
+        let address = server
+            .server_addr()
+            .to_ip()
+            .ok_or_else(|| format!("Monitor address '{}' is not an IP address", address))?;
+
+        let shared = Arc::new(Shared {
+            state: Mutex::new(MonitorState {
+                stats: Value::Null,
+                history: VecDeque::new(),
+                population_png: None,
+                best_png: None,
+                n_renders: 0,
+                render_requested: false,
+                paused: false,
+                checkpoint_requested: false,
+            }),
+            changed: Condvar::new(),
+        });
+
+        let thread = {
+            let shared = shared.clone();
+            let server = server.clone();
+            thread::spawn(move || {
+                while let Ok(request) = server.recv() {
+                    Self::_handle(&shared, request);
+                }
+            })
+        };
+
+        Ok(Monitor {
+            shared,
+            server,
+            address,
+            history_size,
+            start: Instant::now(),
+            thread: Some(thread),
+        })
+    }
+
+    // Function creates the monitor from the "monitor_address" and "monitor_history" config values
+    // The default address is on localhost, so the run can't be controlled from other machines
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let address: String = config
+            .may_get_val("monitor_address")?
+            .unwrap_or(DEFAULT_MONITOR_ADDRESS.to_string());
+
+        Self::new(
+            &address,
+            config
+                .may_get_uint("monitor_history")?
+                .unwrap_or(DEFAULT_MONITOR_HISTORY),
+        )
+    }
+
+    pub fn get_address(&self) -> SocketAddr {
+        self.address
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.shared.state.lock().unwrap().paused
+    }
+
+    // Function returns true once for every checkpoint requested since the last call
+    pub fn take_checkpoint_request(&self) -> bool {
+        std::mem::take(&mut self.shared.state.lock().unwrap().checkpoint_requested)
+    }
+
+    // Function publishes the statistics of the current generation and renders the requested population map
+    // It blocks while the run is paused, the requested images are still rendered meanwhile
+    pub fn update<Individual, IndividualData>(&self, pop: &Population<Individual, IndividualData>)
+    where
+        Individual: EvoIndividual<IndividualData>,
+        IndividualData: Send + Sync,
+    {
+        self._update(pop, |_| None);
+    }
+
+    // Function works like update, the render of the best individual is published as well
+    pub fn update_with_best<Individual, IndividualData>(
+        &self,
+        pop: &Population<Individual, IndividualData>,
+    ) where
+        Individual: EvoIndividual<IndividualData> + Visualise<IndividualData>,
+        IndividualData: Send + Sync,
+    {
+        self._update(pop, |pop| {
+            Some(pop.get_best().visualise(pop.get_individual_data()))
+        });
+    }
+
+    fn _update<Individual, IndividualData, F>(
+        &self,
+        pop: &Population<Individual, IndividualData>,
+        render_best: F,
+    ) where
+        Individual: EvoIndividual<IndividualData>,
+        IndividualData: Send + Sync,
+        F: Fn(&Population<Individual, IndividualData>) -> Option<RgbImage>,
+    {
+        let record = RunLogger::json_record(pop, self.start.elapsed().as_secs_f64());
+
+        let mut state = self.shared.state.lock().unwrap();
+        state.stats = record.clone();
+        if state.history.len() == self.history_size {
+            state.history.pop_front();
+        }
+        if self.history_size > 0 {
+            state.history.push_back(record);
+        }
+
+        loop {
+            if state.render_requested {
+                state.render_requested = false;
+                // Images are rendered without the lock, so the server can answer other requests meanwhile
+                drop(state);
+                let population_png = Self::_encode_png(pop.visualise());
+                let best_png = render_best(pop).map(Self::_encode_png);
+
+                state = self.shared.state.lock().unwrap();
+                state.population_png = Some(population_png);
+                state.best_png = best_png;
+                state.n_renders += 1;
+                self.shared.changed.notify_all();
+            }
+            if !state.paused {
+                break;
+            }
+            state = self
+                .shared
+                .changed
+                .wait_timeout(state, PAUSED_POLL)
+                .unwrap()
+                .0;
+        }
+    }
+
+    fn _encode_png(image: RgbImage) -> Vec<u8> {
+        let mut png = Vec::new();
+        DynamicImage::ImageRgb8(image)
+            .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+            .unwrap();
+        png
+    }
+
+    fn _handle(shared: &Shared, request: Request) {
+        let path = request.url().split('?').next().unwrap_or("").to_string();
+        // Forms of the index page are answered by a redirect back to it, curl and scripts get JSON
+        let from_browser = request.headers().iter().any(|header| {
+            header.field.equiv("Accept") && header.value.as_str().contains("text/html")
+        });
+
+        let response = match (request.method(), path.as_str()) {
+            (Method::Get, "/") => Self::_response(INDEX_PAGE.as_bytes().to_vec(), "text/html"),
+            (Method::Get, "/stats") => {
+                let state = shared.state.lock().unwrap();
+                let mut stats = state.stats.clone();
+                if let Value::Object(stats) = &mut stats {
+                    stats.insert("paused".to_string(), json!(state.paused));
+                }
+                Self::_json_response(&stats)
+            }
+            (Method::Get, "/history") => {
+                let state = shared.state.lock().unwrap();
+                Self::_json_response(&json!(state.history))
+            }
+            (Method::Get, "/population.png") => {
+                Self::_image_response(shared, |state| state.population_png.clone())
+            }
+            (Method::Get, "/best.png") => {
+                Self::_image_response(shared, |state| state.best_png.clone())
+            }
+            (Method::Post, "/pause") | (Method::Post, "/resume") => {
+                let mut state = shared.state.lock().unwrap();
+                state.paused = path == "/pause";
+                // Images of the paused generation are rendered right away
+                state.render_requested |= state.paused;
+                shared.changed.notify_all();
+                Self::_control_response(&json!({ "paused": state.paused }), from_browser)
+            }
+            (Method::Post, "/checkpoint") => {
+                shared.state.lock().unwrap().checkpoint_requested = true;
+                Self::_control_response(&json!({ "checkpoint_requested": true }), from_browser)
+            }
+            (Method::Get, _) | (Method::Post, _) => {
+                Self::_response(b"Not found".to_vec(), "text/plain").with_status_code(404)
+            }
+            _ => {
+                Self::_response(b"Method not allowed".to_vec(), "text/plain").with_status_code(405)
+            }
+        };
+
+        let _ = request.respond(response);
+    }
+
+    // Function serves the last render right away and asks the run for a fresh one, so the server never waits for
+    // the run, 503 is answered until the first render
+    fn _image_response<F>(shared: &Shared, get_png: F) -> Response<Cursor<Vec<u8>>>
+    where
+        F: Fn(&MonitorState) -> Option<Vec<u8>>,
+    {
+        let mut state = shared.state.lock().unwrap();
+        state.render_requested = true;
+        shared.changed.notify_all();
+
+        match get_png(&state) {
+            Some(png) => Self::_response(png, "image/png"),
+            None if state.n_renders == 0 => {
+                Self::_response(b"Image not rendered yet".to_vec(), "text/plain")
+                    .with_status_code(503)
+                    .with_header(Header::from_bytes("Retry-After", "1").unwrap())
+            }
+            None => {
+                Self::_response(b"Image not available".to_vec(), "text/plain").with_status_code(404)
+            }
+        }
+    }
+
+    fn _control_response(value: &Value, from_browser: bool) -> Response<Cursor<Vec<u8>>> {
+        if from_browser {
+            Self::_response(Vec::new(), "text/plain")
+                .with_status_code(303)
+                .with_header(Header::from_bytes("Location", "/").unwrap())
+        } else {
+            Self::_json_response(value)
+        }
+    }
+
+    fn _json_response(value: &Value) -> Response<Cursor<Vec<u8>>> {
+        Self::_response(value.to_string().into_bytes(), "application/json")
+    }
+
+    fn _response(data: Vec<u8>, content_type: &str) -> Response<Cursor<Vec<u8>>> {
+        Response::from_data(data)
+            .with_header(Header::from_bytes("Content-Type", content_type).unwrap())
+    }
+}
+
+impl Drop for Monitor {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockIndividual, MockIndividualData};
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::str::FromStr;
+
+    // Function sends the request and returns the status code and the body of the response
+    fn request(address: SocketAddr, method: &str, path: &str) -> (u16, Vec<u8>) {
+        request_with_headers(address, method, path, "")
+    }
+
+    fn request_with_headers(
+        address: SocketAddr,
+        method: &str,
+        path: &str,
+        headers: &str,
+    ) -> (u16, Vec<u8>) {
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.0\r\nContent-Length: 0\r\n{}\r\n",
+            method, path, headers
+        )
+        .unwrap();
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).unwrap();
+
+        let header_end = response
+            .windows(4)
+            .position(|window| window == b"\r\n\r\n")
+            .unwrap();
+        let status = String::from_utf8_lossy(&response[9..12]).parse().unwrap();
+        (status, response[header_end + 4..].to_vec())
+    }
+
+    // Function repeats the request until the image is rendered
+    fn request_image(address: SocketAddr, path: &str) -> (u16, Vec<u8>) {
+        let start = Instant::now();
+        loop {
+            let (status, body) = request(address, "GET", path);
+            if status != 503 || start.elapsed() > Duration::from_secs(10) {
+                return (status, body);
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    fn request_json(address: SocketAddr, method: &str, path: &str) -> Value {
+        let (status, body) = request(address, method, path);
+        assert_eq!(status, 200);
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[test]
+    fn test_monitor() {
+        let config =
+            Config::from_str("{\"pop_width\": 4, \"pop_height\": 4, \"seed\": 1}").unwrap();
+        let mut pop: Population<MockIndividual, MockIndividualData> = Population::new(&config);
+        let monitor = Arc::new(Monitor::new("127.0.0.1:0", 2).unwrap());
+        let address = monitor.get_address();
+
+        for _ in 0..3 {
+            monitor.update(&pop);
+            pop.next_gen();
+        }
+        // Image is served right away, it is rendered by the next update
+        assert_eq!(request(address, "GET", "/population.png").0, 503);
+        let stats = request_json(address, "GET", "/stats");
+        assert_eq!(stats["generation"], 2);
+        assert_eq!(stats["paused"], false);
+        // Only the last two records are kept
+        let history = request_json(address, "GET", "/history");
+        assert_eq!(history.as_array().unwrap().len(), 2);
+        assert_eq!(history[1]["generation"], 2);
+        assert_eq!(request(address, "GET", "/").0, 200);
+        assert_eq!(request(address, "GET", "/nothing").0, 404);
+
+        // Checkpoint is reported once
+        assert!(!monitor.take_checkpoint_request());
+        request_json(address, "POST", "/checkpoint");
+        assert!(monitor.take_checkpoint_request());
+        assert!(!monitor.take_checkpoint_request());
+
+        // Form of the index page is redirected back to it
+        let (status, _) = request_with_headers(
+            address,
+            "POST",
+            "/checkpoint",
+            "Accept: text/html,application/xhtml+xml\r\n",
+        );
+        assert_eq!(status, 303);
+        assert!(monitor.take_checkpoint_request());
+
+        // Paused run blocks in update, the images are rendered meanwhile
+        assert_eq!(request_json(address, "POST", "/pause")["paused"], true);
+        let run = {
+            let monitor = monitor.clone();
+            thread::spawn(move || {
+                monitor.update(&pop);
+                pop
+            })
+        };
+        let (status, png) = request_image(address, "/population.png");
+        assert_eq!(status, 200);
+        assert_eq!(&png[1..4], b"PNG");
+        // Mock individual can't be visualised
+        assert_eq!(request_image(address, "/best.png").0, 404);
+        assert!(!run.is_finished());
+        assert!(monitor.is_paused());
+
+        assert_eq!(request_json(address, "POST", "/resume")["paused"], false);
+        let pop = run.join().unwrap();
+        assert_eq!(pop.get_generation(), 3);
+        assert_eq!(request_json(address, "GET", "/stats")["generation"], 3);
+    }
+}
@@ -0,0 +1,959 @@
+use crate::config::Config;
+use crate::evo_individual::{hash_value, EvoIndividual, RemoteIndividual};
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::f64::consts::PI;
+use std::fmt;
+use std::sync::Arc;
+use strum_macros::{Display, EnumIter, EnumString};
+
+const DEFAULT_N_ELEMENTS: usize = 20;
+const DEFAULT_PERM_MUTATION_TYPE: PermMutationType = PermMutationType::Inversion;
+const DEFAULT_PERM_CROSSOVER_TYPE: PermCrossoverType = PermCrossoverType::Order;
+
+// Element of a permutation of the numbers from 0 to n - 1
+pub trait PermElement: Copy + PartialEq + fmt::Debug + Send + Sync + 'static {
+    // Function returns the largest index the element can hold
+    fn max_index() -> usize;
+    // Function panics if the index doesn't fit in the element, see PermutationData::check_n_elements
+    fn from_index(index: usize) -> Self;
+    fn to_index(self) -> usize;
+}
+
+macro_rules! impl_perm_element {
+    ($($t:ty),*) => {
+        $(impl PermElement for $t {
+            fn max_index() -> usize {
+                usize::try_from(<$t>::MAX).unwrap_or(usize::MAX)
+            }
+
+            fn from_index(index: usize) -> Self {
+                match <$t>::try_from(index) {
+                    Ok(element) => element,
+                    Err(_) => panic!("Index {} doesn't fit in {}", index, stringify!($t)),
+                }
+            }
+
+            fn to_index(self) -> usize {
+                self as usize
+            }
+        })*
+    };
+}
+
+impl_perm_element!(u8, u16, u32, u64, usize);
+
+// Objective function over the permutation, its value is the fitness that is maximised
+pub type PermObjective<T> = Arc<dyn Fn(&[T]) -> f64 + Send + Sync>;
+
+#[derive(Clone, Copy, PartialEq, Debug, EnumString, EnumIter, Display)]
+pub enum PermMutationType {
+    // Two random elements are swapped
+    #[strum(serialize = "swap")]
+    Swap,
+    // Random element is moved to a random position
+    #[strum(serialize = "insertion")]
+    Insertion,
+    // Random part of the permutation is reversed
+    #[strum(serialize = "inversion")]
+    Inversion,
+    // Random part of the permutation is shuffled
+    #[strum(serialize = "scramble")]
+    Scramble,
+    // Random part of the permutation is shifted to another position
+    #[strum(serialize = "shift")]
+    Shift,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, EnumString, EnumIter, Display)]
+pub enum PermCrossoverType {
+    // Order crossover, part of the first parent is kept and the rest is filled in the order of the second parent
+    #[strum(serialize = "order")]
+    Order,
+    // Partially mapped crossover
+    #[strum(serialize = "pmx")]
+    Pmx,
+    // Cycle crossover, every element keeps the position from one of the parents
+    #[strum(serialize = "cycle")]
+    Cycle,
+    // Edge recombination, the child is built from the neighbourhoods of elements in both parents
+    #[strum(serialize = "edge")]
+    EdgeRecombination,
+    // Random positions are kept from the first parent and the rest is filled in the order of the second parent
+    #[strum(serialize = "position")]
+    PositionBased,
+}
+
+// Function reverses the part of the genes from index from to index to, both included
+// If to is lower than from, the part wraps around the end of the genes
+pub fn reverse_part<T: PermElement>(genes: &mut [T], from: usize, to: usize) {
+    let len = genes.len();
+
+    let to = match from < to {
+        true => to,
+        false => to + len,
+    };
+
+    let mut frmi = from;
+    let mut toi = to;
+
+    while frmi <= toi {
+        let abs_toi = toi % len;
+        let abs_frmi = frmi % len;
+
+        genes.swap(abs_frmi, abs_toi);
+
+        frmi += 1;
+        toi -= 1;
+    }
+}
+
+// Function shifts the part of the genes from index from to index to, both included, by shift positions to the right
+// The part and the shift wrap around the end of the genes
+pub fn shift_multiple<T: PermElement>(genes: &mut [T], from: usize, to: usize, shift: usize) {
+    let len = genes.len();
+    let mut i_from = from;
+
+    let slice_len = if to < from {
+        to + 1 + len - i_from
+    } else {
+        to + 1 - i_from
+    };
+
+    let mut i_to = (to + 1) % len;
+
+    let mut tmp: Vec<T> = Vec::with_capacity(slice_len);
+
+    let mut source_i = i_from;
+    for _ in 0..slice_len {
+        tmp.push(genes[source_i]);
+
+        source_i += 1;
+        if source_i >= len {
+            source_i = 0
+        };
+    }
+
+    // Do shifting
+    for _ in 0..shift {
+        genes.swap(i_from, i_to);
+
+        i_from += 1;
+        if i_from >= len {
+            i_from = 0;
+        }
+
+        i_to += 1;
+        if i_to >= len {
+            i_to = 0;
+        }
+    }
+
+    // Put stuff back
+    let mut source_i = i_from;
+    for item in tmp.iter().take(slice_len) {
+        genes[source_i] = *item;
+
+        source_i += 1;
+        if source_i >= len {
+            source_i = 0
+        };
+    }
+}
+
+thread_local! {
+    // Flags of the used elements for the order crossover, kept per thread so the crossover does not allocate
+    static ORDER_CROSSOVER_USED: RefCell<Vec<bool>> = const { RefCell::new(Vec::new()) };
+}
+
+// Function writes the order crossover of the parents to dest
+// Genes from start_cross_point to end_cross_point (wrapping around) are copied from the first parent, the rest is
+// filled by the unused genes of the second parent, read from other_start_cross_point
+pub fn order_crossover_to<T: PermElement>(
+    first: &[T],
+    second: &[T],
+    dest: &mut [T],
+    start_cross_point: usize,
+    end_cross_point: usize,
+    other_start_cross_point: usize,
+) {
+    ORDER_CROSSOVER_USED.with(|used| {
+        let used = &mut *used.borrow_mut();
+        used.clear();
+        used.resize(first.len(), false);
+        _order_crossover_used(
+            first,
+            second,
+            dest,
+            used,
+            start_cross_point,
+            end_cross_point,
+            other_start_cross_point,
+        );
+    });
+}
+
+fn _order_crossover_used<T: PermElement>(
+    first: &[T],
+    second: &[T],
+    dest: &mut [T],
+    used: &mut [bool],
+    start_cross_point: usize,
+    end_cross_point: usize,
+    other_start_cross_point: usize,
+) {
+    let len = first.len();
+
+    let mut i = start_cross_point;
+
+    // Copy points from first parent
+    loop {
+        dest[i] = first[i];
+        used[first[i].to_index()] = true;
+
+        if i == end_cross_point {
+            break;
+        }
+
+        i += 1;
+        if i >= len {
+            i = 0;
+        }
+    }
+
+    // Copy points from second parent
+    let mut other_i = other_start_cross_point;
+    let mut i = (end_cross_point + 1) % len;
+    loop {
+        if !used[second[other_i].to_index()] {
+            dest[i] = second[other_i];
+            used[second[other_i].to_index()] = true;
+
+            i += 1;
+            if i >= len {
+                i = 0;
+            }
+        }
+
+        if i == start_cross_point {
+            break;
+        }
+
+        other_i += 1;
+        if other_i >= len {
+            other_i = 0;
+        }
+    }
+}
+
+// Function returns positions of the elements in the genes
+fn _positions<T: PermElement>(genes: &[T]) -> Vec<usize> {
+    let mut positions = vec![0; genes.len()];
+    for (i, gene) in genes.iter().enumerate() {
+        positions[gene.to_index()] = i;
+    }
+    positions
+}
+
+// Function returns the partially mapped crossover of the parents
+// Genes from index from to index to, both included, are copied from the first parent, the rest is taken from the
+// second parent and conflicts are resolved by the mapping between the parents in the copied part
+pub fn pmx_crossover<T: PermElement>(first: &[T], second: &[T], from: usize, to: usize) -> Vec<T> {
+    let pos_in_first = _positions(first);
+    let in_segment = |gene: T| {
+        let pos = pos_in_first[gene.to_index()];
+        pos >= from && pos <= to
+    };
+
+    (0..first.len())
+        .map(|i| {
+            if i >= from && i <= to {
+                return first[i];
+            }
+
+            let mut gene = second[i];
+            while in_segment(gene) {
+                gene = second[pos_in_first[gene.to_index()]];
+            }
+            gene
+        })
+        .collect()
+}
+
+// Function returns the cycle crossover of the parents
+// Elements of odd cycles keep positions from the first parent, elements of even cycles from the second one
+pub fn cycle_crossover<T: PermElement>(first: &[T], second: &[T]) -> Vec<T> {
+    let pos_in_first = _positions(first);
+    let mut child = first.to_vec();
+    let mut visited = vec![false; first.len()];
+    let mut from_first = true;
+
+    for start in 0..first.len() {
+        if visited[start] {
+            continue;
+        }
+
+        let mut i = start;
+        loop {
+            visited[i] = true;
+            child[i] = if from_first { first[i] } else { second[i] };
+
+            i = pos_in_first[second[i].to_index()];
+            if i == start {
+                break;
+            }
+        }
+
+        from_first = !from_first;
+    }
+
+    child
+}
+
+// Function returns the edge recombination of the parents
+// Next element of the child is the neighbour of the last element with the fewest remaining neighbours
+pub fn edge_recombination<T: PermElement>(first: &[T], second: &[T], rng: &mut SmallRng) -> Vec<T> {
+    let len = first.len();
+    if len == 0 {
+        return Vec::new();
+    }
+
+    // Neighbours of every element in both of the parents
+    let mut edges: Vec<Vec<usize>> = vec![Vec::with_capacity(4); len];
+    for parent in [first, second] {
+        for i in 0..len {
+            let gene = parent[i].to_index();
+            for neighbour in [parent[(i + len - 1) % len], parent[(i + 1) % len]] {
+                let neighbour = neighbour.to_index();
+                if neighbour != gene && !edges[gene].contains(&neighbour) {
+                    edges[gene].push(neighbour);
+                }
+            }
+        }
+    }
+
+    let mut used = vec![false; len];
+    let mut child = Vec::with_capacity(len);
+    let mut current = first[0].to_index();
+
+    loop {
+        child.push(T::from_index(current));
+        used[current] = true;
+
+        if child.len() == len {
+            break;
+        }
+
+        // Current element can't be chosen again
+        for neighbour in edges[current].clone() {
+            edges[neighbour].retain(|&gene| gene != current);
+        }
+
+        current = match edges[current].iter().map(|&gene| edges[gene].len()).min() {
+            Some(min_edges) => {
+                let candidates: Vec<usize> = edges[current]
+                    .iter()
+                    .copied()
+                    .filter(|&gene| edges[gene].len() == min_edges)
+                    .collect();
+                *candidates.choose(rng).unwrap()
+            }
+            None => {
+                let unused: Vec<usize> = (0..len).filter(|&gene| !used[gene]).collect();
+                *unused.choose(rng).unwrap()
+            }
+        };
+    }
+
+    child
+}
+
+// Function returns the position-based crossover of the parents
+// Every position is kept from the first parent with the probability 0.5, the rest is filled in the order of the second
+// parent
+pub fn position_based_crossover<T: PermElement>(
+    first: &[T],
+    second: &[T],
+    rng: &mut SmallRng,
+) -> Vec<T> {
+    let kept: Vec<bool> = (0..first.len()).map(|_| rng.gen_bool(0.5)).collect();
+
+    let mut used = vec![false; first.len()];
+    for (gene, _) in first.iter().zip(kept.iter()).filter(|(_, kept)| **kept) {
+        used[gene.to_index()] = true;
+    }
+
+    let mut rest = second.iter().filter(|gene| !used[gene.to_index()]);
+
+    first
+        .iter()
+        .zip(kept.iter())
+        .map(|(gene, kept)| if *kept { *gene } else { *rest.next().unwrap() })
+        .collect()
+}
+
+// Function returns the ratio of the edges of the first tour that are not in the second tour
+// Tours are cyclic and undirected, so the rotated or reversed tour has the distance 0
+pub fn edge_distance<T: PermElement>(first: &[T], second: &[T]) -> f64 {
+    let len = first.len();
+    if len < 2 {
+        return 0.0;
+    }
+
+    let mut neighbours = vec![[0usize; 2]; len];
+    for (i, gene) in second.iter().enumerate() {
+        neighbours[gene.to_index()] = [
+            second[(i + len - 1) % len].to_index(),
+            second[(i + 1) % len].to_index(),
+        ];
+    }
+
+    let n_missing = (0..len)
+        .filter(|&i| !neighbours[first[i].to_index()].contains(&first[(i + 1) % len].to_index()))
+        .count();
+
+    n_missing as f64 / len as f64
+}
+
+// Function returns the hash of the tour, which is the same for all of its rotations and reversals
+pub fn tour_hash<T: PermElement>(genes: &[T]) -> u64 {
+    let len = genes.len();
+    let start = (0..len).min_by_key(|&i| genes[i].to_index()).unwrap_or(0);
+
+    // Tour starts at the lowest element and continues to its lower neighbour
+    let forward =
+        len < 2 || genes[(start + 1) % len].to_index() <= genes[(start + len - 1) % len].to_index();
+    let canonical: Vec<usize> = (0..len)
+        .map(|k| {
+            let i = if forward { start + k } else { start + len - k };
+            genes[i % len].to_index()
+        })
+        .collect();
+
+    hash_value(&canonical)
+}
+
+pub fn swap_mutation<T: PermElement>(genes: &mut [T], rng: &mut SmallRng) {
+    let i = rng.gen_range(0..genes.len());
+    let j = rng.gen_range(0..genes.len());
+    genes.swap(i, j);
+}
+
+pub fn insertion_mutation<T: PermElement>(genes: &mut [T], rng: &mut SmallRng) {
+    let from = rng.gen_range(0..genes.len());
+    let to = rng.gen_range(0..genes.len());
+
+    if from < to {
+        genes[from..=to].rotate_left(1);
+    } else {
+        genes[to..=from].rotate_right(1);
+    }
+}
+
+pub fn inversion_mutation<T: PermElement>(genes: &mut [T], rng: &mut SmallRng) {
+    let from = rng.gen_range(0..genes.len());
+    let to = rng.gen_range(0..genes.len());
+    reverse_part(genes, from, to);
+}
+
+pub fn scramble_mutation<T: PermElement>(genes: &mut [T], rng: &mut SmallRng) {
+    let mut from = rng.gen_range(0..genes.len());
+    let mut to = rng.gen_range(0..genes.len());
+    if from > to {
+        std::mem::swap(&mut from, &mut to);
+    }
+    genes[from..=to].shuffle(rng);
+}
+
+pub fn shift_mutation<T: PermElement>(genes: &mut [T], rng: &mut SmallRng) {
+    let from = rng.gen_range(0..genes.len());
+    let to = rng.gen_range(0..genes.len());
+    let shift = rng.gen_range(1..genes.len().max(2));
+    shift_multiple(genes, from, to, shift);
+}
+
+#[derive(Clone)]
+pub struct PermutationData<T: PermElement> {
+    pub n_elements: usize,
+    pub objective: PermObjective<T>,
+
+    pub mutation_type: PermMutationType,
+    pub crossover_type: PermCrossoverType,
+}
+
+impl<T: PermElement> PermutationData<T> {
+    // Function panics if the elements don't fit in the element type, from_config returns an error instead
+    pub fn new<F>(n_elements: usize, objective: F) -> Self
+    where
+        F: Fn(&[T]) -> f64 + Send + Sync + 'static,
+    {
+        if let Err(err) = Self::check_n_elements(n_elements) {
+            panic!("{}", err);
+        }
+
+        PermutationData {
+            n_elements,
+            objective: Arc::new(objective),
+            mutation_type: DEFAULT_PERM_MUTATION_TYPE,
+            crossover_type: DEFAULT_PERM_CROSSOVER_TYPE,
+        }
+    }
+
+    // Function creates data with the genome parameters from the config and the given objective
+    pub fn from_config<F>(config: &Config, objective: F) -> Result<Self, String>
+    where
+        F: Fn(&[T]) -> f64 + Send + Sync + 'static,
+    {
+        let n_elements = config
+            .may_get_uint("n_elements")?
+            .unwrap_or(DEFAULT_N_ELEMENTS);
+        Self::check_n_elements(n_elements)?;
+        let mut ind_data = Self::new(n_elements, objective);
+
+        ind_data.mutation_type = config
+            .may_get_enum("perm_mutation")?
+            .unwrap_or(DEFAULT_PERM_MUTATION_TYPE);
+        ind_data.crossover_type = config
+            .may_get_enum("perm_crossover")?
+            .unwrap_or(DEFAULT_PERM_CROSSOVER_TYPE);
+
+        Ok(ind_data)
+    }
+
+    // Function checks that the indices from 0 to n_elements - 1 fit in the element type
+    pub fn check_n_elements(n_elements: usize) -> Result<(), String> {
+        if n_elements.saturating_sub(1) > T::max_index() {
+            return Err(format!(
+                "{} elements don't fit in the permutation element type, the largest index is {}",
+                n_elements,
+                T::max_index()
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct PermutationIndividual<T: PermElement> {
+    pub fitness: f64,
+    pub genes: Vec<T>,
+}
+
+impl<T: PermElement> PermutationIndividual<T> {
+    pub fn new(genes: Vec<T>) -> Self {
+        PermutationIndividual {
+            fitness: 0.0,
+            genes,
+        }
+    }
+
+    pub fn get_genes(&self) -> &[T] {
+        &self.genes
+    }
+
+    fn _order_crossover_into(first: &[T], second: &[T], dest: &mut [T], rng: &mut SmallRng) {
+        let len = first.len();
+        order_crossover_to(
+            first,
+            second,
+            dest,
+            rng.gen_range(0..len),
+            rng.gen_range(0..len),
+            rng.gen_range(0..len),
+        );
+    }
+}
+
+impl<T: PermElement> EvoIndividual<PermutationData<T>> for PermutationIndividual<T> {
+    fn new_randomised(ind_data: &PermutationData<T>, rng: &mut SmallRng) -> Self {
+        let mut genes: Vec<T> = (0..ind_data.n_elements).map(T::from_index).collect();
+        genes.shuffle(rng);
+
+        Self::new(genes)
+    }
+
+    // Mutation is applied with the probability of mutation, amount of mutation is not used
+    fn mutate(
+        &mut self,
+        ind_data: &PermutationData<T>,
+        rng: &mut SmallRng,
+        mut_prob: f32,
+        _mut_amount: f32,
+    ) {
+        if self.genes.len() < 2 || rng.gen_range(0.0..1.0) >= mut_prob {
+            return;
+        }
+
+        match ind_data.mutation_type {
+            PermMutationType::Swap => swap_mutation(&mut self.genes, rng),
+            PermMutationType::Insertion => insertion_mutation(&mut self.genes, rng),
+            PermMutationType::Inversion => inversion_mutation(&mut self.genes, rng),
+            PermMutationType::Scramble => scramble_mutation(&mut self.genes, rng),
+            PermMutationType::Shift => shift_mutation(&mut self.genes, rng),
+        }
+    }
+
+    fn crossover(
+        &self,
+        another_ind: &PermutationIndividual<T>,
+        ind_data: &PermutationData<T>,
+        rng: &mut SmallRng,
+    ) -> PermutationIndividual<T> {
+        let len = self.genes.len();
+        if len == 0 {
+            return self.clone();
+        }
+
+        let genes = match ind_data.crossover_type {
+            PermCrossoverType::Order => {
+                let mut genes = self.genes.clone();
+                Self::_order_crossover_into(&self.genes, &another_ind.genes, &mut genes, rng);
+                genes
+            }
+            PermCrossoverType::Pmx => {
+                let mut from = rng.gen_range(0..len);
+                let mut to = rng.gen_range(0..len);
+                if from > to {
+                    std::mem::swap(&mut from, &mut to);
+                }
+                pmx_crossover(&self.genes, &another_ind.genes, from, to)
+            }
+            PermCrossoverType::Cycle => cycle_crossover(&self.genes, &another_ind.genes),
+            PermCrossoverType::EdgeRecombination => {
+                edge_recombination(&self.genes, &another_ind.genes, rng)
+            }
+            PermCrossoverType::PositionBased => {
+                position_based_crossover(&self.genes, &another_ind.genes, rng)
+            }
+        };
+
+        Self::new(genes)
+    }
+
+    // Order crossover is written into the genome of dest, the other types create a new genome
+    fn crossover_into(
+        &self,
+        another_ind: &PermutationIndividual<T>,
+        ind_data: &PermutationData<T>,
+        rng: &mut SmallRng,
+        dest: &mut PermutationIndividual<T>,
+    ) {
+        if ind_data.crossover_type != PermCrossoverType::Order || self.genes.is_empty() {
+            *dest = self.crossover(another_ind, ind_data, rng);
+            return;
+        }
+
+        dest.fitness = 0.0;
+        dest.genes.clone_from(&self.genes);
+        Self::_order_crossover_into(&self.genes, &another_ind.genes, &mut dest.genes, rng);
+    }
+
+    fn count_fitness(&mut self, ind_data: &PermutationData<T>) {
+        self.fitness = (ind_data.objective)(&self.genes);
+    }
+
+    fn get_fitness(&self) -> f64 {
+        self.fitness
+    }
+
+    // Genome is copied into the allocation of this individual
+    fn copy_from(&mut self, other: &Self) {
+        self.fitness = other.fitness;
+        self.genes.clone_from(&other.genes);
+    }
+
+    // Visuals are the projections of the elements to the sine and cosine of their positions
+    // Permutations with similar order of the elements have similar visuals
+    fn get_visuals(&self, _ind_data: &PermutationData<T>) -> (f64, f64) {
+        let len = self.genes.len().max(1) as f64;
+        let mut a = 0.0;
+        let mut b = 0.0;
+
+        for (i, gene) in self.genes.iter().enumerate() {
+            let angle = 2.0 * PI * i as f64 / len;
+            a += angle.sin() * gene.to_index() as f64 / len;
+            b += angle.cos() * gene.to_index() as f64 / len;
+        }
+
+        (a, b)
+    }
+
+    // Distance is the ratio of the positions with different elements
+    fn distance(&self, other: &Self, _ind_data: &PermutationData<T>) -> f64 {
+        let n_different = self
+            .genes
+            .iter()
+            .zip(other.genes.iter())
+            .filter(|(a, b)| a != b)
+            .count();
+
+        n_different as f64 / self.genes.len().max(1) as f64
+    }
+
+    fn genome_hash(&self, _ind_data: &PermutationData<T>) -> u64 {
+        let indices: Vec<usize> = self.genes.iter().map(|gene| gene.to_index()).collect();
+        hash_value(&indices)
+    }
+}
+
+impl<T: PermElement> RemoteIndividual<PermutationData<T>> for PermutationIndividual<T> {
+    fn genome_to_json(&self) -> Value {
+        json!(self
+            .genes
+            .iter()
+            .map(|gene| gene.to_index())
+            .collect::<Vec<usize>>())
+    }
+
+    // Genome has to be a permutation of the elements of the data
+    fn from_genome_json(genome: &Value, ind_data: &PermutationData<T>) -> Result<Self, String> {
+        let indices = genome
+            .as_array()
+            .and_then(|genes| {
+                genes
+                    .iter()
+                    .map(|gene| gene.as_u64().map(|index| index as usize))
+                    .collect::<Option<Vec<usize>>>()
+            })
+            .ok_or_else(|| format!("Genome '{}' is not an array of indices", genome))?;
+
+        let mut used = vec![false; ind_data.n_elements];
+        for &index in &indices {
+            if index >= ind_data.n_elements || used[index] {
+                return Err(format!(
+                    "Genome '{}' is not a permutation of {} elements",
+                    genome, ind_data.n_elements
+                ));
+            }
+            used[index] = true;
+        }
+        if indices.len() != ind_data.n_elements {
+            return Err(format!(
+                "Genome has {} elements instead of {}",
+                indices.len(),
+                ind_data.n_elements
+            ));
+        }
+
+        Ok(Self::new(indices.into_iter().map(T::from_index).collect()))
+    }
+
+    fn set_fitness(&mut self, fitness: f64) {
+        self.fitness = fitness;
+    }
+}
+
+impl<T: PermElement> fmt::Display for PermutationIndividual<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}, fitness: {}", self.genes, self.fitness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::population::Population;
+    use rand::SeedableRng;
+    use std::str::FromStr;
+    use strum::IntoEnumIterator;
+
+    fn is_permutation(genes: &[u16]) -> bool {
+        let mut sorted = genes.to_vec();
+        sorted.sort();
+        sorted == (0..genes.len() as u16).collect::<Vec<u16>>()
+    }
+
+    #[test]
+    fn test_edge_distance_and_tour_hash() {
+        let tour: Vec<u16> = vec![0, 1, 2, 3, 4, 5];
+        let rotated: Vec<u16> = vec![3, 4, 5, 0, 1, 2];
+        let reversed: Vec<u16> = vec![2, 1, 0, 5, 4, 3];
+
+        // Rotated and reversed tours have the same edges
+        assert_eq!(edge_distance(&tour, &rotated), 0.0);
+        assert_eq!(edge_distance(&tour, &reversed), 0.0);
+        assert_eq!(tour_hash(&tour), tour_hash(&rotated));
+        assert_eq!(tour_hash(&tour), tour_hash(&reversed));
+
+        // Swapping two neighbours changes 2 of the 6 edges
+        let swapped: Vec<u16> = vec![0, 2, 1, 3, 4, 5];
+        assert!((edge_distance(&tour, &swapped) - 2.0 / 6.0).abs() < 1e-12);
+        assert_ne!(tour_hash(&tour), tour_hash(&swapped));
+    }
+
+    #[test]
+    fn test_shift_multiple() {
+        // 6 elements test
+        let genes_6: Vec<u16> = vec![0, 1, 2, 3, 4, 5];
+
+        let mut genes = genes_6.clone();
+        shift_multiple(&mut genes, 0, 2, 3);
+        assert_eq!(genes, vec![3, 4, 5, 0, 1, 2]);
+
+        genes = genes_6.clone();
+        shift_multiple(&mut genes, 0, 2, 1);
+        assert_eq!(genes, vec![3, 0, 1, 2, 4, 5]);
+
+        genes = genes_6.clone();
+        shift_multiple(&mut genes, 0, 2, 6);
+        assert_eq!(genes, vec![0, 1, 2, 3, 4, 5]);
+
+        // 7 elements test
+        let genes_7: Vec<u16> = vec![0, 1, 2, 3, 4, 5, 6];
+
+        genes = genes_7.clone();
+        shift_multiple(&mut genes, 1, 3, 7);
+        assert_eq!(genes, vec![6, 1, 2, 3, 0, 4, 5]);
+
+        genes = genes_7.clone();
+        shift_multiple(&mut genes, 1, 3, 13);
+        assert_eq!(genes, vec![1, 2, 3, 5, 6, 0, 4]);
+
+        genes = genes_7.clone();
+        shift_multiple(&mut genes, 1, 3, 0);
+        assert_eq!(genes, vec![0, 1, 2, 3, 4, 5, 6]);
+
+        genes = genes_7.clone();
+        shift_multiple(&mut genes, 3, 3, 5);
+        assert_eq!(genes, vec![1, 3, 2, 4, 5, 6, 0]);
+
+        genes = genes_7.clone();
+        shift_multiple(&mut genes, 3, 3, 6);
+        assert_eq!(genes, vec![1, 2, 3, 4, 5, 6, 0]);
+    }
+
+    #[test]
+    fn test_reverse_part() {
+        let genes_6: Vec<u16> = vec![0, 1, 2, 3, 4, 5];
+
+        let mut genes = genes_6.clone();
+        reverse_part(&mut genes, 0, 2);
+        assert_eq!(genes, vec![2, 1, 0, 3, 4, 5]);
+
+        genes = genes_6.clone();
+        reverse_part(&mut genes, 5, 0);
+        assert_eq!(genes, vec![5, 1, 2, 3, 4, 0]);
+
+        genes = genes_6.clone();
+        reverse_part(&mut genes, 5, 1);
+        assert_eq!(genes, vec![0, 5, 2, 3, 4, 1]);
+
+        genes = genes_6.clone();
+        reverse_part(&mut genes, 5, 2);
+        assert_eq!(genes, vec![1, 0, 5, 3, 4, 2]);
+
+        genes = genes_6.clone();
+        reverse_part(&mut genes, 2, 2);
+        assert_eq!(genes, vec![4, 3, 2, 1, 0, 5]);
+    }
+
+    #[test]
+    fn test_order_crossover_to() {
+        let first: Vec<u16> = vec![0, 1, 2, 3, 4, 5];
+        let second: Vec<u16> = vec![5, 4, 3, 2, 1, 0];
+        let mut dest = first.clone();
+
+        order_crossover_to(&first, &second, &mut dest, 0, 2, 0);
+        assert_eq!(dest, vec![0, 1, 2, 5, 4, 3]);
+
+        order_crossover_to(&first, &second, &mut dest, 5, 0, 1);
+        assert_eq!(dest, vec![0, 4, 3, 2, 1, 5]);
+
+        let first: Vec<u16> = vec![4, 1, 3, 2, 5, 0];
+        let second: Vec<u16> = vec![3, 5, 0, 1, 4, 2];
+        order_crossover_to(&first, &second, &mut dest, 1, 3, 2);
+        assert_eq!(dest, vec![5, 1, 3, 2, 0, 4]);
+    }
+
+    #[test]
+    fn test_pmx_and_cycle_crossover() {
+        let first: Vec<u16> = vec![0, 1, 2, 3, 4, 5, 6, 7, 8];
+        let second: Vec<u16> = vec![8, 2, 6, 7, 1, 5, 4, 0, 3];
+
+        // Segment 3..=5 is kept, conflicting 4 is replaced by 1 and 3 by 7 through the mapping
+        assert_eq!(
+            pmx_crossover(&first, &second, 3, 5),
+            vec![8, 2, 6, 3, 4, 5, 1, 0, 7]
+        );
+
+        // Cycles of positions are (0, 8, 3, 7), (1, 2, 6, 4) and (5)
+        assert_eq!(
+            cycle_crossover(&first, &second),
+            vec![0, 2, 6, 3, 1, 5, 4, 7, 8]
+        );
+    }
+
+    #[test]
+    fn test_operators_keep_permutation() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut ind_data: PermutationData<u16> = PermutationData::new(12, |_| 0.0);
+
+        for mutation_type in PermMutationType::iter() {
+            for crossover_type in PermCrossoverType::iter() {
+                ind_data.mutation_type = mutation_type;
+                ind_data.crossover_type = crossover_type;
+
+                for _ in 0..50 {
+                    let first = PermutationIndividual::new_randomised(&ind_data, &mut rng);
+                    let second = PermutationIndividual::new_randomised(&ind_data, &mut rng);
+
+                    let mut child = first.crossover(&second, &ind_data, &mut rng);
+                    assert!(is_permutation(&child.genes));
+
+                    child.mutate(&ind_data, &mut rng, 1.0, 0.0);
+                    assert!(is_permutation(&child.genes));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_population() {
+        let config = Config::from_str(
+            "{\"pop_width\": 16, \"pop_height\": 16, \"mut_prob\": 0.5, \"mut_amount\": 0.0, \"crossover_prob\": 0.3, \"seed\": 3, \"n_elements\": 8, \"perm_crossover\": \"pmx\"}",
+        )
+        .unwrap();
+
+        // Fitness is the number of elements at their own position
+        let ind_data: PermutationData<u8> =
+            PermutationData::from_config(&config, |genes: &[u8]| {
+                genes
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, gene)| *i == **gene as usize)
+                    .count() as f64
+            })
+            .unwrap();
+        assert_eq!(ind_data.crossover_type, PermCrossoverType::Pmx);
+
+        // Elements must fit in the element type
+        assert!(PermutationData::<u8>::check_n_elements(256).is_ok());
+        assert!(PermutationData::<u8>::check_n_elements(257).is_err());
+        let large = Config::from_str("{\"n_elements\": 300}").unwrap();
+        assert!(PermutationData::<u8>::from_config(&large, |_| 0.0).is_err());
+        assert!(PermutationData::<u16>::from_config(&large, |_| 0.0).is_ok());
+
+        // Genome is read back only if it is a permutation of the elements
+        let genome = json!([2, 0, 1, 3, 4, 5, 6, 7]);
+        let ind = PermutationIndividual::from_genome_json(&genome, &ind_data).unwrap();
+        assert_eq!(ind.genome_to_json(), genome);
+        for genome in [
+            json!([0, 0, 1, 2, 3, 4, 5, 6]),
+            json!([0, 1, 2]),
+            json!([8, 0]),
+        ] {
+            assert!(PermutationIndividual::from_genome_json(&genome, &ind_data).is_err());
+        }
+
+        let mut pop: Population<PermutationIndividual<u8>, PermutationData<u8>> =
+            Population::new_with_data(&config, ind_data);
+        for _ in 0..50 {
+            pop.next_gen();
+        }
+
+        assert_eq!(pop.get_best().get_fitness(), 8.0);
+    }
+}
@@ -1,13 +1,23 @@
 use super::evo_individual::EvoIndividual;
+use crate::batch_queue::BatchEvaluator;
+use crate::colormap::Colormap;
 use crate::config::Config;
-use crate::evo_individual::EvoIndividualData;
+use crate::evo_individual::{EvoIndividualData, RemoteIndividual};
+use crate::genealogy::{Birth, Genealogy, Operator};
+use crate::hall_of_fame::HallOfFame;
 use crate::rand::SeedableRng;
-use crate::utils::{IndexedLabData, LabData};
+use crate::utils::{cmp_fitness, is_valid_fitness, normalize_component, IndexedLabData};
 use image::RgbImage;
 use lab::Lab;
 use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
 use rand::Rng;
 use rayon::prelude::*;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
 use strum_macros::{Display, EnumIter, EnumString};
 
 const DEFAULT_POP_WIDTH: usize = 128;
@@ -16,9 +26,28 @@ const DEFAULT_MUT_PROB: f32 = 0.1;
 const DEFAULT_MUT_AMOUNT: f32 = 1.0;
 const DEFAULT_CROSSOVER_PROB: f32 = 0.1;
 const DEFAULT_SELECTION_STRATEGY_TYPE: SelectionStrategyType = SelectionStrategyType::Tournament;
+const DEFAULT_BOUNDARY_TYPE: BoundaryType = BoundaryType::Torus;
+const DEFAULT_NEIGHBOURHOOD_TYPE: NeighbourhoodType = NeighbourhoodType::L5;
+const DEFAULT_VISUALISATION_MODE: VisualisationMode = VisualisationMode::Lab;
+const DEFAULT_COLORMAP: Colormap = Colormap::Viridis;
 const MAX_NEIGHBOURS: usize = 9;
-
-#[derive(Clone, EnumString, EnumIter, Display)]
+const DEFAULT_DIVERSITY_SAMPLES: usize = 1000;
+const DEFAULT_NICHING_TYPE: NichingType = NichingType::None;
+const DEFAULT_NICHE_RADIUS: f64 = 1.0;
+const DEFAULT_NICHE_CAPACITY: usize = 1;
+const DEFAULT_SHARING_ALPHA: f64 = 1.0;
+const DEFAULT_RESTART_TYPE: RestartType = RestartType::None;
+const DEFAULT_STAGNATION_GENERATIONS: usize = 50;
+const DEFAULT_RESTART_FRACTION: f64 = 0.5;
+const DEFAULT_RESTART_ELITE: usize = 1;
+const DEFAULT_IMMIGRANT_INTERVAL: usize = 0;
+const DEFAULT_IMMIGRANT_FRACTION: f64 = 0.05;
+// Stream of the random numbers for sampling of the diversity, distinct from the streams of the generations
+const DIVERSITY_STREAM: u64 = u64::MAX;
+// Streams of the random numbers for restarts and immigrants count down from here, one per generation
+const RANDOMISATION_STREAM: u64 = u64::MAX - 1;
+
+#[derive(Clone, Copy, PartialEq, Debug, EnumString, EnumIter, Display)]
 pub enum SelectionStrategyType {
     #[strum(serialize = "tournament")]
     Tournament,
@@ -26,9 +55,222 @@ pub enum SelectionStrategyType {
     Roulette,
 }
 
+#[derive(Clone, Copy, PartialEq, Debug, EnumString, EnumIter, Display)]
+pub enum BoundaryType {
+    // Neighbourhood wraps around in both X and Y
+    #[strum(serialize = "torus")]
+    Torus,
+    // Neighbours outside of the grid are left out
+    #[strum(serialize = "clamped")]
+    Clamped,
+    // Neighbours outside of the grid are mirrored back into the grid
+    #[strum(serialize = "reflecting")]
+    Reflecting,
+    // Neighbourhood wraps around in X, neighbours outside of the grid in Y are left out
+    #[strum(serialize = "cylinder")]
+    Cylinder,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, EnumString, EnumIter, Display)]
+pub enum NeighbourhoodType {
+    // The cell and its 4 neighbours in a + shape
+    #[strum(serialize = "l5")]
+    L5,
+    // The cell and its 8 neighbours in a 3x3 square
+    #[strum(serialize = "c9")]
+    C9,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, EnumString, EnumIter, Display)]
+pub enum NichingType {
+    #[strum(serialize = "none")]
+    None,
+    // Fitness used for selection is divided by the niche count of the neighbours closer than niche_radius
+    #[strum(serialize = "sharing")]
+    Sharing,
+    // Deterministic crowding, offspring competes with its most similar parent for the cell of that parent
+    #[strum(serialize = "crowding")]
+    Crowding,
+    // Only niche_capacity best individuals closer than niche_radius keep their fitness for selection
+    #[strum(serialize = "clearing")]
+    Clearing,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, EnumString, EnumIter, Display)]
+pub enum RestartType {
+    #[strum(serialize = "none")]
+    None,
+    // Fraction restart_fraction of the cells is re-randomised, the elite is kept
+    #[strum(serialize = "partial")]
+    Partial,
+    // All cells except the elite are re-randomised and the lineages start over
+    #[strum(serialize = "full")]
+    Full,
+}
+
+// Way the individuals of the old grid fill the new grid when the population is resized
+#[derive(Clone, Copy, PartialEq, Debug, EnumString, EnumIter, Display)]
+pub enum ResizeType {
+    // Old grid is repeated, shrinking keeps its top left part
+    #[strum(serialize = "tile")]
+    Tile,
+    // Old grid is stretched, every new cell gets the individual of the nearest old cell
+    #[strum(serialize = "interpolate")]
+    Interpolate,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Display)]
+pub enum PopulationEventType {
+    #[strum(serialize = "partial_restart")]
+    PartialRestart,
+    #[strum(serialize = "full_restart")]
+    FullRestart,
+    #[strum(serialize = "immigrants")]
+    Immigrants,
+    // Cells are the new size of the population
+    #[strum(serialize = "resize")]
+    Resize,
+}
+
+// Change of the population made by the restart and immigrant policies or by resizing
+#[derive(Clone, Debug, PartialEq)]
+pub struct PopulationEvent {
+    // Generation in which the event happened, after the individuals of the generation were created
+    pub generation: usize,
+    pub event_type: PopulationEventType,
+    // Number of cells that got new random individuals
+    pub n_cells: usize,
+}
+
+impl fmt::Display for PopulationEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.event_type, self.n_cells)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, EnumString, EnumIter, Display)]
+pub enum VisualisationMode {
+    // Fitness is mapped to L and visual attributes to A and B, all rank-based
+    #[strum(serialize = "lab")]
+    Lab,
+    // Rank-based fitness heatmap using the colormap
+    #[strum(serialize = "fitness")]
+    Fitness,
+    // Fitness heatmap using the colormap with linear normalisation between fixed bounds
+    #[strum(serialize = "absolute")]
+    Absolute,
+    // Number of generations the cell is occupied by the offspring of its own previous occupant
+    #[strum(serialize = "age")]
+    Age,
+    // Each cell is coloured by the initial cell its lineage descends from
+    #[strum(serialize = "lineage")]
+    Lineage,
+    // Rank-based heatmap of the mean distance between the individual and its neighbours
+    #[strum(serialize = "diversity")]
+    Diversity,
+}
+
+impl VisualisationMode {
+    // Function reads the "visualise" config value, which is either a boolean or the name of the mode
+    // Returns None if the visualisation is disabled, true selects the default mode
+    pub fn from_config(config: &Config) -> Result<Option<Self>, String> {
+        match config.json.get("visualise") {
+            None | Some(Value::Bool(false)) => Ok(None),
+            Some(Value::Bool(true)) => Ok(Some(DEFAULT_VISUALISATION_MODE)),
+            Some(Value::String(_)) => config.may_get_enum("visualise"),
+            Some(value) => Err(format!(
+                "Value '{}' of key 'visualise' is neither a boolean nor a visualisation mode",
+                value
+            )),
+        }
+    }
+}
+
+// Diversity of the genomes of the population
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiversityStats {
+    // Mean distance between pairs of individuals, sampled in large populations
+    pub mean_distance: f64,
+    // Mean Shannon entropy of the genome hashes in the neighbourhoods, normalised to 0 (all same) to 1 (all different)
+    pub neighbourhood_entropy: f64,
+    // Number of distinct genome hashes
+    pub n_distinct: usize,
+}
+
+// Summary statistics of the fitness of the population
+// Individuals with NaN or infinite fitness are only counted by n_invalid, the other values are NaN if all are invalid
+#[derive(Clone, Debug, PartialEq)]
+pub struct PopulationStats {
+    pub best_fitness: f64,
+    pub worst_fitness: f64,
+    pub mean_fitness: f64,
+    pub median_fitness: f64,
+    pub std_fitness: f64,
+    pub n_invalid: usize,
+}
+
+// Function selecting the parent from the indices of the neighbourhood by the selection fitness
+type SelectionFn = fn(&mut SmallRng, &[usize], &[f64]) -> usize;
+// Function writing the indices of the neighbours of the cell to the buffer for the grid width and height
+type NeighboursFn = fn(usize, usize, usize, &mut [usize; MAX_NEIGHBOURS]) -> usize;
+// Function setting the fitness of all offspring of a generation at once
+type BatchFitnessFn<Individual> = Arc<dyn Fn(&mut [Individual]) + Send + Sync>;
+
+// Rule for mapping a coordinate that can be out of the grid to a cell coordinate
+trait EdgeRule {
+    // Returns None if there is no cell for the coordinate
+    fn apply(c: isize, size: usize) -> Option<usize>;
+}
+
+struct WrapEdge;
+struct ClampEdge;
+struct ReflectEdge;
+
+impl EdgeRule for WrapEdge {
+    #[inline]
+    fn apply(c: isize, size: usize) -> Option<usize> {
+        Some(c.rem_euclid(size as isize) as usize)
+    }
+}
+
+impl EdgeRule for ClampEdge {
+    #[inline]
+    fn apply(c: isize, size: usize) -> Option<usize> {
+        if c < 0 || c >= size as isize {
+            None
+        } else {
+            Some(c as usize)
+        }
+    }
+}
+
+impl EdgeRule for ReflectEdge {
+    #[inline]
+    fn apply(c: isize, size: usize) -> Option<usize> {
+        let size = size as isize;
+        let reflected = if c < 0 {
+            -c
+        } else if c >= size {
+            2 * (size - 1) - c
+        } else {
+            c
+        };
+        ClampEdge::apply(reflected, size as usize)
+    }
+}
+
 pub struct Population<Individual, IndividualData> {
-    // Current and next generation of individuals
+    // Current and next generation of individuals, the next one is written over the previous generation
     inds: Vec<Individual>,
+    next_inds: Vec<Individual>,
+
+    // Buffers reused by every generation for the origins of the individuals and for the selection fitness
+    // With crowding, births are the origins of the offspring, cell_births the origins of the cells and winners the
+    // offspring that replace the individuals of the cells
+    births: Vec<Birth>,
+    cell_births: Vec<Birth>,
+    winners: Vec<Option<usize>>,
+    selection_fitness: Vec<f64>,
 
     // Population size
     pop_width: usize,
@@ -42,23 +284,152 @@ pub struct Population<Individual, IndividualData> {
     // Current generation number
     i_generation: usize,
 
+    // Number of fitness evaluations done so far
+    n_evaluations: usize,
+
+    // Seed from which the random number generators of all cells and generations are derived
+    seed: u64,
+
     // Data for individuals
     ind_data: IndividualData,
 
-    selection_fn: fn(&mut SmallRng, &[usize], &[Individual]) -> usize,
-    neighbours_fn: fn(usize, usize, usize, &mut [usize; MAX_NEIGHBOURS]) -> usize,
+    selection_strategy_type: SelectionStrategyType,
+    boundary_type: BoundaryType,
+    neighbourhood_type: NeighbourhoodType,
+    selection_fn: SelectionFn,
+    neighbours_fn: NeighboursFn,
+
+    // Evaluation of the offspring of a generation in one batch instead of count_fitness, see set_batch_evaluator
+    batch_fitness: Option<BatchFitnessFn<Individual>>,
+
+    // Per-cell age and index of the initial cell the lineage descends from
+    ages: Vec<usize>,
+    lineages: Vec<usize>,
+    next_lineages: Vec<usize>,
+
+    // Visualisation parameters
+    visualisation_mode: VisualisationMode,
+    colormap: Colormap,
+    visualisation_bounds: Option<(f64, f64)>,
+
+    // Number of pairs of individuals sampled for the mean distance
+    diversity_samples: usize,
+
+    // Niching parameters, the radius is in the units of the distance of the individuals
+    niching_type: NichingType,
+    niche_radius: f64,
+    niche_capacity: usize,
+    sharing_alpha: f64,
+
+    // Restart policy applied after stagnation_generations generations without improvement of the best fitness
+    restart_type: RestartType,
+    stagnation_generations: usize,
+    restart_fraction: f64,
+    // Number of the best individuals that are never re-randomised
+    restart_elite: usize,
+
+    // Every immigrant_interval generations the fraction of the cells gets random immigrants, 0 disables them
+    immigrant_interval: usize,
+    immigrant_fraction: f64,
+
+    // Best fitness seen so far and the generation in which it was reached
+    best_fitness_so_far: f64,
+    last_improvement: usize,
+
+    // Restarts and immigrations done so far
+    events: Vec<PopulationEvent>,
+
+    // Ids, parents and operators of the individuals, tracked only if enabled by the config
+    genealogy: Option<Genealogy>,
+
+    // Best distinct individuals ever seen
+    hall_of_fame: HallOfFame<Individual>,
 }
 
-impl<Individual: EvoIndividual<IndividualData>, IndividualData: EvoIndividualData>
-    Population<Individual, IndividualData>
+impl<Individual, IndividualData> Population<Individual, IndividualData>
 where
-    Individual: EvoIndividual<IndividualData> + Send + Sync + Clone,
+    Individual: EvoIndividual<IndividualData>,
     IndividualData: EvoIndividualData,
+{
+    // Function creates a new population with randomised individuals and counts their fitness
+    // Individual data are created from the config by IndividualData::from_config
+    pub fn new(config: &Config) -> Population<Individual, IndividualData> {
+        Self::new_with_data(config, IndividualData::from_config(config))
+    }
+}
+
+impl<Individual, IndividualData> Population<Individual, IndividualData>
+where
+    Individual: EvoIndividual<IndividualData>,
+    IndividualData: Send + Sync,
 {
     pub fn get_at(&self, x: usize, y: usize) -> &Individual {
         &self.inds[y * self.pop_width + x]
     }
 
+    // Function iterates over the individuals row by row, the individual at (x, y) is the (y * width + x)-th one
+    pub fn iter(&self) -> std::slice::Iter<'_, Individual> {
+        self.inds.iter()
+    }
+
+    pub fn par_iter(&self) -> rayon::slice::Iter<'_, Individual> {
+        self.inds.par_iter()
+    }
+
+    // Function returns the individuals of the row y from the left
+    pub fn row(&self, y: usize) -> &[Individual] {
+        &self.inds[y * self.pop_width..(y + 1) * self.pop_width]
+    }
+
+    // Function iterates over the individuals of the column x from the top
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &Individual> {
+        assert!(x < self.pop_width, "column {} is out of the population", x);
+        self.inds.iter().skip(x).step_by(self.pop_width)
+    }
+
+    // Function returns the k best individuals from the best, or all of them if k is larger than the population
+    // Individuals with the same fitness are in the order of their cells
+    pub fn top_k(&self, k: usize) -> Vec<&Individual> {
+        let k = k.min(self.inds.len());
+        if k == 0 {
+            return Vec::new();
+        }
+
+        let mut order: Vec<usize> = (0..self.inds.len()).collect();
+        let cmp = |&a: &usize, &b: &usize| {
+            cmp_fitness(self.inds[b].get_fitness(), self.inds[a].get_fitness()).then(a.cmp(&b))
+        };
+        order.select_nth_unstable_by(k - 1, cmp);
+        order.truncate(k);
+        order.sort_unstable_by(cmp);
+
+        order.iter().map(|&i| &self.inds[i]).collect()
+    }
+
+    // Function returns the worst individual in the current generation
+    pub fn worst(&self) -> &Individual {
+        self.inds
+            .iter()
+            .min_by(|a, b| cmp_fitness(a.get_fitness(), b.get_fitness()))
+            .expect("population must not be empty")
+    }
+
+    // Function returns the rank of the individual at (x, y), the number of individuals with a better fitness
+    // The best individual has rank 0, individuals with the same fitness have the same rank
+    pub fn rank_of(&self, x: usize, y: usize) -> usize {
+        let fitness = self.get_at(x, y).get_fitness();
+        self.inds
+            .iter()
+            .filter(|ind| cmp_fitness(ind.get_fitness(), fitness).is_gt())
+            .count()
+    }
+
+    // Function returns the coordinates (x, y) of the best individual returned by get_best
+    pub fn best_position(&self) -> (usize, usize) {
+        let best = self._best_index();
+        (best % self.pop_width, best / self.pop_width)
+    }
+
     pub fn get_width(&self) -> usize {
         self.pop_width
     }
@@ -72,8 +443,182 @@ where
         self.i_generation
     }
 
+    // Function returns the number of fitness evaluations done so far
+    pub fn get_evaluations(&self) -> usize {
+        self.n_evaluations
+    }
+
+    // Function returns the seed of the population, the same seed and config give the same run
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn get_mut_prob(&self) -> f32 {
+        self.mut_prob
+    }
+
+    pub fn get_mut_amount(&self) -> f32 {
+        self.mut_amount
+    }
+
+    pub fn get_crossover_prob(&self) -> f32 {
+        self.crossover_prob
+    }
+
+    pub fn get_selection_strategy(&self) -> SelectionStrategyType {
+        self.selection_strategy_type
+    }
+
+    pub fn get_boundary(&self) -> BoundaryType {
+        self.boundary_type
+    }
+
+    pub fn get_neighbourhood(&self) -> NeighbourhoodType {
+        self.neighbourhood_type
+    }
+
+    pub fn get_niching(&self) -> NichingType {
+        self.niching_type
+    }
+
+    pub fn get_restart_type(&self) -> RestartType {
+        self.restart_type
+    }
+
+    // Function returns the number of generations since the last improvement of the best fitness
+    pub fn get_stagnation(&self) -> usize {
+        self.i_generation - self.last_improvement
+    }
+
+    // Function returns the restarts and immigrations done so far, in the order they happened
+    pub fn get_events(&self) -> &[PopulationEvent] {
+        &self.events
+    }
+
+    // Function returns the best distinct individuals ever seen, updated after every generation
+    pub fn get_hall_of_fame(&self) -> &HallOfFame<Individual> {
+        &self.hall_of_fame
+    }
+
+    // Function adds the entries of the hall of fame written by write_json, e.g. by an interrupted run
+    pub fn read_hall_of_fame(&mut self, path: &str) -> Result<(), String>
+    where
+        Individual: RemoteIndividual<IndividualData>,
+    {
+        self.hall_of_fame.read_json(path, &self.ind_data)
+    }
+
+    // Function returns the genealogy of the population, None if it is not enabled by the "genealogy" config value
+    pub fn get_genealogy(&self) -> Option<&Genealogy> {
+        self.genealogy.as_ref()
+    }
+
+    // Setters of the parameters take effect from the next generation
+
+    pub fn set_mut_prob(&mut self, mut_prob: f32) {
+        self.mut_prob = mut_prob;
+    }
+
+    pub fn set_mut_amount(&mut self, mut_amount: f32) {
+        self.mut_amount = mut_amount;
+    }
+
+    pub fn set_crossover_prob(&mut self, crossover_prob: f32) {
+        self.crossover_prob = crossover_prob;
+    }
+
+    pub fn set_selection_strategy(&mut self, selection_strategy_type: SelectionStrategyType) {
+        self.selection_strategy_type = selection_strategy_type;
+        self.selection_fn = Self::_selection_fn(selection_strategy_type);
+    }
+
+    pub fn set_boundary(&mut self, boundary_type: BoundaryType) {
+        self.boundary_type = boundary_type;
+        self.neighbours_fn = Self::_neighbours_fn(self.neighbourhood_type, boundary_type);
+    }
+
+    pub fn set_neighbourhood(&mut self, neighbourhood_type: NeighbourhoodType) {
+        self.neighbourhood_type = neighbourhood_type;
+        self.neighbours_fn = Self::_neighbours_fn(neighbourhood_type, self.boundary_type);
+    }
+
+    // Function makes next_gen send the genomes of all offspring of a generation to the evaluator in one call instead
+    // of calling count_fitness, so remote workers get the whole generation at once
+    // Genomes that can't be evaluated get NaN fitness. Initial individuals, restarts and immigrants still use
+    // count_fitness
+    pub fn set_batch_evaluator<Evaluator: BatchEvaluator>(&mut self, evaluator: Arc<Evaluator>)
+    where
+        Individual: RemoteIndividual<IndividualData> + 'static,
+    {
+        self.batch_fitness = Some(Arc::new(move |inds: &mut [Individual]| {
+            let genomes = inds.par_iter().map(|ind| ind.genome_to_json()).collect();
+            let results = evaluator.evaluate_batch(genomes);
+            for (ind, result) in inds.iter_mut().zip(results) {
+                ind.set_fitness(result.unwrap_or(f64::NAN));
+            }
+        }));
+    }
+
+    // Function makes next_gen count the fitness of each offspring by count_fitness again
+    pub fn remove_batch_evaluator(&mut self) {
+        self.batch_fitness = None;
+    }
+
+    // Function changes the size of the grid between generations, the cells of the new grid get copies of the
+    // individuals of the old one with their fitness, age, lineage and genealogy id, so no evaluations are needed
+    pub fn resize(
+        &mut self,
+        width: usize,
+        height: usize,
+        resize_type: ResizeType,
+    ) -> Result<(), String> {
+        if width == 0 || height == 0 {
+            return Err(format!(
+                "Population size {}x{} must not be empty",
+                width, height
+            ));
+        }
+
+        let (old_width, old_height) = (self.pop_width, self.pop_height);
+        let sources: Vec<usize> = (0..width * height)
+            .map(|i| {
+                let (x, y) = (i % width, i / width);
+                let (source_x, source_y) = match resize_type {
+                    ResizeType::Tile => (x % old_width, y % old_height),
+                    ResizeType::Interpolate => (x * old_width / width, y * old_height / height),
+                };
+                source_y * old_width + source_x
+            })
+            .collect();
+
+        let inds: Vec<Individual> = sources.iter().map(|&i| self.inds[i].clone()).collect();
+        self.inds = inds;
+        self.ages = sources.iter().map(|&i| self.ages[i]).collect();
+        self.lineages = sources.iter().map(|&i| self.lineages[i]).collect();
+        if let Some(genealogy) = &mut self.genealogy {
+            genealogy.remap_cells(&sources);
+        }
+
+        // Buffers of the next generation are created again by the next generation
+        self.next_inds = Vec::new();
+        self.next_lineages.clear();
+
+        self.pop_width = width;
+        self.pop_height = height;
+        self.events.push(PopulationEvent {
+            generation: self.i_generation,
+            event_type: PopulationEventType::Resize,
+            n_cells: width * height,
+        });
+        Ok(())
+    }
+
     // Function creates a new population with randomised individuals and counts their fitness
-    pub fn new(config: &Config) -> Population<Individual, IndividualData> {
+    // Individual data are given by the caller, which allows them to contain things like closures
+    pub fn new_with_data(
+        config: &Config,
+        ind_data: IndividualData,
+    ) -> Population<Individual, IndividualData> {
         let pop_width = config
             .may_get_uint("pop_width")
             .unwrap()
@@ -83,7 +628,12 @@ where
             .unwrap()
             .unwrap_or(DEFAULT_POP_HEIGHT);
 
-        let ind_data = IndividualData::from_config(config);
+        // Use random seed if it is not set in the config
+        let seed = config
+            .may_get_uint("seed")
+            .unwrap()
+            .unwrap_or_else(|| SmallRng::from_entropy().gen());
+
         let size = pop_width * pop_height;
         let mut inds: Vec<Individual> = Vec::with_capacity(size);
 
@@ -91,9 +641,7 @@ where
         inds.par_extend(
             (0..size)
                 .into_par_iter()
-                .map_init(SmallRng::from_entropy, |rng, _| {
-                    Self::_new_random_individual(rng, &ind_data)
-                }),
+                .map(|i| Self::_new_random_individual(&mut Self::_cell_rng(seed, 0, i), &ind_data)),
         );
 
         let selection_strategy_type = config
@@ -101,17 +649,40 @@ where
             .unwrap()
             .unwrap_or(DEFAULT_SELECTION_STRATEGY_TYPE);
 
-        let selection_fn: fn(&mut SmallRng, &[usize], &[Individual]) -> usize =
-            match selection_strategy_type {
-                SelectionStrategyType::Roulette => Self::_roulette_selection,
-                SelectionStrategyType::Tournament => Self::_single_tournament,
-            };
+        let boundary_type = config
+            .may_get_enum("boundary")
+            .unwrap()
+            .unwrap_or(DEFAULT_BOUNDARY_TYPE);
+
+        let neighbourhood_type = config
+            .may_get_enum("neighbourhood")
+            .unwrap()
+            .unwrap_or(DEFAULT_NEIGHBOURHOOD_TYPE);
+
+        let visualisation_bounds = Self::_visualisation_bounds(config).unwrap();
 
-        let neighbours_fn: fn(usize, usize, usize, &mut [usize; MAX_NEIGHBOURS]) -> usize =
-            { Self::_l5_selection };
+        let best_fitness_so_far = inds
+            .iter()
+            .map(|ind| ind.get_fitness())
+            .fold(f64::NEG_INFINITY, f64::max);
+
+        let mut hall_of_fame = HallOfFame::from_config(config);
+        hall_of_fame.update(&inds, &ind_data, 0);
+
+        let genealogy = if config.may_get_bool("genealogy").unwrap().unwrap_or(false) {
+            let fitnesses: Vec<f64> = inds.iter().map(|ind| ind.get_fitness()).collect();
+            Some(Genealogy::new(&fitnesses))
+        } else {
+            None
+        };
 
         Population {
             inds,
+            next_inds: Vec::new(),
+            births: Vec::with_capacity(size),
+            cell_births: Vec::new(),
+            winners: Vec::new(),
+            selection_fitness: Vec::with_capacity(size),
             pop_width,
             pop_height,
             mut_prob: config
@@ -127,78 +698,336 @@ where
                 .unwrap()
                 .unwrap_or(DEFAULT_CROSSOVER_PROB),
             i_generation: 0,
+            n_evaluations: size,
+            seed,
             ind_data,
-            selection_fn,
-            neighbours_fn,
+            selection_strategy_type,
+            boundary_type,
+            neighbourhood_type,
+            selection_fn: Self::_selection_fn(selection_strategy_type),
+            neighbours_fn: Self::_neighbours_fn(neighbourhood_type, boundary_type),
+            batch_fitness: None,
+            ages: vec![0; size],
+            lineages: (0..size).collect(),
+            next_lineages: Vec::with_capacity(size),
+            visualisation_mode: VisualisationMode::from_config(config)
+                .unwrap()
+                .unwrap_or(DEFAULT_VISUALISATION_MODE),
+            colormap: config
+                .may_get_enum("colormap")
+                .unwrap()
+                .unwrap_or(DEFAULT_COLORMAP),
+            visualisation_bounds,
+            diversity_samples: config
+                .may_get_uint("diversity_samples")
+                .unwrap()
+                .unwrap_or(DEFAULT_DIVERSITY_SAMPLES),
+            niching_type: config
+                .may_get_enum("niching")
+                .unwrap()
+                .unwrap_or(DEFAULT_NICHING_TYPE),
+            niche_radius: config
+                .may_get_float("niche_radius")
+                .unwrap()
+                .unwrap_or(DEFAULT_NICHE_RADIUS),
+            niche_capacity: config
+                .may_get_uint("niche_capacity")
+                .unwrap()
+                .unwrap_or(DEFAULT_NICHE_CAPACITY),
+            sharing_alpha: config
+                .may_get_float("sharing_alpha")
+                .unwrap()
+                .unwrap_or(DEFAULT_SHARING_ALPHA),
+            restart_type: config
+                .may_get_enum("restart")
+                .unwrap()
+                .unwrap_or(DEFAULT_RESTART_TYPE),
+            stagnation_generations: config
+                .may_get_uint("stagnation_generations")
+                .unwrap()
+                .unwrap_or(DEFAULT_STAGNATION_GENERATIONS),
+            restart_fraction: config
+                .may_get_float("restart_fraction")
+                .unwrap()
+                .unwrap_or(DEFAULT_RESTART_FRACTION),
+            restart_elite: config
+                .may_get_uint("restart_elite")
+                .unwrap()
+                .unwrap_or(DEFAULT_RESTART_ELITE),
+            immigrant_interval: config
+                .may_get_uint("immigrant_interval")
+                .unwrap()
+                .unwrap_or(DEFAULT_IMMIGRANT_INTERVAL),
+            immigrant_fraction: config
+                .may_get_float("immigrant_fraction")
+                .unwrap()
+                .unwrap_or(DEFAULT_IMMIGRANT_FRACTION),
+            best_fitness_so_far,
+            last_improvement: 0,
+            events: Vec::new(),
+            genealogy,
+            hall_of_fame,
         }
     }
 
     // Function moves the population to the next generation
     // It does selection, crossover/mutation and counts fitness for each individual
+    // Offspring are written over the individuals of the generation before the current one, so no buffers are allocated
     pub fn next_gen(&mut self) {
         let pop_size = self.inds.len();
 
-        // Create a new vector for the next generation
-        let mut next_gen_inds: Vec<Individual> = Vec::with_capacity(pop_size);
+        // Take the buffers of the next generation, the origins of its individuals and the selection fitness
+        let mut next_gen_inds = std::mem::take(&mut self.next_inds);
+        let mut births = std::mem::take(&mut self.births);
+        let mut selection_fitness = std::mem::take(&mut self.selection_fitness);
+
+        // Buffer of the next generation is created by the first generation, later it only gets overwritten
+        if next_gen_inds.len() != pop_size {
+            next_gen_inds.clone_from(&self.inds);
+        }
+
+        // Fitness used for selection, adjusted by sharing or clearing
+        self._count_selection_fitness(&mut selection_fitness);
 
         // Do selection and crossover/mutation in parallel for each individual
-        next_gen_inds.par_extend((0..pop_size).into_par_iter().map_init(
-            || (SmallRng::from_entropy(), [0usize; MAX_NEIGHBOURS]),
-            |(rng, neigh_buf), i| {
+        let stream = self.i_generation as u64 + 1;
+        births.clear();
+        births.par_extend(next_gen_inds.par_iter_mut().enumerate().map_init(
+            || [0usize; MAX_NEIGHBOURS],
+            |neigh_buf, (i, res)| {
+                let rng = &mut Self::_cell_rng(self.seed, stream, i);
+
                 // Select 5 individuals
                 let n_neigh = (self.neighbours_fn)(i, self.pop_width, self.pop_height, neigh_buf);
                 let indices = &neigh_buf[..n_neigh];
 
                 // Decide whether to do crossover or mutation
-                let mut res = if rng.gen_range(0.0..1.0) < self.crossover_prob {
+                let (parent, other_parent, operator) = if rng.gen_range(0.0..1.0)
+                    < self.crossover_prob
+                {
                     // Do crossover
 
                     // Select two individuals
-                    let (first_ind, second_ind) = Self::_dual_tournament(indices, &self.inds);
-
-                    self.inds[first_ind].crossover(&self.inds[second_ind], &self.ind_data, rng)
+                    let (first_ind, second_ind) =
+                        Self::_dual_tournament(indices, &selection_fitness);
+
+                    self.inds[first_ind].crossover_into(
+                        &self.inds[second_ind],
+                        &self.ind_data,
+                        rng,
+                        res,
+                    );
+                    (first_ind, second_ind, Operator::Crossover)
                 } else {
                     // Do mutation
 
                     // Select one individual based on the selection type
-                    let selected_ind_index = (self.selection_fn)(rng, indices, &self.inds);
+                    let selected_ind_index = (self.selection_fn)(rng, indices, &selection_fitness);
 
-                    let mut res = self.inds[selected_ind_index].clone();
+                    res.copy_from(&self.inds[selected_ind_index]);
                     res.mutate(&self.ind_data, rng, self.mut_prob, self.mut_amount);
-                    res
+                    (selected_ind_index, selected_ind_index, Operator::Mutation)
+                };
+
+                // Count fitness of the new individual and return its origin
+                // With the batch evaluator, the fitness of the birth is set after the whole generation is evaluated
+                if self.batch_fitness.is_none() {
+                    res.count_fitness(&self.ind_data);
+                }
+                let birth = Birth {
+                    parent,
+                    other_parent,
+                    operator,
+                    improved: self._is_improvement(res.get_fitness(), parent, other_parent),
+                    is_offspring: true,
+                    fitness: res.get_fitness(),
                 };
 
-                // Count fitness of the new individual and return it
-                res.count_fitness(&self.ind_data);
-                res
+                if self.niching_type == NichingType::Crowding {
+                    return self._most_similar_parent_first(res, birth);
+                }
+                birth
             },
         ));
 
-        // Swap the current generation with the next generation and increment the generation counter
-        std::mem::swap(&mut self.inds, &mut next_gen_inds);
+        if let Some(batch_fitness) = &self.batch_fitness {
+            batch_fitness(&mut next_gen_inds);
+            for (birth, ind) in births.iter_mut().zip(next_gen_inds.iter()) {
+                birth.fitness = ind.get_fitness();
+                birth.improved =
+                    self._is_improvement(birth.fitness, birth.parent, birth.other_parent);
+            }
+        }
+
+        let mut cell_births = std::mem::take(&mut self.cell_births);
+        if self.niching_type == NichingType::Crowding {
+            // Winning offspring are copied into the cells of their parents, the other cells keep their individuals
+            self._crowding_replacement(&next_gen_inds, &mut births, &mut cell_births);
+        } else {
+            // Swap the current generation with the next generation
+            // The previous generation becomes the buffer of the next one
+            std::mem::swap(&mut self.inds, &mut next_gen_inds);
+            cell_births.clone_from(&births);
+        }
+
+        // Increment the generation counter
+        self.next_inds = next_gen_inds;
+        self.selection_fitness = selection_fitness;
+        self._update_lineage(&cell_births);
         self.i_generation += 1;
+        self.n_evaluations += pop_size;
+
+        if let Some(genealogy) = &mut self.genealogy {
+            genealogy.add_generation(self.i_generation, &births, &cell_births);
+        }
+        self.births = births;
+        self.cell_births = cell_births;
+        self.hall_of_fame
+            .update(&self.inds, &self.ind_data, self.i_generation);
+
+        self._apply_policies();
     }
 
-    // Function returns the best individual in the current generation
+    // Function returns the best individual in the current generation, individuals with NaN fitness are the worst
     pub fn get_best(&self) -> &Individual {
-        self.inds
+        &self.inds[self._best_index()]
+    }
+
+    // Function returns the genealogy id of the best individual, None if the genealogy is not enabled
+    pub fn get_best_id(&self) -> Option<u64> {
+        self.genealogy
+            .as_ref()
+            .map(|genealogy| genealogy.get_id(self._best_index()))
+    }
+
+    // Function returns summary statistics of the fitness of the current generation
+    pub fn get_stats(&self) -> PopulationStats {
+        let mut fitnesses: Vec<f64> = self
+            .inds
             .iter()
-            .max_by(|a, b| {
-                a.get_fitness()
-                    .partial_cmp(&b.get_fitness())
-                    .expect("fitness must not be NaN")
-            })
-            .expect("population must not be empty")
+            .map(|ind| ind.get_fitness())
+            .filter(|&fitness| is_valid_fitness(fitness))
+            .collect();
+        fitnesses.sort_by(|a, b| a.total_cmp(b));
+
+        let n_invalid = self.inds.len() - fitnesses.len();
+        if fitnesses.is_empty() {
+            return PopulationStats {
+                best_fitness: f64::NAN,
+                worst_fitness: f64::NAN,
+                mean_fitness: f64::NAN,
+                median_fitness: f64::NAN,
+                std_fitness: f64::NAN,
+                n_invalid,
+            };
+        }
+
+        let len = fitnesses.len() as f64;
+        let mean = fitnesses.iter().sum::<f64>() / len;
+        let variance = fitnesses
+            .iter()
+            .map(|f| (f - mean) * (f - mean))
+            .sum::<f64>()
+            / len;
+
+        let mid = fitnesses.len() / 2;
+        let median = if fitnesses.len().is_multiple_of(2) {
+            (fitnesses[mid - 1] + fitnesses[mid]) / 2.0
+        } else {
+            fitnesses[mid]
+        };
+
+        PopulationStats {
+            best_fitness: fitnesses[fitnesses.len() - 1],
+            worst_fitness: fitnesses[0],
+            mean_fitness: mean,
+            median_fitness: median,
+            std_fitness: variance.sqrt(),
+            n_invalid,
+        }
+    }
+
+    // Function returns the diversity of the genomes, computed by distance and genome_hash of the individuals
+    // All pairs are compared if there are at most diversity_samples of them, otherwise random pairs are sampled
+    pub fn get_diversity(&self) -> DiversityStats {
+        let hashes: Vec<u64> = self
+            .inds
+            .par_iter()
+            .map(|ind| ind.genome_hash(&self.ind_data))
+            .collect();
+
+        let n_distinct = hashes.iter().collect::<HashSet<_>>().len();
+
+        DiversityStats {
+            mean_distance: self._mean_distance(),
+            neighbourhood_entropy: self._neighbourhood_entropy(&hashes),
+            n_distinct,
+        }
     }
 
     // Function creates a visualization of the current generation in the form of an PNG image
-    // It maps the fitness (L) and visual attributes (A, B) of each individual
+    // The mapping of individuals to colours is determined by the visualisation mode
     pub fn visualise(&self) -> RgbImage {
-        let mut lab_data = self._prepare_pop_lab_data();
+        self.visualise_with(self.visualisation_mode)
+    }
+
+    // Function creates a visualization of the current generation using the given visualisation mode
+    pub fn visualise_with(&self, mode: VisualisationMode) -> RgbImage {
+        match mode {
+            VisualisationMode::Lab => {
+                let mut lab_data = self._prepare_pop_lab_data();
 
-        lab_data = Self::_normalize_lab_data_rank_based(lab_data);
+                lab_data = Self::_normalize_lab_data_rank_based(lab_data);
 
-        self._write_lab_data_to_image(&lab_data)
+                self._write_lab_data_to_image(&lab_data)
+            }
+            VisualisationMode::Fitness => {
+                let values: Vec<f64> = self.inds.iter().map(|ind| ind.get_fitness()).collect();
+                self._write_values_to_image(&Self::_normalize_values_rank_based(&values))
+            }
+            VisualisationMode::Absolute => {
+                let values: Vec<f64> = self.inds.iter().map(|ind| ind.get_fitness()).collect();
+                let bounds = self
+                    .visualisation_bounds
+                    .unwrap_or_else(|| Self::_finite_bounds(&values));
+                self._write_values_to_image(&Self::_normalize_values_linear(&values, bounds))
+            }
+            VisualisationMode::Age => {
+                let values: Vec<f64> = self.ages.iter().map(|&age| age as f64).collect();
+                let bounds = Self::_finite_bounds(&values);
+                self._write_values_to_image(&Self::_normalize_values_linear(
+                    &values,
+                    (0.0, bounds.1),
+                ))
+            }
+            VisualisationMode::Lineage => self._write_lineages_to_image(),
+            VisualisationMode::Diversity => {
+                let values = self._local_diversity();
+                self._write_values_to_image(&Self::_normalize_values_rank_based(&values))
+            }
+        }
+    }
+
+    pub fn get_visualisation_mode(&self) -> VisualisationMode {
+        self.visualisation_mode
+    }
+
+    pub fn set_visualisation_mode(&mut self, mode: VisualisationMode) {
+        self.visualisation_mode = mode;
+    }
+
+    pub fn get_colormap(&self) -> Colormap {
+        self.colormap
+    }
+
+    pub fn set_colormap(&mut self, colormap: Colormap) {
+        self.colormap = colormap;
+    }
+
+    // Function sets fixed fitness bounds for the absolute visualisation mode
+    // When no bounds are set, the bounds of the current generation are used
+    pub fn set_visualisation_bounds(&mut self, bounds: Option<(f64, f64)>) {
+        self.visualisation_bounds = bounds;
     }
 
     // Function returns the data for individuals
@@ -236,58 +1065,144 @@ where
         5
     }
 
-    fn _normalize_component(
-        data: &mut [IndexedLabData],
-        mut get_component: impl FnMut(&LabData) -> f64,
-        mut set_component: impl FnMut(&mut LabData, f64),
-        min_val: f64,
-        max_val: f64,
-    ) {
-        let len = data.len();
-        let eps = 1e-9;
+    // Function returns the indices of up to 5 neighbours of i in a + shape
+    // Edges of the grid are handled by the X and Y edge rules, so edge cells can have fewer neighbours
+    #[inline]
+    fn _l5_selection_bounded<EdgeX: EdgeRule, EdgeY: EdgeRule>(
+        i: usize,
+        w: usize,
+        h: usize,
+        buf: &mut [usize; MAX_NEIGHBOURS],
+    ) -> usize {
+        let x = (i % w) as isize;
+        let y = (i / w) as isize;
 
-        data.sort_by(|a, b| {
-            get_component(&a.data)
-                .partial_cmp(&get_component(&b.data))
-                .unwrap()
-        });
-        let mut last_val = get_component(&data.last().unwrap().data);
-        let mut last_val_normalised = 0.0;
+        buf[0] = i; // center
+        let mut n = 1;
 
-        for (i, value) in data.iter_mut().enumerate() {
-            let current_val = get_component(&value.data);
+        // left, right, up, down
+        for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+            if let (Some(nx), Some(ny)) = (EdgeX::apply(x + dx, w), EdgeY::apply(y + dy, h)) {
+                buf[n] = ny * w + nx;
+                n += 1;
+            }
+        }
 
-            if (current_val - last_val).abs() < eps {
-                set_component(&mut value.data, last_val_normalised);
-            } else {
-                last_val = current_val;
-                let normalised_val = ((i as f64) * (max_val - min_val)) / len as f64 + min_val;
-                set_component(&mut value.data, normalised_val);
-                last_val_normalised = normalised_val;
+        n
+    }
+
+    // Function returns the indices of up to 9 neighbours of i in a 3x3 square
+    // Edges of the grid are handled by the X and Y edge rules, so edge cells can have fewer neighbours
+    #[inline]
+    fn _c9_selection_bounded<EdgeX: EdgeRule, EdgeY: EdgeRule>(
+        i: usize,
+        w: usize,
+        h: usize,
+        buf: &mut [usize; MAX_NEIGHBOURS],
+    ) -> usize {
+        let x = (i % w) as isize;
+        let y = (i / w) as isize;
+
+        buf[0] = i; // center
+        let mut n = 1;
+
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if dx == 0 && dy == 0 {
+                    continue;
+                }
+                if let (Some(nx), Some(ny)) = (EdgeX::apply(x + dx, w), EdgeY::apply(y + dy, h)) {
+                    buf[n] = ny * w + nx;
+                    n += 1;
+                }
             }
         }
+
+        n
     }
 
     // Private methods
 
+    // Function reads the fixed bounds of the absolute visualisation, both of them must be set or none
+    fn _visualisation_bounds(config: &Config) -> Result<Option<(f64, f64)>, String> {
+        match (
+            config.may_get_float("visualisation_min")?,
+            config.may_get_float("visualisation_max")?,
+        ) {
+            (Some(min), Some(max)) => Ok(Some((min, max))),
+            (None, None) => Ok(None),
+            _ => Err(
+                "Both visualisation_min and visualisation_max must be set for fixed visualisation bounds"
+                    .to_string(),
+            ),
+        }
+    }
+
+    fn _selection_fn(selection_strategy_type: SelectionStrategyType) -> SelectionFn {
+        match selection_strategy_type {
+            SelectionStrategyType::Roulette => Self::_roulette_selection,
+            SelectionStrategyType::Tournament => Self::_single_tournament,
+        }
+    }
+
+    fn _neighbours_fn(
+        neighbourhood_type: NeighbourhoodType,
+        boundary_type: BoundaryType,
+    ) -> NeighboursFn {
+        match (neighbourhood_type, boundary_type) {
+            (NeighbourhoodType::L5, BoundaryType::Torus) => Self::_l5_selection,
+            (NeighbourhoodType::L5, BoundaryType::Clamped) => {
+                Self::_l5_selection_bounded::<ClampEdge, ClampEdge>
+            }
+            (NeighbourhoodType::L5, BoundaryType::Reflecting) => {
+                Self::_l5_selection_bounded::<ReflectEdge, ReflectEdge>
+            }
+            (NeighbourhoodType::L5, BoundaryType::Cylinder) => {
+                Self::_l5_selection_bounded::<WrapEdge, ClampEdge>
+            }
+            (NeighbourhoodType::C9, BoundaryType::Torus) => {
+                Self::_c9_selection_bounded::<WrapEdge, WrapEdge>
+            }
+            (NeighbourhoodType::C9, BoundaryType::Clamped) => {
+                Self::_c9_selection_bounded::<ClampEdge, ClampEdge>
+            }
+            (NeighbourhoodType::C9, BoundaryType::Reflecting) => {
+                Self::_c9_selection_bounded::<ReflectEdge, ReflectEdge>
+            }
+            (NeighbourhoodType::C9, BoundaryType::Cylinder) => {
+                Self::_c9_selection_bounded::<WrapEdge, ClampEdge>
+            }
+        }
+    }
+
+    // Function returns the index of the best individual, the last one if there are more of them
+    fn _best_index(&self) -> usize {
+        self.inds
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| cmp_fitness(a.get_fitness(), b.get_fitness()))
+            .map(|(i, _)| i)
+            .expect("population must not be empty")
+    }
+
     // Function normalizes the L, A and B values of the population using the rank-based method
     // This method doesn't preserve the order of the values
     fn _normalize_lab_data_rank_based(mut lab_data: Vec<IndexedLabData>) -> Vec<IndexedLabData> {
-        Self::_normalize_component(
+        normalize_component(
             &mut lab_data,
             |lab_data| lab_data.l,
             |lab_data, val| lab_data.l = val,
             10.0,
             90.0,
         );
-        Self::_normalize_component(
+        normalize_component(
             &mut lab_data,
             |lab_data| lab_data.a,
             |lab_data, val| lab_data.a = val,
             -128.0,
             128.0,
         );
-        Self::_normalize_component(
+        normalize_component(
             &mut lab_data,
             |lab_data| lab_data.b,
             |lab_data, val| lab_data.b = val,
@@ -317,6 +1232,15 @@ where
         img
     }
 
+    // Function returns the random number generator of the cell i in the given stream
+    // Every generation uses its own stream, so the run doesn't depend on the order of parallel execution
+    fn _cell_rng(seed: u64, stream: u64, i: usize) -> SmallRng {
+        let cell_seed = seed
+            ^ stream.wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (i as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+        SmallRng::seed_from_u64(cell_seed)
+    }
+
     // Function creates a new individual with randomised values and counts its fitness
     fn _new_random_individual(rng: &mut SmallRng, ind_data: &IndividualData) -> Individual {
         let mut curr_gen_ind = Individual::new_randomised(ind_data, rng);
@@ -327,11 +1251,11 @@ where
     // Private methods
 
     // Function returns the index of the best individual in the tournament
-    fn _single_tournament(_rng: &mut SmallRng, indices: &[usize], inds: &[Individual]) -> usize {
+    fn _single_tournament(_rng: &mut SmallRng, indices: &[usize], fitness: &[f64]) -> usize {
         let mut best_i = indices[0];
 
         for &index in indices.iter().skip(1) {
-            if inds[index].get_fitness() > inds[best_i].get_fitness() {
+            if cmp_fitness(fitness[index], fitness[best_i]).is_gt() {
                 best_i = index;
             }
         }
@@ -339,38 +1263,58 @@ where
         best_i
     }
 
-    fn _roulette_selection(rng: &mut SmallRng, indices: &[usize], inds: &[Individual]) -> usize {
-        // Get min fitness
-        let mut min_fitness = inds[indices[0]].get_fitness();
+    // Function selects an individual with the probability proportional to its fitness above the worst one
+    // Individuals with NaN or -inf fitness are never selected by the wheel, individuals with +inf fitness are selected
+    // uniformly if there are any, and the best individual is returned if all the weights are zero
+    fn _roulette_selection(rng: &mut SmallRng, indices: &[usize], fitness: &[f64]) -> usize {
+        let n_infinite = indices
+            .iter()
+            .filter(|&&index| fitness[index] == f64::INFINITY)
+            .count();
+        if n_infinite > 0 {
+            let chosen = rng.gen_range(0..n_infinite);
+            return *indices
+                .iter()
+                .filter(|&&index| fitness[index] == f64::INFINITY)
+                .nth(chosen)
+                .unwrap();
+        }
+
+        // Get min fitness of the valid individuals
+        let mut min_fitness = f64::INFINITY;
         for &index in indices.iter() {
-            let fitness = inds[index].get_fitness();
-            if fitness < min_fitness {
-                min_fitness = fitness;
+            if is_valid_fitness(fitness[index]) && fitness[index] < min_fitness {
+                min_fitness = fitness[index];
             }
         }
+        let weight = |index: usize| {
+            if is_valid_fitness(fitness[index]) {
+                // subtract the min fitness to avoid negative values
+                fitness[index] - min_fitness
+            } else {
+                0.0
+            }
+        };
 
         // Calculate the sum of fitnesses
         let mut fitness_sum = 0.0;
         for &index in indices.iter() {
-            // subtract the min fitness to avoid negative values
-            fitness_sum += inds[index].get_fitness() - min_fitness;
+            fitness_sum += weight(index);
         }
-
-        // Calculate the probabilities of each individual
-        let mut probabilities = Vec::with_capacity(indices.len());
-        for &index in indices.iter() {
-            // subtract the min fitness to avoid negative values
-            let prob = (inds[index].get_fitness() - min_fitness) / fitness_sum;
-            probabilities.push(prob);
+        if !(fitness_sum > 0.0 && fitness_sum.is_finite()) {
+            return *indices
+                .iter()
+                .max_by(|&&a, &&b| cmp_fitness(fitness[a], fitness[b]))
+                .unwrap();
         }
 
-        // Select an individual based on the probabilities
-        let rand_val = rng.gen_range(0.0..1.0);
+        // Select an individual by the running sum of the weights
+        let rand_val = rng.gen_range(0.0..1.0) * fitness_sum;
         let mut sum = 0.0;
-        for i in 0..indices.len() {
-            sum += probabilities[i];
+        for &index in indices.iter() {
+            sum += weight(index);
             if sum > rand_val {
-                return indices[i];
+                return index;
             }
         }
 
@@ -379,9 +1323,9 @@ where
     }
 
     // Function selects two individuals using roulette selection
-    fn _dual_rulette(rng: &mut SmallRng, indices: &[usize], inds: &[Individual]) -> (usize, usize) {
+    fn _dual_rulette(rng: &mut SmallRng, indices: &[usize], fitness: &[f64]) -> (usize, usize) {
         // Select the first individual
-        let first = Self::_roulette_selection(rng, indices, inds);
+        let first = Self::_roulette_selection(rng, indices, fitness);
 
         // Remove the first index from the indices vector to avoid selecting the same individual twice
         let mut indices2 = Vec::with_capacity(indices.len() - 1);
@@ -392,21 +1336,22 @@ where
         }
 
         // Select the second individual
-        let second = Self::_roulette_selection(rng, &indices2, inds);
+        let second = Self::_roulette_selection(rng, &indices2, fitness);
 
         (first, second)
     }
 
     // Function returns the indices of the two best individuals in the tournament
-    fn _dual_tournament(indices: &[usize], inds: &[Individual]) -> (usize, usize) {
+    // If there is only one index, it is returned as both parents
+    fn _dual_tournament(indices: &[usize], fitness: &[f64]) -> (usize, usize) {
         let mut best_i = indices[0];
-        let mut second_best_i = indices[1];
+        let mut second_best_i = *indices.get(1).unwrap_or(&best_i);
 
         for &index in indices.iter().skip(1) {
-            if inds[index].get_fitness() > inds[best_i].get_fitness() {
+            if cmp_fitness(fitness[index], fitness[best_i]).is_gt() {
                 second_best_i = best_i;
                 best_i = index;
-            } else if inds[index].get_fitness() > inds[second_best_i].get_fitness() {
+            } else if cmp_fitness(fitness[index], fitness[second_best_i]).is_gt() {
                 second_best_i = index;
             }
         }
@@ -414,9 +1359,436 @@ where
         (best_i, second_best_i)
     }
 
-    // Function gets the L, A and B values of the current generation
-    fn _prepare_pop_lab_data(&self) -> Vec<IndexedLabData> {
-        let len = self.inds.len();
+    // Function writes the fitness of the individuals used for selection into the buffer
+    // Sharing and clearing adjust the fitness by the neighbours closer than niche_radius, since the selection only
+    // compares the individuals of a neighbourhood
+    fn _count_selection_fitness(&self, selection_fitness: &mut Vec<f64>) {
+        selection_fitness.clear();
+        if self.niching_type != NichingType::Sharing && self.niching_type != NichingType::Clearing {
+            selection_fitness.extend(self.inds.iter().map(|ind| ind.get_fitness()));
+            return;
+        }
+
+        // Adjusted fitness is relative to the worst fitness, so negative fitness is handled as well
+        let fitness = |i: usize| self.inds[i].get_fitness();
+        // Invalid individuals keep their fitness, so they stay the worst
+        let min_fitness = (0..self.inds.len())
+            .map(fitness)
+            .filter(|&fitness| is_valid_fitness(fitness))
+            .fold(f64::INFINITY, f64::min);
+
+        selection_fitness.par_extend((0..self.inds.len()).into_par_iter().map_init(
+            || [0usize; MAX_NEIGHBOURS],
+            |neigh_buf, i| {
+                if !is_valid_fitness(fitness(i)) {
+                    return fitness(i);
+                }
+                let n_neigh = (self.neighbours_fn)(i, self.pop_width, self.pop_height, neigh_buf);
+                let neighbours = neigh_buf[..n_neigh].iter().filter(|&&j| j != i);
+
+                match self.niching_type {
+                    NichingType::Sharing => {
+                        // Individual is in its own niche with the share 1
+                        let niche_count = 1.0
+                            + neighbours
+                                .map(|&j| {
+                                    self._sharing(
+                                        self.inds[i].distance(&self.inds[j], &self.ind_data),
+                                    )
+                                })
+                                .sum::<f64>();
+                        min_fitness + (fitness(i) - min_fitness) / niche_count
+                    }
+                    _ => {
+                        // Individual is cleared if its niche has niche_capacity better individuals
+                        // Ties are broken by the index, so one of the same individuals is kept
+                        let n_better = neighbours
+                            .filter(|&&j| match cmp_fitness(fitness(j), fitness(i)) {
+                                Ordering::Greater => true,
+                                Ordering::Equal => j < i,
+                                Ordering::Less => false,
+                            })
+                            .filter(|&&j| {
+                                self.inds[i].distance(&self.inds[j], &self.ind_data)
+                                    < self.niche_radius
+                            })
+                            .count();
+                        if n_better >= self.niche_capacity {
+                            min_fitness
+                        } else {
+                            fitness(i)
+                        }
+                    }
+                }
+            },
+        ))
+    }
+
+    // Function returns the share of the individual in the distance for fitness sharing
+    fn _sharing(&self, distance: f64) -> f64 {
+        if distance < self.niche_radius {
+            1.0 - (distance / self.niche_radius).powf(self.sharing_alpha)
+        } else {
+            0.0
+        }
+    }
+
+    // Function returns the birth with the parent most similar to the offspring as the first parent
+    // Function returns true if the fitness of the offspring is better than the fitness of both parents
+    fn _is_improvement(&self, fitness: f64, parent: usize, other_parent: usize) -> bool {
+        fitness
+            > self.inds[parent]
+                .get_fitness()
+                .max(self.inds[other_parent].get_fitness())
+    }
+
+    fn _most_similar_parent_first(&self, offspring: &Individual, birth: Birth) -> Birth {
+        if birth.other_parent != birth.parent
+            && offspring.distance(&self.inds[birth.other_parent], &self.ind_data)
+                < offspring.distance(&self.inds[birth.parent], &self.ind_data)
+        {
+            return Birth {
+                parent: birth.other_parent,
+                other_parent: birth.parent,
+                ..birth
+            };
+        }
+        birth
+    }
+
+    // Function replaces the parents by the offspring that beat them, the first parent of every birth is the most
+    // similar one, so each offspring competes for the cell of that parent only
+    // Offspring wins ties with the parent, so the population can drift on plateaus. If more offspring compete for the
+    // same cell, the fittest one competes with the parent, the earlier one wins ties
+    fn _crowding_replacement(
+        &mut self,
+        offspring: &[Individual],
+        births: &mut [Birth],
+        cell_births: &mut Vec<Birth>,
+    ) {
+        let mut winners = std::mem::take(&mut self.winners);
+        winners.clear();
+        winners.resize(self.inds.len(), None);
+        for (i, birth) in births.iter_mut().enumerate() {
+            birth.is_offspring = false;
+
+            let cell = birth.parent;
+            let beats_rival = match winners[cell] {
+                Some(rival) => {
+                    cmp_fitness(offspring[i].get_fitness(), offspring[rival].get_fitness()).is_gt()
+                }
+                None => true,
+            };
+            if beats_rival
+                && cmp_fitness(offspring[i].get_fitness(), self.inds[cell].get_fitness()).is_ge()
+            {
+                winners[cell] = Some(i);
+            }
+        }
+
+        // Cells without a winner keep their individual, which counts as its own parent
+        cell_births.clear();
+        for (cell, &winner) in winners.iter().enumerate() {
+            let birth = match winner {
+                Some(i) => {
+                    births[i].is_offspring = true;
+                    self.inds[cell].copy_from(&offspring[i]);
+                    births[i]
+                }
+                None => Birth {
+                    parent: cell,
+                    other_parent: cell,
+                    operator: Operator::Mutation,
+                    improved: false,
+                    is_offspring: false,
+                    fitness: self.inds[cell].get_fitness(),
+                },
+            };
+            cell_births.push(birth);
+        }
+        self.winners = winners;
+    }
+
+    // Function updates the ages and lineages of cells from the parents of the new generation
+    fn _update_lineage(&mut self, births: &[Birth]) {
+        for (i, birth) in births.iter().enumerate() {
+            self.ages[i] = if birth.parent == i {
+                self.ages[i] + 1
+            } else {
+                0
+            };
+        }
+
+        let lineages = &self.lineages;
+        self.next_lineages.clear();
+        self.next_lineages
+            .extend(births.iter().map(|birth| lineages[birth.parent]));
+        std::mem::swap(&mut self.lineages, &mut self.next_lineages);
+    }
+
+    // Function updates the stagnation counter and does the restart or immigration if it is due
+    fn _apply_policies(&mut self) {
+        let best_fitness = self
+            .inds
+            .iter()
+            .map(|ind| ind.get_fitness())
+            .fold(f64::NEG_INFINITY, f64::max);
+        if best_fitness > self.best_fitness_so_far {
+            self.best_fitness_so_far = best_fitness;
+            self.last_improvement = self.i_generation;
+        }
+
+        let size = self.inds.len();
+        let restart = if self.get_stagnation() < self.stagnation_generations {
+            None
+        } else {
+            match self.restart_type {
+                RestartType::None => None,
+                RestartType::Partial => Some(PopulationEventType::PartialRestart),
+                RestartType::Full => Some(PopulationEventType::FullRestart),
+            }
+        };
+
+        if let Some(event_type) = restart {
+            let n_cells = match event_type {
+                PopulationEventType::FullRestart => size,
+                _ => (self.restart_fraction * size as f64).round() as usize,
+            };
+            self._randomise_cells(n_cells, event_type);
+
+            // Stagnation is counted again from the restart
+            self.last_improvement = self.i_generation;
+        } else if self.immigrant_interval > 0
+            && self.i_generation.is_multiple_of(self.immigrant_interval)
+        {
+            let n_cells = (self.immigrant_fraction * size as f64).round() as usize;
+            self._randomise_cells(n_cells, PopulationEventType::Immigrants);
+        }
+    }
+
+    // Function replaces up to n_cells random cells by new random individuals and records the event
+    // The restart_elite best individuals are never replaced, full restart also starts all the lineages over
+    fn _randomise_cells(&mut self, n_cells: usize, event_type: PopulationEventType) {
+        let size = self.inds.len();
+        let stream = RANDOMISATION_STREAM - self.i_generation as u64;
+
+        let mut order: Vec<usize> = (0..size).collect();
+        order.sort_by(|&a, &b| cmp_fitness(self.inds[b].get_fitness(), self.inds[a].get_fitness()));
+        let mut cells = order.split_off(self.restart_elite.min(size));
+        cells.shuffle(&mut Self::_cell_rng(self.seed, stream, size));
+        cells.truncate(n_cells);
+
+        let new_inds: Vec<Individual> = cells
+            .par_iter()
+            .map(|&i| {
+                Self::_new_random_individual(
+                    &mut Self::_cell_rng(self.seed, stream, i),
+                    &self.ind_data,
+                )
+            })
+            .collect();
+
+        for (&i, ind) in cells.iter().zip(new_inds) {
+            if let Some(genealogy) = &mut self.genealogy {
+                genealogy.add_random(i, self.i_generation, ind.get_fitness());
+            }
+            self.inds[i] = ind;
+            self.ages[i] = 0;
+            self.lineages[i] = i;
+        }
+        if event_type == PopulationEventType::FullRestart {
+            self.ages = vec![0; size];
+            self.lineages = (0..size).collect();
+        }
+
+        self.n_evaluations += cells.len();
+        self.events.push(PopulationEvent {
+            generation: self.i_generation,
+            event_type,
+            n_cells: cells.len(),
+        });
+    }
+
+    // Function returns the mean distance between each individual and its neighbours
+    fn _local_diversity(&self) -> Vec<f64> {
+        (0..self.inds.len())
+            .into_par_iter()
+            .map_init(
+                || [0usize; MAX_NEIGHBOURS],
+                |neigh_buf, i| {
+                    let n_neigh =
+                        (self.neighbours_fn)(i, self.pop_width, self.pop_height, neigh_buf);
+
+                    let mut sum = 0.0;
+                    let mut count = 0;
+                    for &j in neigh_buf[..n_neigh].iter().filter(|&&j| j != i) {
+                        sum += self.inds[i].distance(&self.inds[j], &self.ind_data);
+                        count += 1;
+                    }
+
+                    if count > 0 {
+                        sum / count as f64
+                    } else {
+                        0.0
+                    }
+                },
+            )
+            .collect()
+    }
+
+    // Function returns the mean distance between all pairs of individuals or between sampled pairs
+    fn _mean_distance(&self) -> f64 {
+        let size = self.inds.len();
+        if size < 2 {
+            return 0.0;
+        }
+
+        let distance =
+            |(i, j): (usize, usize)| self.inds[i].distance(&self.inds[j], &self.ind_data);
+
+        if size * (size - 1) / 2 <= self.diversity_samples {
+            let pairs: Vec<(usize, usize)> = (0..size)
+                .flat_map(|i| (i + 1..size).map(move |j| (i, j)))
+                .collect();
+            return pairs.par_iter().map(|&pair| distance(pair)).sum::<f64>() / pairs.len() as f64;
+        }
+
+        // Samples are the same for the same seed and generation
+        let mut rng = Self::_cell_rng(self.seed, DIVERSITY_STREAM, self.i_generation);
+        let pairs: Vec<(usize, usize)> = (0..self.diversity_samples)
+            .map(|_| {
+                let i = rng.gen_range(0..size);
+                let j = (i + rng.gen_range(1..size)) % size;
+                (i, j)
+            })
+            .collect();
+
+        pairs.par_iter().map(|&pair| distance(pair)).sum::<f64>() / pairs.len().max(1) as f64
+    }
+
+    // Function returns the mean normalised Shannon entropy of the genome hashes in the neighbourhoods
+    fn _neighbourhood_entropy(&self, hashes: &[u64]) -> f64 {
+        let entropies: Vec<f64> = (0..hashes.len())
+            .into_par_iter()
+            .map_init(
+                || [0usize; MAX_NEIGHBOURS],
+                |neigh_buf, i| {
+                    let n_neigh =
+                        (self.neighbours_fn)(i, self.pop_width, self.pop_height, neigh_buf);
+                    if n_neigh < 2 {
+                        return 0.0;
+                    }
+
+                    let mut neigh_hashes: Vec<u64> =
+                        neigh_buf[..n_neigh].iter().map(|&j| hashes[j]).collect();
+                    neigh_hashes.sort_unstable();
+
+                    let entropy: f64 = neigh_hashes
+                        .chunk_by(|a, b| a == b)
+                        .map(|chunk| {
+                            let p = chunk.len() as f64 / n_neigh as f64;
+                            -p * p.ln()
+                        })
+                        .sum();
+
+                    entropy / (n_neigh as f64).ln()
+                },
+            )
+            .collect();
+
+        entropies.iter().sum::<f64>() / entropies.len() as f64
+    }
+
+    // Function returns the min and max of the finite values, or (0.0, 0.0) if there are none
+    fn _finite_bounds(values: &[f64]) -> (f64, f64) {
+        values
+            .iter()
+            .filter(|value| value.is_finite())
+            .fold(None, |bounds: Option<(f64, f64)>, &value| match bounds {
+                None => Some((value, value)),
+                Some((min, max)) => Some((min.min(value), max.max(value))),
+            })
+            .unwrap_or((0.0, 0.0))
+    }
+
+    // Function normalizes the values linearly from the bounds to 0.0 - 1.0
+    fn _normalize_values_linear(values: &[f64], bounds: (f64, f64)) -> Vec<f64> {
+        let (min, max) = bounds;
+        let range = max - min;
+
+        values
+            .iter()
+            .map(|&value| {
+                if range > 0.0 {
+                    (value - min) / range
+                } else {
+                    0.5
+                }
+            })
+            .collect()
+    }
+
+    // Function normalizes the values to 0.0 - 1.0 using the rank-based method
+    fn _normalize_values_rank_based(values: &[f64]) -> Vec<f64> {
+        let mut data: Vec<IndexedLabData> = values
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| IndexedLabData::new(value, 0.0, 0.0, i))
+            .collect();
+
+        normalize_component(
+            &mut data,
+            |lab_data| lab_data.l,
+            |lab_data, val| lab_data.l = val,
+            0.0,
+            1.0,
+        );
+
+        let mut normalised = vec![0.0; values.len()];
+        for value in data {
+            normalised[value.index] = value.data.l;
+        }
+        normalised
+    }
+
+    // Function writes the normalized values of the population to an RgbImage object using the colormap
+    fn _write_values_to_image(&self, values: &[f64]) -> RgbImage {
+        let mut img = RgbImage::new(self.pop_width as u32, self.pop_height as u32);
+
+        for (i, &value) in values.iter().enumerate() {
+            let x = i % self.pop_width;
+            let y = i / self.pop_width;
+
+            img.put_pixel(x as u32, y as u32, image::Rgb(self.colormap.map(value)));
+        }
+        img
+    }
+
+    // Function writes the lineages of the population to an RgbImage object
+    // Each lineage gets a pseudo-random colour derived from the index of its initial cell
+    fn _write_lineages_to_image(&self) -> RgbImage {
+        let mut img = RgbImage::new(self.pop_width as u32, self.pop_height as u32);
+
+        for (i, &lineage) in self.lineages.iter().enumerate() {
+            let x = i % self.pop_width;
+            let y = i / self.pop_width;
+
+            let hash = (lineage as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+            let angle = (hash >> 40) as f64 / (1u64 << 24) as f64 * std::f64::consts::TAU;
+            let rgb = Lab {
+                l: 45.0 + (hash & 0x1F) as f32,
+                a: (angle.cos() * 70.0) as f32,
+                b: (angle.sin() * 70.0) as f32,
+            }
+            .to_rgb();
+            img.put_pixel(x as u32, y as u32, image::Rgb(rgb));
+        }
+        img
+    }
+
+    // Function gets the L, A and B values of the current generation
+    fn _prepare_pop_lab_data(&self) -> Vec<IndexedLabData> {
+        let len = self.inds.len();
         let mut lab_data: Vec<IndexedLabData> = Vec::with_capacity(len);
         for (i, ind) in self.inds.iter().enumerate() {
             let l = ind.get_fitness();
@@ -430,6 +1802,8 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::bit_string::{BitStringData, BitStringIndividual};
+    use crate::permutation::{PermutationData, PermutationIndividual};
     use crate::testing::{MockIndividual, MockIndividualData};
     use crate::utils::LabData;
     use std::str::FromStr;
@@ -480,41 +1854,136 @@ mod tests {
         assert_eq!(neighbors, [22, 21, 23, 17, 2]);
     }
 
+    #[test]
+    fn test_l5_selection_bounded() {
+        let mut neigh_buf = [0usize; MAX_NEIGHBOURS];
+        let pop_width = 5;
+        let pop_height = 5;
+
+        // Clamped - corners have only 2 neighbours, edges 3
+        let n_neigh = TestPopulation::_l5_selection_bounded::<ClampEdge, ClampEdge>(
+            0,
+            pop_width,
+            pop_height,
+            &mut neigh_buf,
+        );
+        assert_eq!(&neigh_buf[..n_neigh], [0, 1, 5]);
+
+        let n_neigh = TestPopulation::_l5_selection_bounded::<ClampEdge, ClampEdge>(
+            22,
+            pop_width,
+            pop_height,
+            &mut neigh_buf,
+        );
+        assert_eq!(&neigh_buf[..n_neigh], [22, 21, 23, 17]);
+
+        // Middle element is not affected by the edge rules
+        let n_neigh = TestPopulation::_l5_selection_bounded::<ClampEdge, ClampEdge>(
+            12,
+            pop_width,
+            pop_height,
+            &mut neigh_buf,
+        );
+        assert_eq!(&neigh_buf[..n_neigh], [12, 11, 13, 7, 17]);
+
+        // Reflecting - neighbours outside of the grid are mirrored back
+        let n_neigh = TestPopulation::_l5_selection_bounded::<ReflectEdge, ReflectEdge>(
+            0,
+            pop_width,
+            pop_height,
+            &mut neigh_buf,
+        );
+        assert_eq!(&neigh_buf[..n_neigh], [0, 1, 1, 5, 5]);
+
+        let n_neigh = TestPopulation::_l5_selection_bounded::<ReflectEdge, ReflectEdge>(
+            24,
+            pop_width,
+            pop_height,
+            &mut neigh_buf,
+        );
+        assert_eq!(&neigh_buf[..n_neigh], [24, 23, 23, 19, 19]);
+
+        // Cylinder - wraps in X, clamped in Y
+        let n_neigh = TestPopulation::_l5_selection_bounded::<WrapEdge, ClampEdge>(
+            0,
+            pop_width,
+            pop_height,
+            &mut neigh_buf,
+        );
+        assert_eq!(&neigh_buf[..n_neigh], [0, 4, 1, 5]);
+
+        let n_neigh = TestPopulation::_l5_selection_bounded::<WrapEdge, ClampEdge>(
+            24,
+            pop_width,
+            pop_height,
+            &mut neigh_buf,
+        );
+        assert_eq!(&neigh_buf[..n_neigh], [24, 23, 20, 19]);
+
+        // Torus - same as the default L5 selection
+        let n_neigh = TestPopulation::_l5_selection_bounded::<WrapEdge, WrapEdge>(
+            24,
+            pop_width,
+            pop_height,
+            &mut neigh_buf,
+        );
+        assert_eq!(&neigh_buf[..n_neigh], [24, 23, 20, 19, 4]);
+
+        // Single cell grid has no neighbours except itself when clamped
+        let n_neigh = TestPopulation::_l5_selection_bounded::<ClampEdge, ReflectEdge>(
+            0,
+            1,
+            1,
+            &mut neigh_buf,
+        );
+        assert_eq!(&neigh_buf[..n_neigh], [0]);
+    }
+
+    #[test]
+    fn test_c9_selection_bounded() {
+        let mut neigh_buf = [0usize; MAX_NEIGHBOURS];
+
+        // Inner cell of a 4x4 grid has the full 3x3 square
+        let n_neigh =
+            TestPopulation::_c9_selection_bounded::<ClampEdge, ClampEdge>(5, 4, 4, &mut neigh_buf);
+        assert_eq!(&neigh_buf[..n_neigh], [5, 0, 1, 2, 4, 6, 8, 9, 10]);
+
+        // Corner cell of a clamped grid has only 3 neighbours
+        let n_neigh =
+            TestPopulation::_c9_selection_bounded::<ClampEdge, ClampEdge>(0, 4, 4, &mut neigh_buf);
+        assert_eq!(&neigh_buf[..n_neigh], [0, 1, 4, 5]);
+
+        // Corner cell of a torus wraps around in both directions
+        let n_neigh =
+            TestPopulation::_c9_selection_bounded::<WrapEdge, WrapEdge>(0, 4, 4, &mut neigh_buf);
+        assert_eq!(&neigh_buf[..n_neigh], [0, 15, 12, 13, 3, 1, 7, 4, 5]);
+    }
+
     #[test]
     fn test_single_tournament() {
         let mut rng = SmallRng::from_entropy();
-        let mut vec_ind = Vec::new();
-        for i in 0..6 {
-            vec_ind.push(MockIndividual {
-                fitness: i as f64,
-                visuals: (0.0, 0.0),
-                value: 0.0,
-            });
-        }
+        let fitness: Vec<f64> = (0..6).map(|i| i as f64).collect();
 
-        let res = TestPopulation::_single_tournament(&mut rng, &vec![0, 3, 2, 1], &mut vec_ind);
+        let res = TestPopulation::_single_tournament(&mut rng, &[0, 3, 2, 1], &fitness);
         assert_eq!(res, 3);
 
-        let res = TestPopulation::_single_tournament(&mut rng, &vec![3, 0, 2, 4], &mut vec_ind);
+        let res = TestPopulation::_single_tournament(&mut rng, &[3, 0, 2, 4], &fitness);
         assert_eq!(res, 4);
     }
 
     #[test]
     fn test_dual_tournament() {
-        let mut vec_ind = Vec::new();
-        for i in 0..6 {
-            vec_ind.push(MockIndividual {
-                fitness: i as f64,
-                visuals: (0.0, 0.0),
-                value: 0.0,
-            });
-        }
+        let fitness: Vec<f64> = (0..6).map(|i| i as f64).collect();
 
-        let res = TestPopulation::_dual_tournament(&vec![0, 3, 2, 1], &mut vec_ind);
+        let res = TestPopulation::_dual_tournament(&[0, 3, 2, 1], &fitness);
         assert_eq!(res, (3, 2));
 
-        let res = TestPopulation::_dual_tournament(&vec![3, 0, 2, 4], &mut vec_ind);
+        let res = TestPopulation::_dual_tournament(&[3, 0, 2, 4], &fitness);
         assert_eq!(res, (4, 3));
+
+        // Single index is returned as both parents
+        let res = TestPopulation::_dual_tournament(&[2], &fitness);
+        assert_eq!(res, (2, 2));
     }
 
     #[test]
@@ -639,4 +2108,758 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_population_clamped_boundary() {
+        let config = Config::from_str("{\"pop_width\": 3,  \"pop_height\": 3, \"mut_prob\":1.0, \"crossover_prob\":0.0, \"boundary\":\"clamped\"  }").unwrap();
+
+        let mut pop = Population::new(&config);
+
+        let mut vec_ind = Vec::new();
+        for i in 0..pop.inds.len() {
+            vec_ind.push(MockIndividual {
+                fitness: i as f64,
+                visuals: (i as f64, i as f64),
+                value: i as f64,
+            });
+        }
+        pop.inds = vec_ind;
+
+        // Edge cells don't see the opposite side of the grid
+        pop.next_gen();
+        let values: Vec<f64> = pop.inds.iter().map(|ind| ind.value).collect();
+        assert_eq!(values, [4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 8.0, 9.0, 9.0]);
+    }
+
+    #[test]
+    fn test_lineage() {
+        let config = Config::from_str(
+            "{\"pop_width\": 3,  \"pop_height\": 3, \"mut_prob\":1.0, \"crossover_prob\":0.0 }",
+        )
+        .unwrap();
+
+        let mut pop = Population::new(&config);
+
+        let mut vec_ind = Vec::new();
+        for i in 0..pop.inds.len() {
+            vec_ind.push(MockIndividual {
+                fitness: i as f64,
+                visuals: (i as f64, i as f64),
+                value: i as f64,
+            });
+        }
+        pop.inds = vec_ind;
+
+        // Initially every cell is its own lineage
+        assert_eq!(pop.lineages, (0..9).collect::<Vec<usize>>());
+        assert_eq!(pop.ages, vec![0; 9]);
+
+        // Cells take the lineage of their parents, only the cells that are their own parents get older
+        pop.next_gen();
+        assert_eq!(pop.lineages, [6, 7, 8, 6, 7, 8, 8, 8, 8]);
+        assert_eq!(pop.ages, [0, 0, 0, 0, 0, 0, 0, 0, 1]);
+
+        pop.next_gen();
+        assert_eq!(pop.lineages, [8, 8, 8, 8, 8, 8, 8, 8, 8]);
+        assert_eq!(pop.ages, [0, 0, 1, 0, 0, 1, 1, 1, 2]);
+    }
+
+    #[test]
+    fn test_visualise_modes() {
+        let config = Config::from_str("{\"pop_width\": 4,  \"pop_height\": 2, \"colormap\": \"grayscale\", \"visualise\": \"absolute\", \"visualisation_min\": 0.0, \"visualisation_max\": 10.0 }").unwrap();
+
+        let mut pop = Population::new(&config);
+        assert_eq!(pop.get_visualisation_mode(), VisualisationMode::Absolute);
+
+        let mut vec_ind = Vec::new();
+        for i in 0..pop.inds.len() {
+            vec_ind.push(MockIndividual {
+                fitness: i as f64,
+                visuals: (i as f64, 0.0),
+                value: i as f64,
+            });
+        }
+        pop.inds = vec_ind;
+
+        // Fitness is normalised linearly between the fixed bounds
+        let img = pop.visualise();
+        assert_eq!(img.dimensions(), (4, 2));
+        assert_eq!(img.get_pixel(0, 0).0, [0, 0, 0]);
+        assert_eq!(img.get_pixel(1, 1).0, [128, 128, 128]);
+
+        // Without fixed bounds, the bounds of the current generation are used
+        pop.set_visualisation_bounds(None);
+        let img = pop.visualise();
+        assert_eq!(img.get_pixel(3, 1).0, [255, 255, 255]);
+
+        // Rank-based fitness heatmap preserves the order of the individuals
+        let img = pop.visualise_with(VisualisationMode::Fitness);
+        assert!(img.get_pixel(0, 0).0[0] < img.get_pixel(3, 0).0[0]);
+        assert!(img.get_pixel(3, 0).0[0] < img.get_pixel(3, 1).0[0]);
+
+        // All of the modes produce an image of the population size
+        for mode in [
+            VisualisationMode::Lab,
+            VisualisationMode::Age,
+            VisualisationMode::Lineage,
+            VisualisationMode::Diversity,
+        ] {
+            assert_eq!(pop.visualise_with(mode).dimensions(), (4, 2));
+        }
+
+        // Visualise flag is either a boolean or the name of the mode
+        for (value, mode) in [
+            ("false", None),
+            ("true", Some(VisualisationMode::Lab)),
+            ("\"lineage\"", Some(VisualisationMode::Lineage)),
+        ] {
+            let config = Config::from_str(&format!("{{\"visualise\": {}}}", value)).unwrap();
+            assert_eq!(VisualisationMode::from_config(&config).unwrap(), mode);
+        }
+        let config = Config::from_str("{}").unwrap();
+        assert_eq!(VisualisationMode::from_config(&config).unwrap(), None);
+        let config = Config::from_str("{\"visualise\": \"nothing\"}").unwrap();
+        assert!(VisualisationMode::from_config(&config).is_err());
+
+        // Range with only one bound is refused
+        let config = Config::from_str("{\"visualisation_min\": 0.0}").unwrap();
+        assert!(TestPopulation::_visualisation_bounds(&config).is_err());
+    }
+
+    #[test]
+    fn test_ranking() {
+        let config = Config::from_str("{\"pop_width\": 3,  \"pop_height\": 2 }").unwrap();
+        let mut pop: TestPopulation = Population::new(&config);
+        pop.inds = [4.0, f64::NAN, 3.0, 8.0, 3.0, -1.0]
+            .iter()
+            .map(|&fitness| MockIndividual {
+                fitness,
+                visuals: (0.0, 0.0),
+                value: fitness,
+            })
+            .collect();
+
+        let top: Vec<f64> = pop.top_k(4).iter().map(|ind| ind.value).collect();
+        assert_eq!(top, vec![8.0, 4.0, 3.0, 3.0]);
+        assert_eq!(pop.top_k(10).len(), 6);
+        assert!(pop.top_k(0).is_empty());
+        assert!(pop.worst().value.is_nan());
+
+        assert_eq!(pop.best_position(), (0, 1));
+        assert_eq!(pop.rank_of(0, 1), 0);
+        assert_eq!(pop.rank_of(2, 0), 2);
+        assert_eq!(pop.rank_of(1, 1), 2);
+        assert_eq!(pop.rank_of(1, 0), 5);
+
+        // Rows and columns follow the cells of get_at
+        let row: Vec<f64> = pop.row(1).iter().map(|ind| ind.value).collect();
+        assert_eq!(row, vec![8.0, 3.0, -1.0]);
+        let column: Vec<f64> = pop.column(2).map(|ind| ind.value).collect();
+        assert_eq!(column, vec![3.0, -1.0]);
+        assert_eq!(pop.iter().count(), 6);
+        assert_eq!(pop.par_iter().filter(|ind| ind.fitness > 0.0).count(), 4);
+    }
+
+    #[test]
+    fn test_resize() {
+        let config =
+            Config::from_str("{\"pop_width\": 2,  \"pop_height\": 2, \"genealogy\": true}")
+                .unwrap();
+        let mut pop: TestPopulation = Population::new(&config);
+        for (i, ind) in pop.inds.iter_mut().enumerate() {
+            ind.value = i as f64;
+        }
+        pop.next_gen();
+        let values =
+            |pop: &TestPopulation| -> Vec<f64> { pop.iter().map(|ind| ind.value).collect() };
+        let before = values(&pop);
+        let ids: Vec<u64> = (0..4)
+            .map(|i| pop.get_genealogy().unwrap().get_id(i))
+            .collect();
+
+        // Tiling repeats the grid, the genealogy ids and lineages move with the individuals
+        pop.resize(3, 3, ResizeType::Tile).unwrap();
+        assert_eq!((pop.get_width(), pop.get_height()), (3, 3));
+        let b = &before;
+        assert_eq!(
+            values(&pop),
+            vec![b[0], b[1], b[0], b[2], b[3], b[2], b[0], b[1], b[0]]
+        );
+        assert_eq!(pop.get_genealogy().unwrap().get_id(4), ids[3]);
+        assert_eq!(pop.lineages[8], pop.lineages[0]);
+
+        // Interpolation stretches every cell, shrinking keeps the top left part
+        pop.resize(2, 2, ResizeType::Tile).unwrap();
+        pop.resize(4, 2, ResizeType::Interpolate).unwrap();
+        assert_eq!(
+            values(&pop),
+            vec![b[0], b[0], b[1], b[1], b[2], b[2], b[3], b[3]]
+        );
+        assert!(pop.resize(0, 2, ResizeType::Tile).is_err());
+
+        let event = pop.get_events().last().unwrap();
+        assert_eq!(event.to_string(), "resize:8");
+
+        // Next generation runs on the new grid without new evaluations for the copies
+        let evaluations = pop.get_evaluations();
+        pop.next_gen();
+        assert_eq!(pop.get_evaluations(), evaluations + 8);
+        assert_eq!(pop.iter().count(), 8);
+    }
+
+    #[test]
+    fn test_setters() {
+        let config = Config::from_str("{\"pop_width\": 4,  \"pop_height\": 4}").unwrap();
+        let mut pop: TestPopulation = Population::new(&config);
+
+        pop.set_mut_prob(0.5);
+        pop.set_mut_amount(0.2);
+        pop.set_crossover_prob(0.7);
+        pop.set_selection_strategy(SelectionStrategyType::Roulette);
+        pop.set_neighbourhood(NeighbourhoodType::C9);
+        pop.set_boundary(BoundaryType::Clamped);
+        assert_eq!(
+            (
+                pop.get_mut_prob(),
+                pop.get_mut_amount(),
+                pop.get_crossover_prob()
+            ),
+            (0.5, 0.2, 0.7)
+        );
+        assert_eq!(
+            pop.get_selection_strategy(),
+            SelectionStrategyType::Roulette
+        );
+        assert_eq!(pop.get_neighbourhood(), NeighbourhoodType::C9);
+        assert_eq!(pop.get_boundary(), BoundaryType::Clamped);
+
+        // Corner cell of the clamped grid has 4 distinct cells in the Moore neighbourhood
+        let mut buf = [0usize; MAX_NEIGHBOURS];
+        let n_neigh = (pop.neighbours_fn)(0, 4, 4, &mut buf);
+        let distinct: HashSet<usize> = buf[..n_neigh].iter().copied().collect();
+        assert_eq!(distinct.len(), 4);
+        pop.next_gen();
+    }
+
+    #[test]
+    fn test_stats() {
+        let config = Config::from_str("{\"pop_width\": 2,  \"pop_height\": 2 }").unwrap();
+
+        let mut pop = Population::new(&config);
+        assert_eq!(pop.get_evaluations(), 4);
+
+        pop.inds = [4.0, 1.0, 3.0, 8.0]
+            .iter()
+            .map(|&fitness| MockIndividual {
+                fitness,
+                visuals: (0.0, 0.0),
+                value: fitness,
+            })
+            .collect();
+
+        let stats = pop.get_stats();
+        assert_eq!(
+            stats,
+            PopulationStats {
+                best_fitness: 8.0,
+                worst_fitness: 1.0,
+                mean_fitness: 4.0,
+                median_fitness: 3.5,
+                std_fitness: 6.5f64.sqrt(),
+                n_invalid: 0,
+            }
+        );
+
+        pop.next_gen();
+        assert_eq!(pop.get_evaluations(), 8);
+    }
+
+    #[test]
+    fn test_invalid_fitness() {
+        let config = Config::from_str(
+            "{\"pop_width\": 3,  \"pop_height\": 2, \"selection_strategy\": \"roulette\"}",
+        )
+        .unwrap();
+        let mut pop: TestPopulation = Population::new(&config);
+        pop.inds = [2.0, f64::NAN, f64::NEG_INFINITY, 1.0, f64::NAN, -1.0]
+            .iter()
+            .map(|&fitness| MockIndividual {
+                fitness,
+                visuals: (fitness, 0.0),
+                value: fitness,
+            })
+            .collect();
+
+        // NaN is the worst fitness, invalid individuals are only counted by the stats
+        assert_eq!(pop.get_best().get_fitness(), 2.0);
+        let stats = pop.get_stats();
+        assert_eq!(stats.n_invalid, 3);
+        assert_eq!((stats.best_fitness, stats.worst_fitness), (2.0, -1.0));
+        assert_eq!(stats.mean_fitness, 2.0 / 3.0);
+
+        let fitness: Vec<f64> = pop.inds.iter().map(|ind| ind.get_fitness()).collect();
+        let mut rng = SmallRng::seed_from_u64(0);
+        assert_eq!(
+            TestPopulation::_single_tournament(&mut rng, &[1, 2, 5], &fitness),
+            5
+        );
+        assert_eq!(
+            TestPopulation::_dual_tournament(&[4, 2, 1], &fitness),
+            (2, 4)
+        );
+
+        // Roulette never selects the invalid individuals and selects +inf whenever it is present
+        for _ in 0..100 {
+            let res = TestPopulation::_roulette_selection(&mut rng, &[1, 2, 3, 4, 5], &fitness);
+            assert!(res == 3 || res == 5);
+        }
+        assert_eq!(
+            TestPopulation::_roulette_selection(&mut rng, &[1, 2, 4], &fitness),
+            2
+        );
+        let mut fitness = fitness;
+        fitness[0] = f64::INFINITY;
+        assert_eq!(
+            TestPopulation::_roulette_selection(&mut rng, &[0, 2, 3], &fitness),
+            0
+        );
+
+        // Niching and the generation step handle the invalid individuals as well
+        pop.niching_type = NichingType::Sharing;
+        let fitness = selection_fitness(&pop);
+        assert!(fitness[1].is_nan());
+        assert_eq!(fitness[2], f64::NEG_INFINITY);
+        assert!(fitness[0].is_finite());
+        pop.next_gen();
+        pop.visualise_with(VisualisationMode::Lab);
+        assert!(pop.get_best().get_fitness().is_finite());
+    }
+
+    #[test]
+    fn test_niching_fitness() {
+        let config = Config::from_str(
+            "{\"pop_width\": 3, \"pop_height\": 3, \"niching\": \"sharing\", \"niche_radius\": 1.0}",
+        )
+        .unwrap();
+        let mut pop: TestPopulation = Population::new(&config);
+        assert_eq!(pop.get_niching(), NichingType::Sharing);
+        for (i, ind) in pop.inds.iter_mut().enumerate() {
+            ind.fitness = i as f64;
+        }
+
+        // Individuals share the fitness with their 4 same neighbours
+        let fitness = selection_fitness(&pop);
+        for (i, f) in fitness.iter().enumerate() {
+            assert!((f - i as f64 / 5.0).abs() < 1e-12);
+        }
+
+        // Only the best individual of each neighbourhood keeps its fitness
+        pop.niching_type = NichingType::Clearing;
+        let fitness = selection_fitness(&pop);
+        assert_eq!(fitness[8], 8.0);
+        assert_eq!(fitness[7], 0.0);
+        assert_eq!(fitness[0], 0.0);
+
+        // Individuals farther than the radius are not in the same niche
+        for (i, ind) in pop.inds.iter_mut().enumerate() {
+            ind.visuals = (i as f64 * 2.0, 0.0);
+        }
+        assert_eq!(
+            selection_fitness(&pop),
+            (0..9).map(|i| i as f64).collect::<Vec<f64>>()
+        );
+        pop.niching_type = NichingType::Sharing;
+        assert_eq!(
+            selection_fitness(&pop),
+            (0..9).map(|i| i as f64).collect::<Vec<f64>>()
+        );
+    }
+
+    fn selection_fitness(pop: &TestPopulation) -> Vec<f64> {
+        let mut fitness = Vec::new();
+        pop._count_selection_fitness(&mut fitness);
+        fitness
+    }
+
+    #[test]
+    fn test_crowding_replacement() {
+        let config =
+            Config::from_str("{\"pop_width\": 2, \"pop_height\": 1, \"niching\": \"crowding\"}")
+                .unwrap();
+        let mut pop: TestPopulation = Population::new(&config);
+        pop.inds[0] = MockIndividual {
+            fitness: 5.0,
+            visuals: (0.0, 0.0),
+            value: 5.0,
+        };
+        pop.inds[1] = MockIndividual {
+            fitness: 1.0,
+            visuals: (10.0, 0.0),
+            value: 1.0,
+        };
+
+        let birth = Birth {
+            parent: 0,
+            other_parent: 1,
+            operator: Operator::Crossover,
+            improved: false,
+            is_offspring: true,
+            fitness: 3.0,
+        };
+
+        // First offspring is close to the worse parent and replaces it, the second one is close to the better parent
+        // and loses, so the better parent is kept once
+        let offspring = vec![
+            MockIndividual {
+                fitness: 3.0,
+                visuals: (9.0, 0.0),
+                value: 3.0,
+            },
+            MockIndividual {
+                fitness: 3.0,
+                visuals: (1.0, 0.0),
+                value: 3.0,
+            },
+        ];
+        let mut births: Vec<Birth> = offspring
+            .iter()
+            .map(|ind| pop._most_similar_parent_first(ind, birth))
+            .collect();
+        assert_eq!((births[0].parent, births[1].parent), (1, 0));
+
+        let mut cell_births = Vec::new();
+        pop._crowding_replacement(&offspring, &mut births, &mut cell_births);
+        let values: Vec<f64> = pop.inds.iter().map(|ind| ind.value).collect();
+        assert_eq!(values, vec![5.0, 3.0]);
+        assert!(births[0].is_offspring && !births[1].is_offspring);
+        assert_eq!(
+            (cell_births[0].parent, cell_births[0].is_offspring),
+            (0, false)
+        );
+        assert_eq!(
+            (cell_births[1].parent, cell_births[1].is_offspring),
+            (1, true)
+        );
+
+        // Population with crowding never gets worse, because the mock offspring is better than its parent
+        pop.next_gen();
+        assert_eq!(pop.get_best().get_fitness(), 6.0);
+    }
+
+    #[test]
+    fn test_crowding_diversity() {
+        // Fitness is the number of bits matching the closest of four peaks, so there are four optima
+        let peaks: Vec<Vec<bool>> = (0..4)
+            .map(|peak| (0..24).map(|bit| (bit / 6 + peak) % 4 < 2).collect())
+            .collect();
+        let objective = move |bits: &[bool]| {
+            peaks
+                .iter()
+                .map(|peak| peak.iter().zip(bits).filter(|(a, b)| a == b).count())
+                .max()
+                .unwrap() as f64
+        };
+
+        let n_distinct = |niching: &str| {
+            let config = Config::from_str(&format!(
+                "{{\"pop_width\": 16, \"pop_height\": 16, \"mut_prob\": 0.02, \"crossover_prob\": 0.5, \"seed\": 7, \"niching\": \"{}\"}}",
+                niching
+            ))
+            .unwrap();
+            let ind_data = BitStringData::new(24, objective.clone());
+            let mut pop: Population<BitStringIndividual, BitStringData> =
+                Population::new_with_data(&config, ind_data);
+            for _ in 0..100 {
+                pop.next_gen();
+            }
+            pop.get_diversity().n_distinct
+        };
+
+        // Crowding keeps more distinct genotypes than no niching
+        assert!(n_distinct("crowding") > n_distinct("none"));
+    }
+
+    #[test]
+    fn test_restart_policies() {
+        let fitnesses = |pop: &TestPopulation| -> Vec<f64> {
+            let mut fitnesses: Vec<f64> = pop.inds.iter().map(|ind| ind.get_fitness()).collect();
+            fitnesses.sort_by(|a, b| a.total_cmp(b));
+            fitnesses
+        };
+
+        // Mock individuals only improve by mutation, so the best fitness is made unreachable to simulate stagnation
+        let config = Config::from_str(
+            "{\"pop_width\": 4, \"pop_height\": 4, \"crossover_prob\": 0.0, \"restart\": \"partial\", \"stagnation_generations\": 2, \"restart_fraction\": 0.5}",
+        )
+        .unwrap();
+        let mut pop: TestPopulation = Population::new(&config);
+        pop.best_fitness_so_far = 100.0;
+        pop.next_gen();
+        assert!(pop.get_events().is_empty());
+        assert_eq!(pop.get_stagnation(), 1);
+        pop.next_gen();
+
+        // Half of the cells is re-randomised and the stagnation counter is reset
+        let mut expected = vec![0.0; 8];
+        expected.extend(vec![2.0; 8]);
+        assert_eq!(fitnesses(&pop), expected);
+        assert_eq!(pop.get_stagnation(), 0);
+        assert_eq!(pop.get_evaluations(), 16 * 3 + 8);
+        assert_eq!(
+            pop.get_events(),
+            &[PopulationEvent {
+                generation: 2,
+                event_type: PopulationEventType::PartialRestart,
+                n_cells: 8
+            }]
+        );
+        assert_eq!(pop.get_events()[0].to_string(), "partial_restart:8");
+
+        // Full restart keeps only the elite
+        let config = Config::from_str(
+            "{\"pop_width\": 4, \"pop_height\": 4, \"crossover_prob\": 0.0, \"restart\": \"full\", \"stagnation_generations\": 1, \"restart_elite\": 2}",
+        )
+        .unwrap();
+        let mut pop: TestPopulation = Population::new(&config);
+        pop.best_fitness_so_far = 100.0;
+        pop.next_gen();
+        let mut expected = vec![0.0; 14];
+        expected.extend(vec![1.0; 2]);
+        assert_eq!(fitnesses(&pop), expected);
+        assert_eq!(pop.get_events()[0].n_cells, 14);
+        assert!(pop.ages.iter().all(|&age| age == 0));
+
+        // Immigrants come periodically regardless of the stagnation
+        let config = Config::from_str(
+            "{\"pop_width\": 4, \"pop_height\": 4, \"crossover_prob\": 0.0, \"immigrant_interval\": 3, \"immigrant_fraction\": 0.25}",
+        )
+        .unwrap();
+        let mut pop: TestPopulation = Population::new(&config);
+        for _ in 0..6 {
+            pop.next_gen();
+        }
+        let events: Vec<(usize, PopulationEventType)> = pop
+            .get_events()
+            .iter()
+            .map(|event| (event.generation, event.event_type))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                (3, PopulationEventType::Immigrants),
+                (6, PopulationEventType::Immigrants)
+            ]
+        );
+        assert_eq!(fitnesses(&pop).iter().filter(|&&f| f == 0.0).count(), 4);
+    }
+
+    #[test]
+    fn test_genealogy() {
+        let config = Config::from_str("{\"pop_width\": 2, \"pop_height\": 2}").unwrap();
+        let pop: TestPopulation = Population::new(&config);
+        assert!(pop.get_genealogy().is_none());
+
+        let config = Config::from_str(
+            "{\"pop_width\": 2, \"pop_height\": 2, \"genealogy\": true, \"crossover_prob\": 0.0}",
+        )
+        .unwrap();
+        let mut pop: TestPopulation = Population::new(&config);
+        pop.next_gen();
+        pop.next_gen();
+
+        // Every mutation of the mock individual improves it and the ids follow the cells
+        let genealogy = pop.get_genealogy().unwrap();
+        let mutation = genealogy.get_operator_stats(Operator::Mutation);
+        assert_eq!((mutation.n_offspring, mutation.n_improved), (8, 8));
+        assert_eq!(
+            genealogy
+                .get_operator_stats(Operator::Crossover)
+                .n_offspring,
+            0
+        );
+        assert_eq!(genealogy.get_id(3), 11);
+
+        let ancestry = genealogy.get_ancestry(pop.get_best_id().unwrap());
+        assert_eq!(ancestry.len(), 3);
+        assert_eq!(ancestry[0].birth_generation, 2);
+        assert_eq!(ancestry[0].fitness, 2.0);
+        assert_eq!(ancestry[2].operator, Operator::Random);
+    }
+
+    #[test]
+    fn test_double_buffering() {
+        let config = Config::from_str(
+            "{\"pop_width\": 4, \"pop_height\": 4, \"crossover_prob\": 0.5, \"seed\": 3}",
+        )
+        .unwrap();
+        let ind_data = PermutationData::new(10, |genes: &[u16]| genes[0] as f64);
+        let mut pop: Population<PermutationIndividual<u16>, PermutationData<u16>> =
+            Population::new_with_data(&config, ind_data);
+
+        let genomes = |inds: &[PermutationIndividual<u16>]| -> Vec<*const u16> {
+            inds.iter().map(|ind| ind.genes.as_ptr()).collect()
+        };
+
+        // Offspring are written into the genomes of the generation before the current one
+        pop.next_gen();
+        let previous = genomes(&pop.next_inds);
+        pop.next_gen();
+        assert_eq!(genomes(&pop.inds), previous);
+
+        // Generations are the same as without the buffers
+        let mut other: Population<PermutationIndividual<u16>, PermutationData<u16>> =
+            Population::new_with_data(&config, pop.ind_data.clone());
+        other.next_gen();
+        other.next_inds = Vec::new();
+        other.next_gen();
+        for (a, b) in pop.inds.iter().zip(&other.inds) {
+            assert_eq!(a.genes, b.genes);
+        }
+    }
+
+    // Evaluator of the mock genomes, the fitness is the value of the genome, the sizes of the batches are recorded
+    struct MockBatchEvaluator {
+        batch_sizes: std::sync::Mutex<Vec<usize>>,
+        fail: bool,
+    }
+
+    impl BatchEvaluator for MockBatchEvaluator {
+        fn evaluate_batch(&self, genomes: Vec<Value>) -> Vec<Result<f64, String>> {
+            self.batch_sizes.lock().unwrap().push(genomes.len());
+            genomes
+                .iter()
+                .map(|genome| {
+                    if self.fail {
+                        Err("Worker failed".to_string())
+                    } else {
+                        Ok(genome["value"].as_f64().unwrap())
+                    }
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_batch_evaluator() {
+        let config = Config::from_str(
+            "{\"pop_width\": 4,  \"pop_height\": 4, \"crossover_prob\": 0.5, \"seed\": 7 }",
+        )
+        .unwrap();
+        let run = |evaluator: Option<Arc<MockBatchEvaluator>>| {
+            let mut pop: TestPopulation = Population::new(&config);
+            for (i, ind) in pop.inds.iter_mut().enumerate() {
+                ind.value = i as f64;
+                ind.fitness = i as f64;
+            }
+            if let Some(evaluator) = evaluator {
+                pop.set_batch_evaluator(evaluator);
+            }
+            for _ in 0..5 {
+                pop.next_gen();
+            }
+            pop
+        };
+
+        // Whole generation is evaluated in one batch with the same result as count_fitness
+        let evaluator = Arc::new(MockBatchEvaluator {
+            batch_sizes: std::sync::Mutex::new(Vec::new()),
+            fail: false,
+        });
+        let pop = run(Some(evaluator.clone()));
+        assert_eq!(*evaluator.batch_sizes.lock().unwrap(), vec![16; 5]);
+        let other = run(None);
+        for (a, b) in pop.inds.iter().zip(&other.inds) {
+            assert_eq!((a.value, a.fitness), (b.value, b.fitness));
+        }
+        let births = |pop: &TestPopulation| -> Vec<(usize, f64, bool)> {
+            pop.births
+                .iter()
+                .map(|birth| (birth.parent, birth.fitness, birth.improved))
+                .collect()
+        };
+        assert_eq!(births(&pop), births(&other));
+
+        // Genomes that can't be evaluated are invalid
+        let mut pop = run(None);
+        pop.set_batch_evaluator(Arc::new(MockBatchEvaluator {
+            batch_sizes: std::sync::Mutex::new(Vec::new()),
+            fail: true,
+        }));
+        pop.next_gen();
+        assert_eq!(pop.get_stats().n_invalid, 16);
+
+        // Without the evaluator, count_fitness is used again
+        pop.remove_batch_evaluator();
+        pop.next_gen();
+        assert_eq!(pop.get_stats().n_invalid, 0);
+    }
+
+    #[test]
+    fn test_hall_of_fame() {
+        let config = Config::from_str(
+            "{\"pop_width\": 2, \"pop_height\": 2, \"hall_of_fame_size\": 3, \"restart\": \"full\", \"stagnation_generations\": 1, \"restart_elite\": 0}",
+        )
+        .unwrap();
+        let mut pop: TestPopulation = Population::new(&config);
+        assert_eq!(pop.get_hall_of_fame().len(), 1);
+
+        // Best individual is kept even when the whole population is restarted
+        pop.best_fitness_so_far = 100.0;
+        pop.next_gen();
+        assert_eq!(pop.get_best().get_fitness(), 0.0);
+        let best = pop.get_hall_of_fame().get_best().unwrap();
+        assert_eq!((best.fitness, best.generation), (1.0, 1));
+    }
+
+    #[test]
+    fn test_diversity() {
+        let config = Config::from_str("{\"pop_width\": 4,  \"pop_height\": 4 }").unwrap();
+        let mut pop: TestPopulation = Population::new(&config);
+
+        // Converged population has no diversity
+        let diversity = pop.get_diversity();
+        assert_eq!(diversity.mean_distance, 0.0);
+        assert_eq!(diversity.neighbourhood_entropy, 0.0);
+        assert_eq!(diversity.n_distinct, 1);
+
+        // Columns alternate between two genomes, so 64 of 120 pairs differ
+        for (i, ind) in pop.inds.iter_mut().enumerate() {
+            ind.visuals = ((i % 2) as f64, 0.0);
+        }
+        let diversity = pop.get_diversity();
+        assert!((diversity.mean_distance - 64.0 / 120.0).abs() < 1e-12);
+        assert_eq!(diversity.n_distinct, 2);
+
+        // Every L5 neighbourhood has 3 individuals of one genome and 2 of the other
+        let entropy = -(0.6f64 * 0.6f64.ln() + 0.4 * 0.4f64.ln()) / 5f64.ln();
+        assert!((diversity.neighbourhood_entropy - entropy).abs() < 1e-12);
+
+        // Sampled mean distance is close to the exact one
+        pop.diversity_samples = 50;
+        let sampled = pop.get_diversity();
+        assert!((sampled.mean_distance - 64.0 / 120.0).abs() < 0.2);
+        assert_eq!(sampled, pop.get_diversity());
+    }
+
+    #[test]
+    fn test_seed() {
+        let config = Config::from_str("{\"pop_width\": 4,  \"pop_height\": 4, \"crossover_prob\": 0.5, \"selection_strategy\": \"roulette\", \"seed\": 42 }").unwrap();
+
+        let run = || {
+            let mut pop: TestPopulation = Population::new(&config);
+            for (i, ind) in pop.inds.iter_mut().enumerate() {
+                ind.value = i as f64;
+                ind.fitness = i as f64;
+            }
+
+            for _ in 0..5 {
+                pop.next_gen();
+            }
+            assert_eq!(pop.get_seed(), 42);
+            pop.inds.iter().map(|ind| ind.value).collect::<Vec<f64>>()
+        };
+
+        // The same seed gives the same run
+        assert_eq!(run(), run());
+    }
 }
@@ -0,0 +1,497 @@
+use crate::config::Config;
+use crate::evo_individual::{hash_value, EvoIndividual, RemoteIndividual};
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand_distr::{Distribution, Normal};
+use serde_json::{json, Value};
+use std::fmt;
+use std::sync::Arc;
+use strum_macros::{Display, EnumIter, EnumString};
+
+const DEFAULT_N_DIMS: usize = 10;
+const DEFAULT_LOWER_BOUND: f64 = -5.0;
+const DEFAULT_UPPER_BOUND: f64 = 5.0;
+const DEFAULT_REAL_MUTATION_TYPE: RealMutationType = RealMutationType::Gaussian;
+const DEFAULT_REAL_CROSSOVER_TYPE: RealCrossoverType = RealCrossoverType::Sbx;
+const DEFAULT_POLYNOMIAL_ETA: f64 = 20.0;
+const DEFAULT_SBX_ETA: f64 = 15.0;
+const DEFAULT_BLX_ALPHA: f64 = 0.5;
+
+// Objective function over the genes, its value is the fitness that is maximised
+pub type RealObjective = Arc<dyn Fn(&[f64]) -> f64 + Send + Sync>;
+
+#[derive(Clone, Copy, PartialEq, Debug, EnumString, EnumIter, Display)]
+pub enum RealMutationType {
+    // Normally distributed change with standard deviation of mut_amount
+    #[strum(serialize = "gaussian")]
+    Gaussian,
+    // Polynomial mutation with distribution index polynomial_eta
+    #[strum(serialize = "polynomial")]
+    Polynomial,
+    // Gene is reset to a random value within the bounds
+    #[strum(serialize = "uniform")]
+    Uniform,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, EnumString, EnumIter, Display)]
+pub enum RealCrossoverType {
+    // Simulated binary crossover with distribution index sbx_eta
+    #[strum(serialize = "sbx")]
+    Sbx,
+    // Blend crossover, genes are taken from the range of the parents extended by blx_alpha on both sides
+    #[strum(serialize = "blx")]
+    Blx,
+    // Weighted average of the parents with a random weight
+    #[strum(serialize = "arithmetic")]
+    Arithmetic,
+    // Each gene is taken from one of the parents
+    #[strum(serialize = "uniform")]
+    Uniform,
+}
+
+#[derive(Clone)]
+pub struct RealVectorData {
+    // Lower and upper bound of each gene
+    pub bounds: Vec<(f64, f64)>,
+    pub objective: RealObjective,
+
+    pub mutation_type: RealMutationType,
+    pub crossover_type: RealCrossoverType,
+
+    // Parameters of the operators
+    pub polynomial_eta: f64,
+    pub sbx_eta: f64,
+    pub blx_alpha: f64,
+}
+
+impl RealVectorData {
+    // Function creates data for genomes of n_dims genes with the same bounds
+    pub fn new<F>(n_dims: usize, lower_bound: f64, upper_bound: f64, objective: F) -> Self
+    where
+        F: Fn(&[f64]) -> f64 + Send + Sync + 'static,
+    {
+        Self::with_bounds(vec![(lower_bound, upper_bound); n_dims], objective)
+    }
+
+    // Function creates data for genomes with separate bounds for each gene
+    pub fn with_bounds<F>(bounds: Vec<(f64, f64)>, objective: F) -> Self
+    where
+        F: Fn(&[f64]) -> f64 + Send + Sync + 'static,
+    {
+        RealVectorData {
+            bounds,
+            objective: Arc::new(objective),
+            mutation_type: DEFAULT_REAL_MUTATION_TYPE,
+            crossover_type: DEFAULT_REAL_CROSSOVER_TYPE,
+            polynomial_eta: DEFAULT_POLYNOMIAL_ETA,
+            sbx_eta: DEFAULT_SBX_ETA,
+            blx_alpha: DEFAULT_BLX_ALPHA,
+        }
+    }
+
+    // Function creates data with the genome parameters from the config and the given objective
+    pub fn from_config<F>(config: &Config, objective: F) -> Result<Self, String>
+    where
+        F: Fn(&[f64]) -> f64 + Send + Sync + 'static,
+    {
+        let lower_bound = config
+            .may_get_float("lower_bound")?
+            .unwrap_or(DEFAULT_LOWER_BOUND);
+        let upper_bound = config
+            .may_get_float("upper_bound")?
+            .unwrap_or(DEFAULT_UPPER_BOUND);
+        // Genes couldn't be sampled from inverted bounds
+        if lower_bound > upper_bound {
+            return Err(format!(
+                "Lower bound {} is greater than upper bound {}",
+                lower_bound, upper_bound
+            ));
+        }
+
+        let mut ind_data = Self::new(
+            config.may_get_uint("n_dims")?.unwrap_or(DEFAULT_N_DIMS),
+            lower_bound,
+            upper_bound,
+            objective,
+        );
+
+        ind_data.mutation_type = config
+            .may_get_enum("real_mutation")?
+            .unwrap_or(DEFAULT_REAL_MUTATION_TYPE);
+        ind_data.crossover_type = config
+            .may_get_enum("real_crossover")?
+            .unwrap_or(DEFAULT_REAL_CROSSOVER_TYPE);
+        ind_data.polynomial_eta = config
+            .may_get_float("polynomial_eta")?
+            .unwrap_or(DEFAULT_POLYNOMIAL_ETA);
+        ind_data.sbx_eta = config.may_get_float("sbx_eta")?.unwrap_or(DEFAULT_SBX_ETA);
+        ind_data.blx_alpha = config
+            .may_get_float("blx_alpha")?
+            .unwrap_or(DEFAULT_BLX_ALPHA);
+
+        Ok(ind_data)
+    }
+}
+
+#[derive(Clone)]
+pub struct RealVectorIndividual {
+    pub fitness: f64,
+    pub genes: Vec<f64>,
+}
+
+impl RealVectorIndividual {
+    pub fn new(genes: Vec<f64>) -> Self {
+        RealVectorIndividual {
+            fitness: 0.0,
+            genes,
+        }
+    }
+
+    pub fn get_genes(&self) -> &[f64] {
+        &self.genes
+    }
+
+    fn _polynomial_mutation(rng: &mut SmallRng, gene: f64, bounds: (f64, f64), eta: f64) -> f64 {
+        let u: f64 = rng.gen_range(0.0..1.0);
+        let delta = if u < 0.5 {
+            (2.0 * u).powf(1.0 / (eta + 1.0)) - 1.0
+        } else {
+            1.0 - (2.0 * (1.0 - u)).powf(1.0 / (eta + 1.0))
+        };
+
+        gene + delta * (bounds.1 - bounds.0)
+    }
+
+    // Function returns one of the two children of simulated binary crossover of the genes
+    fn _sbx(rng: &mut SmallRng, first: f64, second: f64, eta: f64) -> f64 {
+        if first == second {
+            return first;
+        }
+
+        let u: f64 = rng.gen_range(0.0..1.0);
+        let beta = if u <= 0.5 {
+            (2.0 * u).powf(1.0 / (eta + 1.0))
+        } else {
+            (1.0 / (2.0 * (1.0 - u))).powf(1.0 / (eta + 1.0))
+        };
+
+        if rng.gen_bool(0.5) {
+            0.5 * ((1.0 + beta) * first + (1.0 - beta) * second)
+        } else {
+            0.5 * ((1.0 - beta) * first + (1.0 + beta) * second)
+        }
+    }
+
+    fn _blx(rng: &mut SmallRng, first: f64, second: f64, alpha: f64) -> f64 {
+        let (min, max) = if first < second {
+            (first, second)
+        } else {
+            (second, first)
+        };
+        let extent = (max - min) * alpha;
+
+        if max - min <= 0.0 {
+            min
+        } else {
+            rng.gen_range(min - extent..max + extent)
+        }
+    }
+}
+
+impl EvoIndividual<RealVectorData> for RealVectorIndividual {
+    fn new_randomised(ind_data: &RealVectorData, rng: &mut SmallRng) -> Self {
+        let genes = ind_data
+            .bounds
+            .iter()
+            .map(|&(lower, upper)| rng.gen_range(lower..=upper))
+            .collect();
+
+        Self::new(genes)
+    }
+
+    fn mutate(
+        &mut self,
+        ind_data: &RealVectorData,
+        rng: &mut SmallRng,
+        mut_prob: f32,
+        mut_amount: f32,
+    ) {
+        for (gene, &bounds) in self.genes.iter_mut().zip(ind_data.bounds.iter()) {
+            if rng.gen_range(0.0..1.0) >= mut_prob {
+                continue;
+            }
+
+            *gene = match ind_data.mutation_type {
+                RealMutationType::Gaussian => {
+                    let normal = Normal::new(0.0, mut_amount.abs() as f64).unwrap();
+                    *gene + normal.sample(rng)
+                }
+                RealMutationType::Polynomial => {
+                    Self::_polynomial_mutation(rng, *gene, bounds, ind_data.polynomial_eta)
+                }
+                RealMutationType::Uniform => rng.gen_range(bounds.0..=bounds.1),
+            }
+            .clamp(bounds.0, bounds.1);
+        }
+    }
+
+    fn crossover(
+        &self,
+        another_ind: &RealVectorIndividual,
+        ind_data: &RealVectorData,
+        rng: &mut SmallRng,
+    ) -> RealVectorIndividual {
+        let mut child = Self::new(Vec::with_capacity(self.genes.len()));
+        self.crossover_into(another_ind, ind_data, rng, &mut child);
+        child
+    }
+
+    // Child is written into the genes of dest
+    fn crossover_into(
+        &self,
+        another_ind: &RealVectorIndividual,
+        ind_data: &RealVectorData,
+        rng: &mut SmallRng,
+        dest: &mut RealVectorIndividual,
+    ) {
+        // Arithmetic crossover uses the same weight for all of the genes
+        let ratio = rng.gen_range(0.0..1.0);
+
+        dest.fitness = 0.0;
+        dest.genes.clear();
+        dest.genes.extend(
+            self.genes
+                .iter()
+                .zip(another_ind.genes.iter())
+                .zip(ind_data.bounds.iter())
+                .map(|((&first, &second), &bounds)| {
+                    match ind_data.crossover_type {
+                        RealCrossoverType::Sbx => Self::_sbx(rng, first, second, ind_data.sbx_eta),
+                        RealCrossoverType::Blx => {
+                            Self::_blx(rng, first, second, ind_data.blx_alpha)
+                        }
+                        RealCrossoverType::Arithmetic => first * ratio + second * (1.0 - ratio),
+                        RealCrossoverType::Uniform => {
+                            if rng.gen_bool(0.5) {
+                                first
+                            } else {
+                                second
+                            }
+                        }
+                    }
+                    .clamp(bounds.0, bounds.1)
+                }),
+        );
+    }
+
+    fn count_fitness(&mut self, ind_data: &RealVectorData) {
+        self.fitness = (ind_data.objective)(&self.genes);
+    }
+
+    fn get_fitness(&self) -> f64 {
+        self.fitness
+    }
+
+    // Genome is copied into the allocation of this individual
+    fn copy_from(&mut self, other: &Self) {
+        self.fitness = other.fitness;
+        self.genes.clone_from(&other.genes);
+    }
+
+    // Visuals are the sums of the genes at even and odd positions
+    fn get_visuals(&self, _ind_data: &RealVectorData) -> (f64, f64) {
+        let mut a = 0.0;
+        let mut b = 0.0;
+
+        for (i, gene) in self.genes.iter().enumerate() {
+            if i % 2 == 0 {
+                a += gene;
+            } else {
+                b += gene;
+            }
+        }
+
+        (a, b)
+    }
+
+    // Distance is the euclidean distance between the genes
+    fn distance(&self, other: &Self, _ind_data: &RealVectorData) -> f64 {
+        self.genes
+            .iter()
+            .zip(other.genes.iter())
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    fn genome_hash(&self, _ind_data: &RealVectorData) -> u64 {
+        let bits: Vec<u64> = self.genes.iter().map(|gene| gene.to_bits()).collect();
+        hash_value(&bits)
+    }
+}
+
+// Genome is sent as the array of the genes
+impl RemoteIndividual<RealVectorData> for RealVectorIndividual {
+    fn genome_to_json(&self) -> Value {
+        json!(self.genes)
+    }
+
+    fn from_genome_json(genome: &Value, ind_data: &RealVectorData) -> Result<Self, String> {
+        let genes = genome
+            .as_array()
+            .and_then(|genes| {
+                genes
+                    .iter()
+                    .map(Value::as_f64)
+                    .collect::<Option<Vec<f64>>>()
+            })
+            .ok_or_else(|| format!("Genome '{}' is not an array of numbers", genome))?;
+        if genes.len() != ind_data.bounds.len() {
+            return Err(format!(
+                "Genome has {} genes instead of {}",
+                genes.len(),
+                ind_data.bounds.len()
+            ));
+        }
+
+        Ok(Self::new(genes))
+    }
+
+    fn set_fitness(&mut self, fitness: f64) {
+        self.fitness = fitness;
+    }
+}
+
+impl fmt::Display for RealVectorIndividual {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}, fitness: {}", self.genes, self.fitness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::population::Population;
+    use rand::SeedableRng;
+    use std::str::FromStr;
+    use strum::IntoEnumIterator;
+
+    fn sphere(genes: &[f64]) -> f64 {
+        -genes.iter().map(|x| x * x).sum::<f64>()
+    }
+
+    #[test]
+    fn test_operators_respect_bounds() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let mut ind_data =
+            RealVectorData::with_bounds(vec![(-1.0, 1.0), (0.0, 10.0), (5.0, 6.0)], sphere);
+
+        for mutation_type in RealMutationType::iter() {
+            for crossover_type in RealCrossoverType::iter() {
+                ind_data.mutation_type = mutation_type;
+                ind_data.crossover_type = crossover_type;
+
+                for _ in 0..100 {
+                    let first = RealVectorIndividual::new_randomised(&ind_data, &mut rng);
+                    let second = RealVectorIndividual::new_randomised(&ind_data, &mut rng);
+
+                    let mut child = first.crossover(&second, &ind_data, &mut rng);
+                    child.mutate(&ind_data, &mut rng, 1.0, 100.0);
+
+                    for (gene, bounds) in child.genes.iter().zip(ind_data.bounds.iter()) {
+                        assert!(*gene >= bounds.0 && *gene <= bounds.1);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_crossover() {
+        let mut rng = SmallRng::seed_from_u64(2);
+        let mut ind_data = RealVectorData::new(4, -10.0, 10.0, sphere);
+
+        let first = RealVectorIndividual::new(vec![1.0, 2.0, 3.0, 4.0]);
+        let second = RealVectorIndividual::new(vec![-1.0, 2.0, 5.0, 0.0]);
+
+        // Uniform crossover takes each gene from one of the parents
+        ind_data.crossover_type = RealCrossoverType::Uniform;
+        let child = first.crossover(&second, &ind_data, &mut rng);
+        for i in 0..4 {
+            assert!(child.genes[i] == first.genes[i] || child.genes[i] == second.genes[i]);
+        }
+
+        // Arithmetic crossover is between the parents
+        ind_data.crossover_type = RealCrossoverType::Arithmetic;
+        let child = first.crossover(&second, &ind_data, &mut rng);
+        assert!(child.genes[0] >= -1.0 && child.genes[0] <= 1.0);
+        assert_eq!(child.genes[1], 2.0);
+
+        // BLX-α is within the extended range of the parents
+        ind_data.crossover_type = RealCrossoverType::Blx;
+        ind_data.blx_alpha = 0.5;
+        let child = first.crossover(&second, &ind_data, &mut rng);
+        assert!(child.genes[2] >= 2.0 && child.genes[2] <= 6.0);
+        assert_eq!(child.genes[1], 2.0);
+
+        // SBX of identical genes keeps the gene
+        ind_data.crossover_type = RealCrossoverType::Sbx;
+        let child = first.crossover(&first, &ind_data, &mut rng);
+        assert_eq!(child.genes, first.genes);
+
+        // Crossover written into an individual gives the same child as the new one
+        for crossover_type in RealCrossoverType::iter() {
+            ind_data.crossover_type = crossover_type;
+            let mut dest = RealVectorIndividual::new(vec![0.0; 6]);
+            dest.fitness = 1.0;
+            let mut rng_into = rng.clone();
+            let child = first.crossover(&second, &ind_data, &mut rng);
+            first.crossover_into(&second, &ind_data, &mut rng_into, &mut dest);
+            assert_eq!(dest.genes, child.genes);
+            assert_eq!(dest.fitness, 0.0);
+        }
+    }
+
+    #[test]
+    fn test_mutation_probability() {
+        let mut rng = SmallRng::seed_from_u64(3);
+        let ind_data = RealVectorData::new(10, -10.0, 10.0, sphere);
+
+        // Nothing changes with zero probability of mutation
+        let mut ind = RealVectorIndividual::new(vec![0.5; 10]);
+        ind.mutate(&ind_data, &mut rng, 0.0, 1.0);
+        assert_eq!(ind.genes, vec![0.5; 10]);
+
+        ind.mutate(&ind_data, &mut rng, 1.0, 1.0);
+        assert!(ind.genes.iter().all(|gene| *gene != 0.5));
+    }
+
+    #[test]
+    fn test_population() {
+        let config = Config::from_str(
+            "{\"pop_width\": 16, \"pop_height\": 16, \"mut_prob\": 0.2, \"mut_amount\": 0.1, \"crossover_prob\": 0.2, \"seed\": 4, \"n_dims\": 3, \"real_crossover\": \"blx\"}",
+        )
+        .unwrap();
+        let ind_data = RealVectorData::from_config(&config, sphere).unwrap();
+        assert_eq!(ind_data.bounds.len(), 3);
+        assert_eq!(ind_data.crossover_type, RealCrossoverType::Blx);
+
+        // Inverted bounds are refused
+        let inverted = Config::from_str("{\"lower_bound\": 1.0, \"upper_bound\": -1.0}").unwrap();
+        assert!(RealVectorData::from_config(&inverted, sphere).is_err());
+
+        let mut pop: Population<RealVectorIndividual, RealVectorData> =
+            Population::new_with_data(&config, ind_data);
+
+        let initial_best = pop.get_best().get_fitness();
+        for _ in 0..50 {
+            pop.next_gen();
+        }
+
+        // Objective is evaluated by the individuals and improves
+        let best = pop.get_best();
+        assert_eq!(best.get_fitness(), sphere(best.get_genes()));
+        assert!(best.get_fitness() >= initial_best);
+        assert!(best.get_fitness() > -0.1);
+    }
+}
@@ -0,0 +1,404 @@
+use crate::config::Config;
+use crate::evo_individual::EvoIndividual;
+use crate::population::Population;
+use serde_json::{json, Value};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use strum_macros::{Display, EnumIter, EnumString};
+
+const DEFAULT_LOG_FORMAT: LogFormat = LogFormat::Csv;
+
+const CSV_HEADER: &str = "generation,best_fitness,worst_fitness,mean_fitness,median_fitness,\
+std_fitness,n_invalid,mut_prob,mut_amount,crossover_prob,selection_strategy,elapsed_s,evaluations,events";
+
+#[derive(Clone, Copy, PartialEq, Debug, EnumString, EnumIter, Display)]
+pub enum LogFormat {
+    #[strum(serialize = "csv")]
+    Csv,
+    #[strum(serialize = "jsonl")]
+    Jsonl,
+}
+
+impl LogFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            LogFormat::Csv => "csv",
+            LogFormat::Jsonl => "jsonl",
+        }
+    }
+}
+
+// Logger writes one record per generation with the statistics and parameters of the population
+pub struct RunLogger {
+    writer: BufWriter<File>,
+    format: LogFormat,
+
+    // Time of the creation of the logger, elapsed time of the records is measured from it
+    start: Instant,
+}
+
+impl RunLogger {
+    pub fn new(path: &str, format: LogFormat) -> Result<Self, String> {
+        let file = File::create(path)
+            .map_err(|err| format!("Creating log file '{}' failed: {}", path, err))?;
+        let mut writer = BufWriter::new(file);
+
+        if format == LogFormat::Csv {
+            writeln!(writer, "{}", CSV_HEADER)
+                .map_err(|err| format!("Writing log header failed: {}", err))?;
+        }
+
+        Ok(RunLogger {
+            writer,
+            format,
+            start: Instant::now(),
+        })
+    }
+
+    // Function creates a logger that appends the records to an existing log, e.g. when a run is resumed
+    // The header is written only if the file is new or empty
+    pub fn append(path: &str, format: LogFormat) -> Result<Self, String> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|err| format!("Opening log file '{}' failed: {}", path, err))?;
+        let is_empty = file
+            .metadata()
+            .map_err(|err| format!("Reading log file '{}' failed: {}", path, err))?
+            .len()
+            == 0;
+        let mut writer = BufWriter::new(file);
+
+        if format == LogFormat::Csv && is_empty {
+            writeln!(writer, "{}", CSV_HEADER)
+                .map_err(|err| format!("Writing log header failed: {}", err))?;
+        }
+
+        Ok(RunLogger {
+            writer,
+            format,
+            start: Instant::now(),
+        })
+    }
+
+    // Function returns the generation of the last record of the log, or None if the log has no records
+    pub fn read_last_generation(path: &str, format: LogFormat) -> Result<Option<usize>, String> {
+        let data = std::fs::read_to_string(path)
+            .map_err(|err| format!("Reading log file '{}' failed: {}", path, err))?;
+
+        let last_line = match data.lines().rev().find(|line| !line.trim().is_empty()) {
+            Some(line) if !(format == LogFormat::Csv && line == CSV_HEADER) => line,
+            _ => return Ok(None),
+        };
+
+        let generation = match format {
+            LogFormat::Csv => last_line.split(',').next().and_then(|v| v.parse().ok()),
+            LogFormat::Jsonl => serde_json::from_str::<Value>(last_line)
+                .ok()
+                .and_then(|record| record["generation"].as_u64())
+                .map(|generation| generation as usize),
+        };
+
+        generation
+            .map(Some)
+            .ok_or(format!("Last record of log file '{}' is malformed", path))
+    }
+
+    // Function creates a logger with the format from the "log_format" config value
+    // The file extension matching the format is appended to the path stem
+    pub fn from_config(config: &Config, path_stem: &str) -> Result<Self, String> {
+        let (path, format) = Self::_config_path(config, path_stem)?;
+        Self::new(&path, format)
+    }
+
+    // Function works like from_config, the records are appended to the existing log
+    // The generation of the last record of the log is returned as well, the log must exist
+    pub fn append_from_config(
+        config: &Config,
+        path_stem: &str,
+    ) -> Result<(Self, Option<usize>), String> {
+        let (path, format) = Self::_config_path(config, path_stem)?;
+        let last_generation = Self::read_last_generation(&path, format)?;
+        Ok((Self::append(&path, format)?, last_generation))
+    }
+
+    // Function writes the record of the current generation of the population
+    // Restarts and immigrations of the generation are written as "type:cells" items, separated by ';' in CSV
+    // The record is flushed right away, so the log can be read while the run is in progress
+    pub fn log<Individual, IndividualData>(
+        &mut self,
+        pop: &Population<Individual, IndividualData>,
+    ) -> Result<(), String>
+    where
+        Individual: EvoIndividual<IndividualData>,
+        IndividualData: Send + Sync,
+    {
+        let elapsed_s = self.start.elapsed().as_secs_f64();
+
+        let result = match self.format {
+            LogFormat::Csv => {
+                let stats = pop.get_stats();
+                writeln!(
+                    self.writer,
+                    "{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                    pop.get_generation(),
+                    stats.best_fitness,
+                    stats.worst_fitness,
+                    stats.mean_fitness,
+                    stats.median_fitness,
+                    stats.std_fitness,
+                    stats.n_invalid,
+                    pop.get_mut_prob(),
+                    pop.get_mut_amount(),
+                    pop.get_crossover_prob(),
+                    pop.get_selection_strategy(),
+                    elapsed_s,
+                    pop.get_evaluations(),
+                    Self::_generation_events(pop).join(";")
+                )
+            }
+            LogFormat::Jsonl => writeln!(self.writer, "{}", Self::json_record(pop, elapsed_s)),
+        };
+
+        result
+            .and_then(|_| self.writer.flush())
+            .map_err(|err| format!("Writing log record failed: {}", err))
+    }
+
+    // Function returns the record of the current generation as written to the JSONL log, NaN values are null
+    pub(crate) fn json_record<Individual, IndividualData>(
+        pop: &Population<Individual, IndividualData>,
+        elapsed_s: f64,
+    ) -> Value
+    where
+        Individual: EvoIndividual<IndividualData>,
+        IndividualData: Send + Sync,
+    {
+        let stats = pop.get_stats();
+        let events = Self::_generation_events(pop);
+
+        json!({
+            "generation": pop.get_generation(),
+            "best_fitness": Self::_json_float(stats.best_fitness),
+            "worst_fitness": Self::_json_float(stats.worst_fitness),
+            "mean_fitness": Self::_json_float(stats.mean_fitness),
+            "median_fitness": Self::_json_float(stats.median_fitness),
+            "std_fitness": Self::_json_float(stats.std_fitness),
+            "n_invalid": stats.n_invalid,
+            "mut_prob": pop.get_mut_prob(),
+            "mut_amount": pop.get_mut_amount(),
+            "crossover_prob": pop.get_crossover_prob(),
+            "selection_strategy": pop.get_selection_strategy().to_string(),
+            "elapsed_s": elapsed_s,
+            "evaluations": pop.get_evaluations(),
+            "events": events,
+        })
+    }
+
+    // Function writes the manifest of the run as a JSON file
+    // It contains the config, the effective parameters of the population and the seed
+    pub fn write_manifest<Individual, IndividualData>(
+        path: &str,
+        config: &Config,
+        pop: &Population<Individual, IndividualData>,
+    ) -> Result<(), String>
+    where
+        Individual: EvoIndividual<IndividualData>,
+        IndividualData: Send + Sync,
+    {
+        let started_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+
+        let manifest = json!({
+            "revo_version": env!("CARGO_PKG_VERSION"),
+            "started_at": started_at,
+            "seed": pop.get_seed(),
+            "population": {
+                "pop_width": pop.get_width(),
+                "pop_height": pop.get_height(),
+                "mut_prob": pop.get_mut_prob(),
+                "mut_amount": pop.get_mut_amount(),
+                "crossover_prob": pop.get_crossover_prob(),
+                "selection_strategy": pop.get_selection_strategy().to_string(),
+                "boundary": pop.get_boundary().to_string(),
+                "neighbourhood": pop.get_neighbourhood().to_string(),
+                "niching": pop.get_niching().to_string(),
+                "restart": pop.get_restart_type().to_string(),
+            },
+            "config": config.json,
+        });
+
+        let data = serde_json::to_string_pretty(&manifest)
+            .map_err(|err| format!("Serialising manifest failed: {}", err))?;
+        std::fs::write(path, data)
+            .map_err(|err| format!("Writing manifest '{}' failed: {}", path, err))
+    }
+
+    // Function returns the restarts and immigrations of the current generation as "type:cells" items
+    fn _generation_events<Individual, IndividualData>(
+        pop: &Population<Individual, IndividualData>,
+    ) -> Vec<String>
+    where
+        Individual: EvoIndividual<IndividualData>,
+        IndividualData: Send + Sync,
+    {
+        pop.get_events()
+            .iter()
+            .filter(|event| event.generation == pop.get_generation())
+            .map(|event| event.to_string())
+            .collect()
+    }
+
+    fn _config_path(config: &Config, path_stem: &str) -> Result<(String, LogFormat), String> {
+        let format: LogFormat = config
+            .may_get_enum("log_format")?
+            .unwrap_or(DEFAULT_LOG_FORMAT);
+
+        Ok((format!("{}.{}", path_stem, format.extension()), format))
+    }
+
+    // Function converts the float to JSON value, non-finite values are written as null
+    fn _json_float(value: f64) -> Value {
+        if value.is_finite() {
+            json!(value)
+        } else {
+            Value::Null
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockIndividual, MockIndividualData};
+    use std::fs;
+    use std::str::FromStr;
+
+    fn test_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("revo_run_logger_{}_{}", std::process::id(), name))
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    fn test_population(config: &Config) -> Population<MockIndividual, MockIndividualData> {
+        Population::new(config)
+    }
+
+    #[test]
+    fn test_csv() {
+        let config = Config::from_str("{\"pop_width\": 2, \"pop_height\": 2}").unwrap();
+        let mut pop = test_population(&config);
+
+        let path = test_path("log.csv");
+        let mut logger = RunLogger::new(&path, LogFormat::Csv).unwrap();
+        logger.log(&pop).unwrap();
+        pop.next_gen();
+        logger.log(&pop).unwrap();
+
+        let data = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let lines: Vec<&str> = data.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], CSV_HEADER);
+
+        // Every record has all of the columns
+        let columns: Vec<&str> = lines[2].split(',').collect();
+        assert_eq!(columns.len(), CSV_HEADER.split(',').count());
+        assert_eq!(columns[0], "1");
+        assert_eq!(columns[6], "0");
+        assert_eq!(columns[10], "tournament");
+        assert_eq!(columns[12], "8");
+        assert_eq!(columns[13], "");
+    }
+
+    #[test]
+    fn test_append() {
+        let config = Config::from_str("{\"pop_width\": 2, \"pop_height\": 2}").unwrap();
+        let mut pop = test_population(&config);
+
+        for format in [LogFormat::Csv, LogFormat::Jsonl] {
+            let path = test_path(&format!("append.{}", format.extension()));
+            let _ = fs::remove_file(&path);
+
+            // Log without records has no last generation
+            RunLogger::append(&path, format).unwrap();
+            assert_eq!(
+                RunLogger::read_last_generation(&path, format).unwrap(),
+                None
+            );
+
+            let mut logger = RunLogger::append(&path, format).unwrap();
+            logger.log(&pop).unwrap();
+            drop(logger);
+
+            // Appended records follow the existing ones, the header is written only once
+            pop.next_gen();
+            let mut logger = RunLogger::append(&path, format).unwrap();
+            logger.log(&pop).unwrap();
+            drop(logger);
+
+            let data = fs::read_to_string(&path).unwrap();
+            let generation = RunLogger::read_last_generation(&path, format).unwrap();
+            fs::remove_file(&path).unwrap();
+
+            assert_eq!(generation, Some(pop.get_generation()));
+            let expected_lines = if format == LogFormat::Csv { 3 } else { 2 };
+            assert_eq!(data.lines().count(), expected_lines);
+        }
+    }
+
+    #[test]
+    fn test_jsonl_and_manifest() {
+        let config = Config::from_str(
+            "{\"pop_width\": 2, \"pop_height\": 3, \"seed\": 7, \"log_format\": \"jsonl\"}",
+        )
+        .unwrap();
+        let pop = test_population(&config);
+
+        let path_stem = test_path("log");
+        let mut logger = RunLogger::from_config(&config, &path_stem).unwrap();
+        logger.log(&pop).unwrap();
+
+        let data = fs::read_to_string(format!("{}.jsonl", path_stem)).unwrap();
+
+        // Appending logger continues the same log
+        let (mut logger, last_generation) =
+            RunLogger::append_from_config(&config, &path_stem).unwrap();
+        assert_eq!(last_generation, Some(0));
+        logger.log(&pop).unwrap();
+        drop(logger);
+        let n_records = fs::read_to_string(format!("{}.jsonl", path_stem))
+            .unwrap()
+            .lines()
+            .count();
+        assert_eq!(n_records, 2);
+        fs::remove_file(format!("{}.jsonl", path_stem)).unwrap();
+
+        let record: Value = serde_json::from_str(data.lines().next().unwrap()).unwrap();
+        assert_eq!(record["generation"], 0);
+        assert_eq!(record["best_fitness"], 0.0);
+        assert_eq!(record["evaluations"], 6);
+        assert_eq!(record["events"], json!([]));
+
+        let manifest_path = test_path("manifest.json");
+        RunLogger::write_manifest(&manifest_path, &config, &pop).unwrap();
+        let manifest: Value =
+            serde_json::from_str(&fs::read_to_string(&manifest_path).unwrap()).unwrap();
+        fs::remove_file(&manifest_path).unwrap();
+
+        assert_eq!(manifest["seed"], 7);
+        assert_eq!(manifest["population"]["pop_height"], 3);
+        assert_eq!(manifest["population"]["boundary"], "torus");
+        assert_eq!(manifest["population"]["neighbourhood"], "l5");
+        assert_eq!(manifest["population"]["niching"], "none");
+        assert_eq!(manifest["population"]["restart"], "none");
+        assert_eq!(manifest["config"], config.json);
+    }
+}
@@ -0,0 +1,513 @@
+use crate::config::Config;
+use crate::evo_individual::{EvoIndividual, EvoIndividualData};
+use crate::experiment::{summary_table, Experiment, ExperimentResult};
+use crate::utils::cmp_fitness;
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde_json::Value;
+use std::fmt::Write as FmtWrite;
+use std::str::FromStr;
+use strum_macros::{Display, EnumIter, EnumString};
+
+const SWEEP_KEY: &str = "sweep";
+const DEFAULT_SWEEP_DESIGN: SweepDesign = SweepDesign::Factorial;
+const DEFAULT_N_SAMPLES: usize = 10;
+const DEFAULT_RANGE_STEPS: usize = 5;
+
+#[derive(Clone, Copy, PartialEq, Debug, EnumString, EnumIter, Display)]
+pub enum SweepDesign {
+    // Every combination of the values of all parameters
+    #[strum(serialize = "factorial")]
+    Factorial,
+    // n_samples settings, each parameter is sampled once from each of n_samples equal strata of its values
+    #[strum(serialize = "latin_hypercube")]
+    LatinHypercube,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SweepValues {
+    // Explicit list of values of any type
+    List(Vec<Value>),
+    // Numeric range, the factorial design uses steps evenly spaced values including min and max
+    // Integer ranges are used for integer keys like pop_width
+    Range {
+        min: f64,
+        max: f64,
+        steps: usize,
+        integer: bool,
+    },
+}
+
+impl SweepValues {
+    fn _from_json(key: &str, json: &Value) -> Result<Self, String> {
+        if let Some(list) = json.as_array() {
+            if list.is_empty() {
+                return Err(format!("Sweep values of '{}' are empty", key));
+            }
+            return Ok(SweepValues::List(list.clone()));
+        }
+
+        let get_number = |name: &str| {
+            json.get(name)
+                .and_then(|v| v.as_f64())
+                .ok_or(format!("Sweep range of '{}' has no number '{}'", key, name))
+        };
+
+        let min = get_number("min")?;
+        let max = get_number("max")?;
+        let integer = json["min"].is_i64() && json["max"].is_i64();
+        let steps = match json.get("steps") {
+            Some(steps) => steps.as_u64().ok_or(format!(
+                "Sweep steps of '{}' are not a positive integer",
+                key
+            ))? as usize,
+            None => DEFAULT_RANGE_STEPS,
+        };
+
+        if steps == 0 || min > max {
+            return Err(format!("Sweep range of '{}' is empty", key));
+        }
+
+        Ok(SweepValues::Range {
+            min,
+            max,
+            steps,
+            integer,
+        })
+    }
+
+    // Function returns the value of the range at the position from 0.0 to 1.0
+    fn _range_value(min: f64, max: f64, integer: bool, position: f64) -> Value {
+        let value = min + (max - min) * position;
+        if integer {
+            Value::from(value.round() as i64)
+        } else {
+            Value::from(value)
+        }
+    }
+
+    // Function returns the values used by the factorial design
+    pub fn get_grid(&self) -> Vec<Value> {
+        match self {
+            SweepValues::List(values) => values.clone(),
+            SweepValues::Range {
+                min,
+                max,
+                steps,
+                integer,
+            } => {
+                let mut values: Vec<Value> = (0..*steps)
+                    .map(|i| {
+                        let position = if *steps > 1 {
+                            i as f64 / (*steps - 1) as f64
+                        } else {
+                            0.0
+                        };
+                        Self::_range_value(*min, *max, *integer, position)
+                    })
+                    .collect();
+                // Rounded integer values may repeat
+                values.dedup();
+                values
+            }
+        }
+    }
+
+    // Function returns a random value from the stratum out of n_strata equal strata
+    fn _sample_stratum(&self, stratum: usize, n_strata: usize, rng: &mut SmallRng) -> Value {
+        let position = (stratum as f64 + rng.gen_range(0.0..1.0)) / n_strata as f64;
+
+        match self {
+            SweepValues::List(values) => {
+                let i = ((position * values.len() as f64) as usize).min(values.len() - 1);
+                values[i].clone()
+            }
+            SweepValues::Range {
+                min, max, integer, ..
+            } => Self::_range_value(*min, *max, *integer, position),
+        }
+    }
+}
+
+// Sweep expands the base config to configs with different values of the swept parameters
+pub struct Sweep {
+    base_config: Config,
+    params: Vec<(String, SweepValues)>,
+    design: SweepDesign,
+    n_samples: usize,
+    seed: u64,
+}
+
+impl Sweep {
+    pub fn new(base_config: &Config, design: SweepDesign) -> Self {
+        Sweep {
+            base_config: base_config.clone(),
+            params: Vec::new(),
+            design,
+            n_samples: DEFAULT_N_SAMPLES,
+            seed: base_config
+                .may_get_uint("seed")
+                .unwrap()
+                .unwrap_or_else(|| SmallRng::from_entropy().gen()),
+        }
+    }
+
+    // Function creates the sweep from the "sweep" object of the config, the rest of the config is the base config
+    // The object contains the "design", "n_samples" of the Latin hypercube and "params" with a list of values or
+    // a range {"min", "max", "steps"} for every swept key
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let spec = config
+            .json
+            .get(SWEEP_KEY)
+            .ok_or(format!("Value for key '{}' not found", SWEEP_KEY))?
+            .clone();
+        let spec_config = Config { json: spec };
+
+        let mut base_config = config.clone();
+        if let Some(object) = base_config.json.as_object_mut() {
+            object.remove(SWEEP_KEY);
+        }
+
+        let mut sweep = Self::new(
+            &base_config,
+            spec_config
+                .may_get_enum("design")?
+                .unwrap_or(DEFAULT_SWEEP_DESIGN),
+        );
+        sweep.n_samples = spec_config
+            .may_get_uint("n_samples")?
+            .unwrap_or(DEFAULT_N_SAMPLES);
+
+        let params = spec_config
+            .json
+            .get("params")
+            .and_then(|params| params.as_object())
+            .ok_or("Sweep has no 'params' object")?;
+        for (key, json) in params {
+            sweep.add_param(key, SweepValues::_from_json(key, json)?);
+        }
+
+        Ok(sweep)
+    }
+
+    pub fn add_param(&mut self, key: &str, values: SweepValues) {
+        self.params.push((key.to_string(), values));
+    }
+
+    pub fn set_n_samples(&mut self, n_samples: usize) {
+        self.n_samples = n_samples;
+    }
+
+    // Function returns the values of the swept parameters of every setting
+    pub fn get_settings(&self) -> Vec<Vec<(String, Value)>> {
+        match self.design {
+            SweepDesign::Factorial => self._factorial_settings(),
+            SweepDesign::LatinHypercube => self._latin_hypercube_settings(),
+        }
+    }
+
+    fn _factorial_settings(&self) -> Vec<Vec<(String, Value)>> {
+        let mut settings: Vec<Vec<(String, Value)>> = vec![Vec::new()];
+
+        for (key, values) in &self.params {
+            settings = settings
+                .iter()
+                .flat_map(|setting| {
+                    values.get_grid().into_iter().map(move |value| {
+                        let mut setting = setting.clone();
+                        setting.push((key.clone(), value));
+                        setting
+                    })
+                })
+                .collect();
+        }
+
+        settings
+    }
+
+    fn _latin_hypercube_settings(&self) -> Vec<Vec<(String, Value)>> {
+        let mut rng = SmallRng::seed_from_u64(self.seed);
+        let mut settings: Vec<Vec<(String, Value)>> = vec![Vec::new(); self.n_samples];
+
+        for (key, values) in &self.params {
+            let mut strata: Vec<usize> = (0..self.n_samples).collect();
+            strata.shuffle(&mut rng);
+
+            for (setting, stratum) in settings.iter_mut().zip(strata) {
+                let value = values._sample_stratum(stratum, self.n_samples, &mut rng);
+                setting.push((key.clone(), value));
+            }
+        }
+
+        settings
+    }
+
+    // Function expands the sweep to configs, one for every setting
+    pub fn expand(&self) -> Vec<Config> {
+        self.get_settings()
+            .iter()
+            .map(|setting| self._setting_config(setting))
+            .collect()
+    }
+
+    fn _setting_config(&self, setting: &[(String, Value)]) -> Config {
+        let mut config = self.base_config.clone();
+        for (key, value) in setting {
+            config.set(key, value.clone());
+        }
+        config
+    }
+
+    // Function runs the experiment for every setting, individual data are created from the config of the setting
+    pub fn run<Individual, IndividualData>(&self) -> SweepResult
+    where
+        Individual: EvoIndividual<IndividualData>,
+        IndividualData: EvoIndividualData,
+    {
+        self._run(|config| Experiment::new(config).run::<Individual, IndividualData>())
+    }
+
+    // Function runs the experiment for every setting with a copy of the given individual data
+    pub fn run_with_data<Individual, IndividualData>(
+        &self,
+        ind_data: &IndividualData,
+    ) -> SweepResult
+    where
+        Individual: EvoIndividual<IndividualData>,
+        IndividualData: Clone + Send + Sync,
+    {
+        self._run(|config| {
+            Experiment::new(config).run_with_data::<Individual, IndividualData>(ind_data)
+        })
+    }
+
+    fn _run<F>(&self, run_experiment: F) -> SweepResult
+    where
+        F: Fn(&Config) -> ExperimentResult,
+    {
+        let entries = self
+            .get_settings()
+            .into_iter()
+            .map(|setting| {
+                let result = run_experiment(&self._setting_config(&setting));
+                SweepEntry { setting, result }
+            })
+            .collect();
+
+        SweepResult {
+            keys: self.params.iter().map(|(key, _)| key.clone()).collect(),
+            entries,
+        }
+    }
+}
+
+pub struct SweepEntry {
+    pub setting: Vec<(String, Value)>,
+    pub result: ExperimentResult,
+}
+
+impl SweepEntry {
+    // Settings are ranked by the mean final best fitness of their runs
+    pub fn get_score(&self) -> f64 {
+        self.result.get_final_distribution().mean
+    }
+
+    pub fn get_name(&self) -> String {
+        self.setting
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, _value_to_string(value)))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+// Function writes string values without the quotes
+fn _value_to_string(value: &Value) -> String {
+    match value.as_str() {
+        Some(s) => s.to_string(),
+        None => value.to_string(),
+    }
+}
+
+pub struct SweepResult {
+    pub keys: Vec<String>,
+    pub entries: Vec<SweepEntry>,
+}
+
+impl SweepResult {
+    // Function returns the entries sorted from the best to the worst score, settings with NaN score are the last
+    pub fn get_ranked(&self) -> Vec<&SweepEntry> {
+        let mut ranked: Vec<&SweepEntry> = self.entries.iter().collect();
+        ranked.sort_by(|a, b| cmp_fitness(b.get_score(), a.get_score()));
+        ranked
+    }
+
+    pub fn get_best(&self) -> Option<&SweepEntry> {
+        self.get_ranked().first().copied()
+    }
+
+    // Function writes the ranked settings with the statistics of the final best fitness as CSV
+    pub fn write_csv(&self, path: &str) -> Result<(), String> {
+        let mut data = String::from("rank");
+        for key in &self.keys {
+            write!(data, ",{}", key).unwrap();
+        }
+        data.push_str(",runs,mean,std,min,q1,median,q3,max\n");
+
+        for (rank, entry) in self.get_ranked().iter().enumerate() {
+            write!(data, "{}", rank + 1).unwrap();
+            for (_, value) in &entry.setting {
+                write!(data, ",{}", _value_to_string(value)).unwrap();
+            }
+            let d = entry.result.get_final_distribution();
+            writeln!(
+                data,
+                ",{},{},{},{},{},{},{},{}",
+                entry.result.curves.len(),
+                d.mean,
+                d.std,
+                d.min,
+                d.q1,
+                d.median,
+                d.q3,
+                d.max
+            )
+            .unwrap();
+        }
+
+        std::fs::write(path, data).map_err(|err| format!("Writing file '{}' failed: {}", path, err))
+    }
+
+    // Function returns the summary table of the ranked settings
+    pub fn summary_table(&self) -> String {
+        let names: Vec<String> = self.get_ranked().iter().map(|e| e.get_name()).collect();
+        let rows: Vec<(&str, &ExperimentResult)> = self
+            .get_ranked()
+            .iter()
+            .zip(names.iter())
+            .map(|(entry, name)| (name.as_str(), &entry.result))
+            .collect();
+        summary_table(&rows)
+    }
+}
+
+impl FromStr for Sweep {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_config(&Config::from_str(s)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockIndividual, MockIndividualData};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_factorial() {
+        let sweep = Sweep::from_str(
+            "{\"pop_width\": 4, \"pop_height\": 4, \"sweep\": {\"params\": {\"mut_prob\": [0.1, 0.2], \"selection_strategy\": [\"roulette\", \"tournament\"], \"pop_width\": {\"min\": 4, \"max\": 8, \"steps\": 3}}}}",
+        )
+        .unwrap();
+
+        let configs = sweep.expand();
+        assert_eq!(configs.len(), 12);
+        assert!(configs.iter().all(|c| c.json.get(SWEEP_KEY).is_none()));
+
+        // Existing getters work with the swept values
+        let widths: HashSet<usize> = configs
+            .iter()
+            .map(|c| c.get_uint("pop_width").unwrap())
+            .collect();
+        assert_eq!(widths, HashSet::from([4, 6, 8]));
+        assert_eq!(configs[0].get_float::<f32>("mut_prob").unwrap(), 0.1);
+        assert_eq!(configs[0].get_uint::<usize>("pop_height").unwrap(), 4);
+
+        let ranges = SweepValues::Range {
+            min: 0.0,
+            max: 1.0,
+            steps: 3,
+            integer: false,
+        };
+        assert_eq!(
+            ranges.get_grid(),
+            vec![Value::from(0.0), Value::from(0.5), Value::from(1.0)]
+        );
+    }
+
+    #[test]
+    fn test_latin_hypercube() {
+        let sweep = Sweep::from_str(
+            "{\"seed\": 1, \"sweep\": {\"design\": \"latin_hypercube\", \"n_samples\": 5, \"params\": {\"mut_prob\": {\"min\": 0.0, \"max\": 1.0}, \"crossover_prob\": {\"min\": 0.0, \"max\": 0.5}}}}",
+        )
+        .unwrap();
+
+        let settings = sweep.get_settings();
+        assert_eq!(settings.len(), 5);
+
+        // Every stratum of every parameter is used exactly once
+        for (key, max) in [("mut_prob", 1.0), ("crossover_prob", 0.5)] {
+            let mut strata: Vec<usize> = settings
+                .iter()
+                .map(|s| {
+                    let (_, value) = s.iter().find(|(k, _)| k == key).unwrap();
+                    (value.as_f64().unwrap() / max * 5.0) as usize
+                })
+                .collect();
+            strata.sort();
+            assert_eq!(strata, vec![0, 1, 2, 3, 4]);
+        }
+
+        assert!(Sweep::from_str("{\"sweep\": {\"params\": {\"mut_prob\": []}}}").is_err());
+        assert!(Sweep::from_str("{\"mut_prob\": 0.1}").is_err());
+    }
+
+    #[test]
+    fn test_run() {
+        let sweep = Sweep::from_str(
+            "{\"pop_width\": 4, \"pop_height\": 4, \"n_runs\": 2, \"n_generations\": 3, \"seed\": 5, \"sweep\": {\"params\": {\"crossover_prob\": [0.0, 0.5]}}}",
+        )
+        .unwrap();
+
+        let result = sweep.run::<MockIndividual, MockIndividualData>();
+        assert_eq!(result.entries.len(), 2);
+        assert!(result.entries.iter().all(|e| e.result.curves.len() == 2));
+        assert_eq!(result.get_ranked().len(), 2);
+        assert!(result.summary_table().contains("crossover_prob=0.5"));
+
+        let path = std::env::temp_dir()
+            .join(format!("revo_sweep_{}.csv", std::process::id()))
+            .to_str()
+            .unwrap()
+            .to_string();
+        result.write_csv(&path).unwrap();
+        let data = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(data.starts_with("rank,crossover_prob,runs,mean"));
+        assert_eq!(data.lines().count(), 3);
+    }
+
+    #[test]
+    fn test_ranked_nan() {
+        let entry = |value: f64, score: f64| SweepEntry {
+            setting: vec![("mut_prob".to_string(), Value::from(value))],
+            result: ExperimentResult {
+                seeds: vec![0],
+                curves: vec![vec![score]],
+            },
+        };
+        let result = SweepResult {
+            keys: vec!["mut_prob".to_string()],
+            entries: vec![entry(0.1, 1.0), entry(0.2, f64::NAN), entry(0.3, 2.0)],
+        };
+
+        // Setting with NaN score is ranked the worst
+        let ranked: Vec<String> = result.get_ranked().iter().map(|e| e.get_name()).collect();
+        assert_eq!(ranked, vec!["mut_prob=0.3", "mut_prob=0.1", "mut_prob=0.2"]);
+        assert_eq!(result.get_best().unwrap().get_name(), "mut_prob=0.3");
+    }
+}
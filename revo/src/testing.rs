@@ -1,6 +1,7 @@
 use crate::config::Config;
-use crate::evo_individual::{EvoIndividual, EvoIndividualData};
+use crate::evo_individual::{EvoIndividual, EvoIndividualData, RemoteIndividual};
 use rand::rngs::SmallRng;
+use serde_json::{json, Value};
 
 #[derive(Clone)]
 #[allow(dead_code)]
@@ -64,3 +65,30 @@ impl EvoIndividual<MockIndividualData> for MockIndividual {
         self.visuals
     }
 }
+
+impl RemoteIndividual<MockIndividualData> for MockIndividual {
+    fn genome_to_json(&self) -> Value {
+        json!({"value": self.value, "visuals": [self.visuals.0, self.visuals.1]})
+    }
+
+    fn from_genome_json(genome: &Value, _ind_data: &MockIndividualData) -> Result<Self, String> {
+        let number = |value: &Value| {
+            value
+                .as_f64()
+                .ok_or_else(|| format!("Genome '{}' is not a mock genome", genome))
+        };
+
+        Ok(MockIndividual {
+            fitness: 0.0,
+            visuals: (
+                number(&genome["visuals"][0])?,
+                number(&genome["visuals"][1])?,
+            ),
+            value: number(&genome["value"])?,
+        })
+    }
+
+    fn set_fitness(&mut self, fitness: f64) {
+        self.fitness = fitness;
+    }
+}
@@ -1,3 +1,5 @@
+use std::cmp::Ordering;
+
 #[derive(Debug, Clone, Copy)]
 pub struct Coord {
     pub x: i32,
@@ -57,6 +59,52 @@ impl IndexedLabData {
     }
 }
 
+// Function compares two fitness values by a total order in which NaN is worse than any other value, including -inf
+// Equal values, including 0.0 and -0.0 or two NaNs, are equal
+pub fn cmp_fitness(a: f64, b: f64) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => a.partial_cmp(&b).unwrap(),
+    }
+}
+
+// Individual with NaN or infinite fitness is invalid, it is excluded from the fitness statistics
+pub fn is_valid_fitness(fitness: f64) -> bool {
+    fitness.is_finite()
+}
+
+// Function replaces the component of the data by its rank linearly mapped between min_val and max_val
+// Values closer than 1e-9 get the same normalised value, the data are left sorted by the component with NaN first
+pub fn normalize_component(
+    data: &mut [IndexedLabData],
+    mut get_component: impl FnMut(&LabData) -> f64,
+    mut set_component: impl FnMut(&mut LabData, f64),
+    min_val: f64,
+    max_val: f64,
+) {
+    let len = data.len();
+    let eps = 1e-9;
+
+    data.sort_by(|a, b| cmp_fitness(get_component(&a.data), get_component(&b.data)));
+    let mut last_val = get_component(&data.last().unwrap().data);
+    let mut last_val_normalised = 0.0;
+
+    for (i, value) in data.iter_mut().enumerate() {
+        let current_val = get_component(&value.data);
+
+        if (current_val - last_val).abs() < eps {
+            set_component(&mut value.data, last_val_normalised);
+        } else {
+            last_val = current_val;
+            let normalised_val = ((i as f64) * (max_val - min_val)) / len as f64 + min_val;
+            set_component(&mut value.data, normalised_val);
+            last_val_normalised = normalised_val;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -118,4 +166,41 @@ mod tests {
         assert!((x - 1.0 / (2.0f64).sqrt()).abs() < eps);
         assert!((y - 1.0 / (2.0f64).sqrt()).abs() < eps);
     }
+
+    #[test]
+    fn test_normalize_component() {
+        let mut data: Vec<IndexedLabData> = [3.0, 1.0, 1.0, 2.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &l)| IndexedLabData::new(l, 0.0, 0.0, i))
+            .collect();
+
+        // Same values get the same rank
+        normalize_component(&mut data, |d| d.l, |d, val| d.l = val, 10.0, 90.0);
+        let normalised: Vec<(usize, f64)> = data.iter().map(|d| (d.index, d.data.l)).collect();
+        assert_eq!(normalised, vec![(1, 10.0), (2, 10.0), (3, 50.0), (0, 70.0)]);
+
+        // NaN is ranked below all the other values
+        let mut data: Vec<IndexedLabData> = [1.0, f64::NAN, f64::NEG_INFINITY]
+            .iter()
+            .enumerate()
+            .map(|(i, &l)| IndexedLabData::new(l, 0.0, 0.0, i))
+            .collect();
+        normalize_component(&mut data, |d| d.l, |d, val| d.l = val, 0.0, 3.0);
+        let normalised: Vec<(usize, f64)> = data.iter().map(|d| (d.index, d.data.l)).collect();
+        assert_eq!(normalised, vec![(1, 0.0), (2, 1.0), (0, 2.0)]);
+    }
+
+    #[test]
+    fn test_cmp_fitness() {
+        assert_eq!(cmp_fitness(f64::NAN, f64::NEG_INFINITY), Ordering::Less);
+        assert_eq!(cmp_fitness(1.0, f64::NAN), Ordering::Greater);
+        assert_eq!(cmp_fitness(-f64::NAN, f64::NAN), Ordering::Equal);
+        assert_eq!(cmp_fitness(f64::INFINITY, 1.0), Ordering::Greater);
+        assert_eq!(cmp_fitness(0.0, -0.0), Ordering::Equal);
+
+        assert!(is_valid_fitness(-1.0));
+        assert!(!is_valid_fitness(f64::NAN));
+        assert!(!is_valid_fitness(f64::NEG_INFINITY));
+    }
 }
@@ -0,0 +1,150 @@
+use rand::rngs::SmallRng;
+use rand::Rng;
+use strum_macros::{Display, EnumIter, EnumString};
+
+// Crossovers shared by the built-in genomes that are plain vectors of genes
+#[derive(Clone, Copy, PartialEq, Debug, EnumString, EnumIter, Display)]
+pub enum VectorCrossoverType {
+    // Genes before a random point are taken from the first parent, the rest from the second one
+    #[strum(serialize = "one_point")]
+    OnePoint,
+    // Genes between two random points are taken from the second parent, the rest from the first one
+    #[strum(serialize = "two_point")]
+    TwoPoint,
+    // Each gene is taken from one of the parents
+    #[strum(serialize = "uniform")]
+    Uniform,
+}
+
+impl VectorCrossoverType {
+    // Function returns the genes of the child of the two parents of the same length
+    pub fn apply<T: Clone>(&self, first: &[T], second: &[T], rng: &mut SmallRng) -> Vec<T> {
+        let mut child = Vec::with_capacity(first.len());
+        self.apply_into(first, second, rng, &mut child);
+        child
+    }
+
+    // Function writes the genes of the child into dest, so its allocation is reused
+    pub fn apply_into<T: Clone>(
+        &self,
+        first: &[T],
+        second: &[T],
+        rng: &mut SmallRng,
+        dest: &mut Vec<T>,
+    ) {
+        match self {
+            VectorCrossoverType::OnePoint => one_point_crossover_into(first, second, rng, dest),
+            VectorCrossoverType::TwoPoint => two_point_crossover_into(first, second, rng, dest),
+            VectorCrossoverType::Uniform => uniform_crossover_into(first, second, rng, dest),
+        }
+    }
+}
+
+pub fn one_point_crossover<T: Clone>(first: &[T], second: &[T], rng: &mut SmallRng) -> Vec<T> {
+    let mut child = Vec::with_capacity(first.len());
+    one_point_crossover_into(first, second, rng, &mut child);
+    child
+}
+
+pub fn one_point_crossover_into<T: Clone>(
+    first: &[T],
+    second: &[T],
+    rng: &mut SmallRng,
+    dest: &mut Vec<T>,
+) {
+    let point = rng.gen_range(0..=first.len());
+
+    dest.clear();
+    dest.extend_from_slice(&first[..point]);
+    dest.extend_from_slice(&second[point..]);
+}
+
+pub fn two_point_crossover<T: Clone>(first: &[T], second: &[T], rng: &mut SmallRng) -> Vec<T> {
+    let mut child = Vec::with_capacity(first.len());
+    two_point_crossover_into(first, second, rng, &mut child);
+    child
+}
+
+pub fn two_point_crossover_into<T: Clone>(
+    first: &[T],
+    second: &[T],
+    rng: &mut SmallRng,
+    dest: &mut Vec<T>,
+) {
+    let mut from = rng.gen_range(0..=first.len());
+    let mut to = rng.gen_range(0..=first.len());
+    if from > to {
+        std::mem::swap(&mut from, &mut to);
+    }
+
+    dest.clear();
+    dest.extend_from_slice(&first[..from]);
+    dest.extend_from_slice(&second[from..to]);
+    dest.extend_from_slice(&first[to..]);
+}
+
+pub fn uniform_crossover<T: Clone>(first: &[T], second: &[T], rng: &mut SmallRng) -> Vec<T> {
+    let mut child = Vec::with_capacity(first.len());
+    uniform_crossover_into(first, second, rng, &mut child);
+    child
+}
+
+pub fn uniform_crossover_into<T: Clone>(
+    first: &[T],
+    second: &[T],
+    rng: &mut SmallRng,
+    dest: &mut Vec<T>,
+) {
+    dest.clear();
+    dest.extend(first.iter().zip(second.iter()).map(|(a, b)| {
+        if rng.gen_bool(0.5) {
+            a.clone()
+        } else {
+            b.clone()
+        }
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn test_crossovers() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let first = vec![0; 20];
+        let second = vec![1; 20];
+
+        for _ in 0..100 {
+            // One-point child is a prefix of the first parent and suffix of the second one
+            let child = one_point_crossover(&first, &second, &mut rng);
+            assert_eq!(child.len(), 20);
+            assert!(child.windows(2).all(|w| w[0] <= w[1]));
+
+            // Two-point child has at most one block from the second parent
+            let child = two_point_crossover(&first, &second, &mut rng);
+            assert_eq!(child.len(), 20);
+            assert!(child.windows(2).filter(|w| w[0] != w[1]).count() <= 2);
+
+            for crossover_type in VectorCrossoverType::iter() {
+                let child = crossover_type.apply(&first, &second, &mut rng);
+                assert_eq!(child.len(), 20);
+            }
+        }
+
+        // Crossover written into a longer vector gives the same child as the new one
+        let mut dest = vec![2; 30];
+        for crossover_type in VectorCrossoverType::iter() {
+            let mut rng_into = rng.clone();
+            let child = crossover_type.apply(&first, &second, &mut rng);
+            crossover_type.apply_into(&first, &second, &mut rng_into, &mut dest);
+            assert_eq!(dest, child);
+        }
+
+        // Crossover of the same parents gives the same genes
+        let child = uniform_crossover(&second, &second, &mut rng);
+        assert_eq!(child, second);
+    }
+}
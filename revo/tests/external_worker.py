@@ -0,0 +1,32 @@
+#!/usr/bin/env python3
+# Reference worker of the external evaluator of revo
+# It reads one request per line: {"id": <int>, "genomes": [<genome>, ...]}
+# and answers one line for each of them: {"id": <int>, "fitness": [<number or null>, ...]}
+# Fitness of a genome given as a list of numbers is the negative sum of their squares
+# Genomes used by the tests: "invalid" gets null fitness, "crash" makes the worker exit, "hang" makes it stop
+# answering and {"crash_once": <path>} makes it exit only if the file at the path doesn't exist yet
+import json
+import os
+import sys
+import time
+
+
+def fitness(genome):
+    if genome == "invalid":
+        return None
+    if genome == "crash":
+        sys.exit(1)
+    if genome == "hang":
+        time.sleep(60)
+    if isinstance(genome, dict) and "crash_once" in genome:
+        if not os.path.exists(genome["crash_once"]):
+            open(genome["crash_once"], "w").close()
+            sys.exit(1)
+        return 0.0
+    return -sum(x * x for x in genome)
+
+
+for line in sys.stdin:
+    request = json.loads(line)
+    answer = {"id": request["id"], "fitness": [fitness(genome) for genome in request["genomes"]]}
+    print(json.dumps(answer), flush=True)
@@ -1,50 +1,87 @@
 extern crate revo;
 
 use revo::evo_individual::{EvoIndividual, Visualise};
-use revo::population::Population;
+use revo::population::{Population, VisualisationMode};
 use social_distance::social_distance::{DistanceIndividual, DistanceIndividualData};
 
-use revo::config::{Config, DEFAULT_CONFIG_FILENAME};
-use std::fs;
+use revo::animation::AnimationExporter;
+use revo::cli::{or_exit, CliArgs, HALL_OF_FAME_FILENAME};
 
 fn main() {
-    let config = Config::new(DEFAULT_CONFIG_FILENAME);
-    let output_dir = "./out";
-    let visualise = config.may_get_bool("visualise").unwrap().unwrap_or(false);
+    // Parse the command line and prepare the output directory, existing output is kept unless overwriting is allowed
+    let args = CliArgs::from_env();
+    or_exit(args.prepare_output_dir());
 
+    let config = or_exit(args.load_config());
+    let visualise = VisualisationMode::from_config(&config).unwrap().is_some();
+
+    // Resumed run is replayed up to its last logged generation
     let mut pop: Population<DistanceIndividual, DistanceIndividualData> = Population::new(&config);
+    let mut logger = or_exit(args.start_run(&config, &mut pop));
+    // Resumed run keeps the hall of fame exported by the interrupted run
+    or_exit(args.resume_hall_of_fame(&mut pop));
 
-    fs::create_dir(output_dir).unwrap();
+    // Population maps are streamed into a single animation, resumed run writes a new segment of it
+    let mut pop_exporter = if visualise {
+        Some(or_exit(AnimationExporter::from_config(
+            &config,
+            &args.animation_stem("population", pop.get_generation()),
+        )))
+    } else {
+        None
+    };
 
-    let mut all_best_ind = pop.get_best().clone();
-    for _ in 0..1000000 {
-        let best_ind = pop.get_best();
+    // Renders of the best individual are streamed into another animation
+    let mut best_exporter = if config.may_get_bool("export_best").unwrap().unwrap_or(false) {
+        Some(or_exit(AnimationExporter::from_config(
+            &config,
+            &args.animation_stem("best", pop.get_generation()),
+        )))
+    } else {
+        None
+    };
 
-        if best_ind.get_fitness() > all_best_ind.get_fitness() {
-            all_best_ind = best_ind.clone();
-            let img = all_best_ind.visualise(pop.get_individual_data());
-            img.save(format!("{}/best_{}.png", output_dir, pop.get_generation()).as_str())
+    while args.should_continue(pop.get_generation()) {
+        // Best individual ever seen is a new record if it entered the hall of fame in this generation
+        let hall_of_fame = pop.get_hall_of_fame();
+        if let Some(best) = hall_of_fame
+            .get_best()
+            .filter(|best| best.generation == pop.get_generation())
+        {
+            let img = best.individual.visualise(pop.get_individual_data());
+            img.save(args.output_path(&format!("best_{}.png", pop.get_generation())))
                 .unwrap();
 
-            println!(
+            args.info(&format!(
                 "Round {}, best fitness: {} - New record",
                 pop.get_generation(),
-                best_ind.get_fitness()
-            );
+                best.fitness
+            ));
         } else {
-            println!(
+            args.debug(&format!(
                 "Round {}, best fitness: {}",
                 pop.get_generation(),
-                best_ind.get_fitness()
-            );
+                pop.get_best().get_fitness()
+            ));
         }
 
-        if visualise {
-            let img = pop.visualise();
-            img.save(format!("{}/pop_{:05}.png", output_dir, pop.get_generation()).as_str())
-                .unwrap();
+        or_exit(logger.log(&pop));
+        if let Some(exporter) = &mut pop_exporter {
+            or_exit(exporter.add_population(&pop));
+        }
+        if let Some(exporter) = &mut best_exporter {
+            or_exit(exporter.add_best(&pop));
         }
 
         pop.next_gen();
     }
+
+    // Hall of fame is written first, so it is kept even if an animation fails
+    or_exit(pop.get_hall_of_fame().write_json(
+        &args.output_path(HALL_OF_FAME_FILENAME),
+        pop.get_individual_data(),
+    ));
+    for exporter in [pop_exporter, best_exporter].into_iter().flatten() {
+        or_exit(exporter.finish());
+    }
 }
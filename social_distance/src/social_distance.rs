@@ -4,8 +4,11 @@ use imageproc::rect::Rect;
 use rand::prelude::SmallRng;
 use rand::Rng;
 use revo::config::Config;
-use revo::evo_individual::{EvoIndividual, EvoIndividualData, Visualise};
+use revo::evo_individual::{
+    hash_value, EvoIndividual, EvoIndividualData, RemoteIndividual, Visualise,
+};
 use revo::utils::Coord;
+use serde_json::{json, Value};
 
 const DEFAULT_SCREEN_WIDTH: u32 = 400;
 const DEFAULT_SCREEN_HEIGHT: u32 = 400;
@@ -185,6 +188,12 @@ impl EvoIndividual<DistanceIndividualData> for DistanceIndividual {
         self.fitness
     }
 
+    // Genome is copied into the allocation of this individual
+    fn copy_from(&mut self, other: &Self) {
+        self.fitness = other.fitness;
+        self.coords.clone_from(&other.coords);
+    }
+
     fn get_visuals(&self, _ind_data: &DistanceIndividualData) -> (f64, f64) {
         let mut a: f64 = 0.0;
         let mut b: f64 = 0.0;
@@ -196,4 +205,82 @@ impl EvoIndividual<DistanceIndividualData> for DistanceIndividual {
 
         (a, b)
     }
+
+    // Points are unordered, so the distance is the mean distance of each point to the closest point of the other
+    // individual, averaged over both directions
+    fn distance(&self, other: &Self, _ind_data: &DistanceIndividualData) -> f64 {
+        (Self::_mean_closest_distance(&self.coords, &other.coords)
+            + Self::_mean_closest_distance(&other.coords, &self.coords))
+            / 2.0
+    }
+
+    // Individuals with the same points in a different order have the same hash
+    fn genome_hash(&self, _ind_data: &DistanceIndividualData) -> u64 {
+        let mut points: Vec<(i32, i32)> = self.coords.iter().map(|c| (c.x, c.y)).collect();
+        points.sort_unstable();
+        hash_value(&points)
+    }
+}
+
+// Genome is the array of the [x, y] points
+impl RemoteIndividual<DistanceIndividualData> for DistanceIndividual {
+    fn genome_to_json(&self) -> Value {
+        json!(self
+            .coords
+            .iter()
+            .map(|coord| [coord.x, coord.y])
+            .collect::<Vec<[i32; 2]>>())
+    }
+
+    fn from_genome_json(genome: &Value, ind_data: &DistanceIndividualData) -> Result<Self, String> {
+        let coord = |point: &Value| {
+            let x = point.get(0)?.as_i64()?;
+            let y = point.get(1)?.as_i64()?;
+            Some(Coord {
+                x: x as i32,
+                y: y as i32,
+            })
+        };
+        let coords = genome
+            .as_array()
+            .and_then(|points| points.iter().map(coord).collect::<Option<Vec<Coord>>>())
+            .ok_or_else(|| format!("Genome '{}' is not an array of points", genome))?;
+        if coords.len() != ind_data.n_points {
+            return Err(format!(
+                "Genome has {} points instead of {}",
+                coords.len(),
+                ind_data.n_points
+            ));
+        }
+
+        Ok(DistanceIndividual {
+            fitness: 0.0,
+            coords,
+        })
+    }
+
+    fn set_fitness(&mut self, fitness: f64) {
+        self.fitness = fitness;
+    }
+}
+
+impl DistanceIndividual {
+    fn _mean_closest_distance(from: &[Coord], to: &[Coord]) -> f64 {
+        if from.is_empty() || to.is_empty() {
+            return 0.0;
+        }
+
+        let sum: f64 = from
+            .iter()
+            .map(|point| {
+                to.iter()
+                    .map(|other| Coord::distance_euclid(point, other))
+                    .min()
+                    .unwrap() as f64
+            })
+            .map(f64::sqrt)
+            .sum();
+
+        sum / from.len() as f64
+    }
 }